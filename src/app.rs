@@ -1,152 +1,1828 @@
+use crate::batch::{self, JobOverrides};
+use crate::cancel::CancelToken;
+use crate::children;
+use crate::cleanup;
+use crate::dashboard::{self, Dashboard};
+use crate::dates::{self, CameraClock, DateFormat};
+use crate::deposit::{self, DepositSettings, Repository};
+use crate::dropping::{self, DropSummary};
+use crate::editor;
+use crate::ffmpeg;
+use crate::gaps::GapFilling;
+use crate::handoff::Handoff;
+use crate::health;
+use crate::hooks::{self, Hooks};
+use crate::import;
+use crate::integrity;
+use crate::joblog::JobLog;
+use crate::largeframe;
+use crate::launch;
+use crate::layout;
+use crate::locations::{self, LocationDefaults};
+use crate::logging::{self, LogLevel};
+use crate::notification;
+use crate::pipeline::{
+    self, Job, JobEvent, JobQueue, JobRun, JobStatus, QueueEvent, QueueState, Review,
+};
+use crate::preflight::{self, Validation};
+use crate::queue::{
+    self, HistoryEntry, HistoryQuery, JobRecord, Queue, Render, RenderMetrics, RenderSample,
+};
+use crate::report;
+use crate::runner::{
+    self, output_in, output_name, preview_paths, proxy_path, validate_name_template, video_path,
+    JobSettings, Outcome, Shortfalls, StageTimings, VideoName, DEFAULT_NAME_TEMPLATE,
+    NAME_PLACEHOLDERS,
+};
+use crate::schedule::{self, Cadence};
+use crate::script::{JobScript, ScriptResult};
+use crate::storage;
+use crate::tasks::TaskRuntime;
+use crate::throughput::{self, Throughput};
+use crate::transcode::{self, Container, TranscodeSettings};
+use crate::usage::{LoadMonitor, ThrottleSettings, UsageMonitor};
+use crate::utils;
+use crate::verify;
+use crate::video::{
+    self, AdaptiveEncode, Codec, CodecOptions, H264Profile, Preset, PreviewFormat, ProResProfile,
+    ProcessEnvironment, RateControl,
+};
+use crate::webhook;
 use images_to_video;
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 use tree_migration;
 
-fn build_video_config(
-    image_config: &tree_migration::Config,
-    ffmpeg_path: &PathBuf,
-    codec: images_to_video::Codec,
-    frame_rate: u32,
-    video_output_path: Option<PathBuf>,
-) -> Result<images_to_video::Config, images_to_video::utils::Error> {
-    let output_file_name = image_config.location.clone()
-        + "-"
-        + image_config.camera.as_str()
-        + "-"
-        + image_config.start_date.to_string().as_str()
-        + "-"
-        + image_config.end_date.to_string().as_str()
-        + ".mov";
-
-    images_to_video::build_config(
-        ffmpeg_path.display().to_string().as_str(),
-        image_config.output_path.display().to_string().as_str(),
-        video_output_path,
-        output_file_name.as_str(),
-        frame_rate,
-        codec,
-    )
+/// Height of an expanded job log in the table, in lines.
+const LOG_LINES: usize = 10;
+/// Height of the source image thumbnails of a job in the table, in lines.
+const PREVIEW_LINES: usize = 5;
+/// Size of the source image thumbnails of a job in pixels.
+const PREVIEW_PIXELS: u32 = 160;
+/// Most frames the player decodes. Longer videos are played with frames left out.
+const PLAYER_FRAMES: usize = 1500;
+
+static PLAYER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// First, middle and last source image of a job, scaled down.
+#[derive(Default)]
+pub enum SourcePreview {
+    #[default]
+    None,
+    Loading,
+    /// Loaded but not uploaded as textures yet, which needs the UI.
+    Loaded(Vec<(String, egui::ColorImage)>),
+    Shown(Vec<(String, egui::TextureHandle)>),
+    Failed(String),
+}
+
+fn site_name(location: &str, camera: &str) -> String {
+    format!("{}/{}", location, camera)
+}
+
+fn show_in_file_manager(path: &Path) {
+    if let Err(e) = launch::show_in_file_manager(path) {
+        log::error!("Error opening {} {}", path.display(), e);
+    }
+}
+
+/// Value plotted for a render.
+type Metric = fn(&RenderMetrics) -> f64;
+
+/// Result of the job script for every job it ran for.
+type ScriptResults = Vec<(PathBuf, Result<ScriptResult, String>)>;
+
+/// Plots the duration and throughput of past renders over time, per site and for all sites.
+fn history_plots_ui(ui: &mut egui::Ui, history: &[RenderSample]) {
+    let day = |sample: &RenderSample| sample.finished_at.timestamp() as f64 / 86400.0;
+    let mut sites: BTreeMap<String, Vec<&RenderSample>> = BTreeMap::new();
+    for sample in history {
+        sites
+            .entry(site_name(&sample.location, &sample.camera))
+            .or_default()
+            .push(sample);
+    }
+    let plots: [(&str, Metric); 3] = [
+        ("Duration (min)", |metrics| metrics.duration / 60.0),
+        ("Frames per second", |metrics| {
+            metrics.frames as f64 / metrics.duration.max(0.001)
+        }),
+        ("MB per second", |metrics| {
+            metrics.bytes as f64 / 1_000_000.0 / metrics.duration.max(0.001)
+        }),
+    ];
+    for (label, value) in plots {
+        ui.label(label);
+        egui_plot::Plot::new(label)
+            .height(120.0)
+            .legend(egui_plot::Legend::default())
+            .x_axis_formatter(|x, _, _| {
+                chrono::DateTime::from_timestamp((x * 86400.0) as i64, 0)
+                    .map_or(String::new(), |date| date.date_naive().to_string())
+            })
+            .label_formatter(|name, point| format!("{}\n{:.1}", name, point.y))
+            .show(ui, |plot_ui| {
+                plot_ui.points(
+                    egui_plot::Points::new(
+                        history
+                            .iter()
+                            .map(|sample| [day(sample), value(&sample.metrics)])
+                            .collect::<Vec<_>>(),
+                    )
+                    .radius(2.0)
+                    .color(egui::Color32::GRAY)
+                    .name("All sites"),
+                );
+                for (site, samples) in &sites {
+                    plot_ui.line(
+                        egui_plot::Line::new(
+                            samples
+                                .iter()
+                                .map(|sample| [day(sample), value(&sample.metrics)])
+                                .collect::<Vec<_>>(),
+                        )
+                        .name(site),
+                    );
+                }
+            });
+    }
+}
+
+fn codec_options_ui(ui: &mut egui::Ui, codec: Codec, options: &mut CodecOptions) {
+    if codec == Codec::None {
+        return;
+    }
+    egui::CollapsingHeader::new("Advanced codec options").show(ui, |ui| {
+        match codec {
+            Codec::H264 => {
+                egui::ComboBox::from_label("Profile")
+                    .selected_text(match options.h264_profile {
+                        H264Profile::Baseline => "Baseline",
+                        H264Profile::Main => "Main",
+                        H264Profile::High => "High",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut options.h264_profile,
+                            H264Profile::Baseline,
+                            "Baseline",
+                        );
+                        ui.selectable_value(&mut options.h264_profile, H264Profile::Main, "Main");
+                        ui.selectable_value(&mut options.h264_profile, H264Profile::High, "High");
+                    });
+                egui::ComboBox::from_label("Level")
+                    .selected_text(if options.h264_level.is_empty() {
+                        "Auto"
+                    } else {
+                        options.h264_level.as_str()
+                    })
+                    .show_ui(ui, |ui| {
+                        for level in video::H264_LEVELS {
+                            ui.selectable_value(
+                                &mut options.h264_level,
+                                level.to_owned(),
+                                if level.is_empty() { "Auto" } else { level },
+                            );
+                        }
+                    });
+                preset_ui(ui, &mut options.h264_preset);
+                rate_control_ui(ui, options, Codec::H264);
+                if options.rate_control == RateControl::Bitrate {
+                    ui.checkbox(&mut options.h264_two_pass, "Two-pass")
+                        .on_hover_text("Encode twice to spend the bitrate where the video needs it, which takes about twice as long");
+                }
+            }
+            Codec::H265 => {
+                preset_ui(ui, &mut options.hevc_preset);
+                rate_control_ui(ui, options, Codec::H265);
+                ui.checkbox(&mut options.hevc_apple_tag, "Apple compatible tag (hvc1)");
+                ui.checkbox(&mut options.hevc_ten_bit, "10-bit");
+            }
+            Codec::Vp9 => {
+                ui.add(egui::Slider::new(&mut options.vp9_crf, 0..=63).text("Quality (CRF)"))
+                    .on_hover_text("Lower is better quality and larger files");
+            }
+            Codec::Gif => {
+                ui.label("GIF has no options. It is limited to 256 colours per frame.");
+            }
+            Codec::ProRes => {
+                egui::ComboBox::from_label("Profile")
+                    .selected_text(match options.prores_profile {
+                        ProResProfile::Proxy => "Proxy",
+                        ProResProfile::Lt => "LT",
+                        ProResProfile::Standard => "Standard",
+                        ProResProfile::Hq => "HQ",
+                        ProResProfile::P4444 => "4444",
+                    })
+                    .show_ui(ui, |ui| {
+                        for (profile, label) in [
+                            (ProResProfile::Proxy, "Proxy"),
+                            (ProResProfile::Lt, "LT"),
+                            (ProResProfile::Standard, "Standard"),
+                            (ProResProfile::Hq, "HQ"),
+                            (ProResProfile::P4444, "4444"),
+                        ] {
+                            ui.selectable_value(&mut options.prores_profile, profile, label);
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut options.prores_vendor).desired_width(50.0),
+                    );
+                    ui.label("Vendor".to_owned());
+                });
+            }
+            Codec::None => {}
+        }
+        if let Err(e) = options.validate(codec) {
+            ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+        }
+    });
+    for warning in options.compatibility_warnings(codec) {
+        ui.label(egui::RichText::new(warning).color(egui::Color32::YELLOW));
+    }
+}
+
+fn deposit_settings_ui(ui: &mut egui::Ui, settings: &mut DepositSettings) {
+    egui::ComboBox::from_label("Repository")
+        .selected_text(settings.repository.label())
+        .show_ui(ui, |ui| {
+            for repository in [Repository::Zenodo, Repository::DSpace] {
+                ui.selectable_value(&mut settings.repository, repository, repository.label());
+            }
+        });
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut settings.url);
+        ui.label(match settings.repository {
+            Repository::Zenodo => "Zenodo URL",
+            Repository::DSpace => "SWORD collection URL",
+        });
+    });
+    if settings.repository == Repository::DSpace {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut settings.username);
+            ui.label("User name");
+        });
+    }
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut settings.token).password(true));
+        ui.label(match settings.repository {
+            Repository::Zenodo => "Access token",
+            Repository::DSpace => "Password",
+        })
+        .on_hover_text("Not saved with the settings, enter it again after restarting");
+    });
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut settings.creator);
+        ui.label("Creator");
+    });
+    if settings.repository == Repository::Zenodo {
+        ui.label("Depositions are left as drafts, publish them from the job once checked");
+    }
+    if let Err(e) = settings.validate() {
+        ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+    }
+}
+
+fn environment_ui(ui: &mut egui::Ui, environment: &mut ProcessEnvironment) {
+    egui::CollapsingHeader::new("Advanced job settings").show(ui, |ui| {
+        ui.label("Environment variables for ffmpeg and the hooks".to_owned())
+            .on_hover_text("Saved in plain text with the settings, point a variable to a credentials file rather than entering secrets");
+        let mut removed = None;
+        for (i, (name, value)) in environment.variables.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(name)
+                        .hint_text("Name")
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(value)
+                        .hint_text("Value")
+                        .desired_width(200.0),
+                );
+                if ui.button("✕").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            environment.variables.remove(i);
+        }
+        if ui.button("Add variable").clicked() {
+            environment.variables.push(Default::default());
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Select working directory").clicked() {
+                environment.working_dir = rfd::FileDialog::new().pick_folder();
+            }
+
+            if let Some(path) = &environment.working_dir {
+                ui.monospace(path.display().to_string());
+                if ui.button("Clear").clicked() {
+                    environment.working_dir = None;
+                }
+            } else {
+                ui.label("ffmpeg and the hooks run in the working directory of the app.".to_owned());
+            }
+        });
+    });
+}
+
+/// Chooses between constant quality and an average bitrate for h.264 and HEVC.
+fn rate_control_ui(ui: &mut egui::Ui, options: &mut CodecOptions, codec: Codec) {
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut options.rate_control, RateControl::Quality, "Quality");
+        ui.radio_value(&mut options.rate_control, RateControl::Bitrate, "Bitrate");
+    });
+    match options.rate_control {
+        RateControl::Quality => {
+            let crf = if codec == Codec::H265 {
+                &mut options.hevc_crf
+            } else {
+                &mut options.h264_crf
+            };
+            ui.add(egui::Slider::new(crf, 0..=51).text("Quality (CRF)"))
+                .on_hover_text("Lower is better quality and larger files");
+        }
+        RateControl::Bitrate => {
+            ui.add(
+                egui::DragValue::new(&mut options.bitrate)
+                    .clamp_range(100..=200_000)
+                    .suffix(" kbit/s"),
+            );
+        }
+    }
 }
+
+fn preset_ui(ui: &mut egui::Ui, preset: &mut Preset) {
+    egui::ComboBox::from_label("Preset")
+        .selected_text(preset.name())
+        .show_ui(ui, |ui| {
+            for value in Preset::ALL {
+                ui.selectable_value(preset, value, value.name());
+            }
+        });
+}
+
 pub enum Signal {
-    Success(PathBuf),
-    Error((PathBuf, tree_migration::Error)),
-}
-
-#[derive(PartialEq)]
-pub enum AppState {
-    Init,
-    InvalidConfigs,
-    ValidConfigs,
-    Processing,
-    ProcessingDone,
-    ProcessingErrors,
-}
-
-#[derive(PartialEq)]
-pub enum ItemState {
-    InvalidConfig,
-    ValidConfig,
-    Processing,
-    ProcessingDone,
-    ProcessingError,
-    Unkown,
-}
-
-fn item_state(
-    app_state: &AppState,
-    config: &Result<tree_migration::Config, tree_migration::Error>,
-    done: &Option<Result<(), tree_migration::Error>>,
-) -> ItemState {
-    if done.as_ref().is_some_and(|d| d.is_ok()) {
-        return ItemState::ProcessingDone;
-    } else if done.as_ref().is_some_and(|d| d.is_err()) {
-        return ItemState::ProcessingError;
-    } else if config.is_ok() && done.is_none() && app_state == &AppState::Processing {
-        return ItemState::Processing;
-    } else if config.is_ok() {
-        return ItemState::ValidConfig;
-    } else if config.is_err() {
-        return ItemState::InvalidConfig;
-    }
-    ItemState::Unkown
+    Job((PathBuf, JobEvent)),
+    Verified((PathBuf, Vec<String>)),
+    Validated((PathBuf, Validation)),
+    CleanedUp((PathBuf, std::io::Result<()>)),
+    Imported(std::io::Result<import::ImportSummary>),
+    DatesDetected((PathBuf, Result<dates::DateRange, String>)),
+    Transcoded((PathBuf, Result<PathBuf, String>)),
+    HealthChecked((Option<String>, Vec<String>)),
+    FrameLoaded((PathBuf, Result<egui::ColorImage, String>)),
+    /// Folder of the decoded frames, every how many frames of the video were decoded and the
+    /// frames.
+    PlayerFramesExtracted((PathBuf, usize, Result<Vec<PathBuf>, String>)),
+    SourcesPreviewed((PathBuf, Result<Vec<(String, egui::ColorImage)>, String>)),
+    SourcesCounted((PathBuf, usize)),
+    FfmpegDownloaded(Result<PathBuf, String>),
+    /// What the job script changed about each job, with the error loading the script.
+    ScriptApplied((Option<String>, ScriptResults)),
+    Published((PathBuf, Result<String, String>)),
+    BatchChecked((Vec<String>, Vec<(PathBuf, u32, String)>)),
+}
+
+#[derive(PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Mode {
+    Migration,
+    Transcode,
+}
+
+/// What happens to jobs that cannot be rendered with video although video is enabled.
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum MissingVideo {
+    /// The batch does not start.
+    Block,
+    /// The jobs run without video and are marked as such.
+    Skip,
+}
+
+/// What happens when the outputs of a batch are estimated not to fit on their disks.
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum DiskSpaceCheck {
+    /// Free space is not checked.
+    Off,
+    /// The batch starts with a warning.
+    Warn,
+    /// The batch does not start unless started anyway.
+    Refuse,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum SortColumn {
+    Path,
+    Status,
+}
+
+/// Status chip above the table narrowing it to the jobs in that state.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusFilter {
+    Valid,
+    Invalid,
+    Done,
+    Error,
+}
+
+impl StatusFilter {
+    pub const ALL: [StatusFilter; 4] = [
+        StatusFilter::Valid,
+        StatusFilter::Invalid,
+        StatusFilter::Done,
+        StatusFilter::Error,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatusFilter::Valid => "Valid",
+            StatusFilter::Invalid => "Invalid",
+            StatusFilter::Done => "Done",
+            StatusFilter::Error => "Error",
+        }
+    }
+
+    pub fn matches(&self, item_state: &JobStatus) -> bool {
+        match self {
+            StatusFilter::Valid => item_state == &JobStatus::ValidConfig,
+            StatusFilter::Invalid => item_state == &JobStatus::InvalidConfig,
+            StatusFilter::Done => item_state.is_done(),
+            StatusFilter::Error => item_state == &JobStatus::ProcessingError,
+        }
+    }
+}
+
+fn item_state(app_state: &QueueState, file: &FileEntry) -> JobStatus {
+    file.job().status(app_state)
+}
+
+/// Actions that discard a running batch and may require the session lock phrase.
+#[derive(Clone)]
+pub enum LockedAction {
+    Clear,
+    Cancel(PathBuf),
+    CancelAll,
+}
+
+pub enum Cleanup {
+    Delete,
+    Quarantine,
+}
+
+/// A job whose outputs are about to be cleaned up, with the files that will be removed.
+pub struct PendingCleanup {
+    pub path: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub is_confirmed: bool,
+}
+
+pub struct FileEntry {
+    pub config: Result<tree_migration::Config, tree_migration::Error>,
+    pub done: Option<Result<(), tree_migration::Error>>,
+    pub verification: Option<Vec<String>>,
+    /// Findings of the last "Validate only" run.
+    pub validation: Option<Validation>,
+    pub cleanup_error: Option<String>,
+    pub detected_dates: Option<Result<dates::DateRange, String>>,
+    pub date_ranges: Vec<dates::DateRange>,
+    /// Output path from the config file while the job writes somewhere else.
+    pub default_output_path: Option<PathBuf>,
+    /// Error of a run recorded in the queue database before the app was restarted.
+    pub previous_error: Option<String>,
+    /// Set while the job runs.
+    pub cancel: Option<CancelToken>,
+    pub is_cancelled: bool,
+    /// Set while the job waits for a free slot.
+    pub is_queued: bool,
+    /// Latest progress of a running job, from 0 to 1.
+    pub progress: Option<f32>,
+    pub throughput: Throughput,
+    /// Number of source images of the running job.
+    pub source_count: Option<usize>,
+    /// Encode settings picked from the resolution of the source images of the last run.
+    pub adaptive_encode: Option<AdaptiveEncode>,
+    /// Time taken by the stages of the last run.
+    pub stage_timings: StageTimings,
+    /// Panic message of a job that crashed.
+    pub crash: Option<String>,
+    /// Error of a stage after the migration that failed the job, e.g. the video encode.
+    pub stage_error: Option<String>,
+    /// Video written by the last run, where it was written whatever the settings are now.
+    pub video: Option<PathBuf>,
+    /// DOI or handle of the archive deposited by the last run.
+    pub deposit_id: Option<String>,
+    /// Id of the Zenodo deposition of the last run while it is an unpublished draft.
+    pub deposit_draft: Option<u64>,
+    pub is_publishing: bool,
+    /// What the last run finished without delivering.
+    pub shortfalls: Shortfalls,
+    /// Errors of the last run and the output of its ffmpeg invocations.
+    pub log: JobLog,
+    /// ffmpeg binary the job uses instead of the one in the settings.
+    pub ffmpeg_path: Option<PathBuf>,
+    /// Settings from the batch file the job was loaded from or from the job script.
+    pub overrides: JobOverrides,
+    /// Why the last run was rendered without video although video is enabled.
+    pub video_skipped: Option<&'static str>,
+    /// Other jobs covering the same days of the same site or writing to the same folder.
+    pub conflicts: Vec<JobConflict>,
+    /// See `VideoName::collision`.
+    pub video_name_collision: usize,
+    /// Keeps `video_name_collision` once the job started, so that its video keeps its name when
+    /// jobs are added or removed or other videos appear on disk.
+    pub is_video_name_collision_fixed: bool,
+    /// Lets the job run despite its conflicts.
+    pub is_conflict_ignored: bool,
+    /// Output frames flagged in the QC scrubber.
+    pub flagged_frames: BTreeSet<PathBuf>,
+    pub source_preview: SourcePreview,
+    /// Output frames left out of the video since the last render.
+    pub excluded_frames: BTreeSet<PathBuf>,
+    /// Proxy rendered by the last run in review mode.
+    pub proxy: Option<PathBuf>,
+    pub review: Review,
+}
+
+impl FileEntry {
+    /// Returns the dates the job processes, spanning all of its date ranges.
+    pub fn date_range(&self) -> Option<dates::DateRange> {
+        match (
+            self.date_ranges.first(),
+            self.date_ranges.last(),
+            &self.config,
+        ) {
+            (Some(first), Some(last), _) => Some((first.0, last.1)),
+            (_, _, Ok(config)) => Some((config.start_date, config.end_date)),
+            _ => None,
+        }
+    }
+
+    /// Describes how much real time one second of the video of the job covers, once its source
+    /// images are counted.
+    pub fn speed(&self, frame_rate: u32, target_duration: Option<u32>) -> Option<String> {
+        let (count, (start_date, end_date)) = (self.source_count?, self.date_range()?);
+        let frame_rate = match target_duration {
+            Some(seconds) => count as f64 / seconds.max(1) as f64,
+            None => self.overrides.frame_rate.unwrap_or(frame_rate) as f64,
+        };
+        video::describe_speed((end_date - start_date).num_days() + 1, count, frame_rate)
+    }
+
+    /// Returns the error of the config, of the last run or of a previous run, if any.
+    pub fn error_message(&self) -> String {
+        match (&self.config, &self.done) {
+            (Err(e), _) | (_, Some(Err(e))) => e.to_string(),
+            _ => match (&self.crash, &self.stage_error) {
+                (Some(crash), _) => format!("Crashed: {}", crash),
+                (None, Some(error)) => error.clone(),
+                (None, None) => self.previous_error.clone().unwrap_or_default(),
+            },
+        }
+    }
+
+    pub fn new(config: Result<tree_migration::Config, tree_migration::Error>) -> Self {
+        Self {
+            config,
+            done: None,
+            verification: None,
+            validation: None,
+            cleanup_error: None,
+            detected_dates: None,
+            date_ranges: Vec::new(),
+            default_output_path: None,
+            previous_error: None,
+            cancel: None,
+            is_cancelled: false,
+            is_queued: false,
+            progress: None,
+            throughput: Throughput::default(),
+            source_count: None,
+            adaptive_encode: None,
+            stage_timings: StageTimings::new(),
+            crash: None,
+            stage_error: None,
+            video: None,
+            deposit_id: None,
+            deposit_draft: None,
+            is_publishing: false,
+            shortfalls: Shortfalls::default(),
+            log: JobLog::default(),
+            ffmpeg_path: None,
+            overrides: JobOverrides::default(),
+            video_skipped: None,
+            conflicts: Vec::new(),
+            video_name_collision: 0,
+            is_video_name_collision_fixed: false,
+            is_conflict_ignored: false,
+            flagged_frames: BTreeSet::new(),
+            source_preview: SourcePreview::None,
+            excluded_frames: BTreeSet::new(),
+            proxy: None,
+            review: Review::None,
+        }
+    }
+
+    /// Returns what the status of the job is derived from.
+    pub fn job(&self) -> Job<'_> {
+        Job {
+            is_config_valid: self.config.is_ok(),
+            result: self.done.as_ref().map(|done| done.is_ok()),
+            has_failed_stage: self.crash.is_some() || self.stage_error.is_some(),
+            is_cancelled: self.is_cancelled,
+            is_queued: self.is_queued,
+            has_conflicts: self.conflicts.iter().any(|conflict| !conflict.is_ignored),
+            review: self.review,
+            shortfalls: &self.shortfalls,
+        }
+    }
+
+    /// Restores a job from the queue database, reloading its config file.
+    pub fn restore(record: &JobRecord) -> Self {
+        let mut file = FileEntry::new(tree_migration::Config::from(&record.path));
+        file.date_ranges = record.date_ranges.clone();
+        file.override_output_path(record.output_override.clone());
+        file.ffmpeg_path = record.ffmpeg_path.clone();
+        file.overrides = record.overrides.clone();
+        file.video = record.video.clone();
+        if let Some(collision) = record.video_name_collision {
+            file.video_name_collision = collision;
+            file.is_video_name_collision_fixed = true;
+        }
+        file.review = record.review;
+        file.proxy = record.proxy.clone();
+        file.flagged_frames = record.flagged_frames.clone();
+        file.excluded_frames = record.excluded_frames.clone();
+        match record.status {
+            queue::RecordStatus::Queued => {}
+            queue::RecordStatus::Processing => {
+                file.previous_error = Some("Interrupted before finishing".to_owned())
+            }
+            queue::RecordStatus::Done => file.done = Some(Ok(())),
+            queue::RecordStatus::Error => {
+                file.previous_error = Some(record.error.clone().unwrap_or_default())
+            }
+        }
+        file
+    }
+
+    /// Returns the video written by the last run if it still exists.
+    pub fn video(&self) -> Option<&Path> {
+        self.video.as_deref().filter(|video| video.exists())
+    }
+
+    /// Makes the job use the ffmpeg binary at `path` if it passes the same check as the one in
+    /// the settings, logging why otherwise.
+    pub fn select_ffmpeg(&mut self, path: &Path) {
+        match images_to_video::utils::ffmpeg_path(path.display().to_string().as_str()) {
+            Ok(ffmpeg_path) => self.ffmpeg_path = Some(ffmpeg_path),
+            Err(e) => self
+                .log
+                .line(format!("Error selecting ffmpeg {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Loads the config file of the job at `path` again after it was changed, keeping the
+    /// settings of the job that do not come from the config.
+    pub fn reload(&mut self, path: &Path) {
+        let output_override = match (&self.config, &self.default_output_path) {
+            (Ok(config), Some(_)) => Some(config.output_path.clone()),
+            _ => None,
+        };
+        let mut file = FileEntry::new(tree_migration::Config::from(path));
+        file.override_output_path(output_override);
+        file.ffmpeg_path = self.ffmpeg_path.take();
+        file.overrides = std::mem::take(&mut self.overrides);
+        file.flagged_frames = std::mem::take(&mut self.flagged_frames);
+        file.excluded_frames = std::mem::take(&mut self.excluded_frames);
+        *self = file;
+    }
+
+    /// Points the job at `output_path` instead of the config's, or back to the config's when `None`.
+    pub fn override_output_path(&mut self, output_path: Option<PathBuf>) {
+        let Ok(config) = &mut self.config else {
+            return;
+        };
+        let default_output_path = self
+            .default_output_path
+            .take()
+            .unwrap_or_else(|| config.output_path.clone());
+        match output_path {
+            Some(output_path) => {
+                config.output_path = output_path;
+                self.default_output_path = Some(default_output_path);
+            }
+            None => config.output_path = default_output_path,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TranscodeEntry {
+    pub is_running: bool,
+    pub done: Option<Result<PathBuf, String>>,
+}
+
+#[derive(Default)]
+pub struct ImportWindow {
+    pub is_open: bool,
+    pub is_importing: bool,
+    pub card_path: Option<PathBuf>,
+    pub archive_path: Option<PathBuf>,
+    pub location: String,
+    pub result: Option<Result<String, String>>,
+}
+
+#[derive(Default)]
+pub struct ConfigEditorWindow {
+    pub path: Option<PathBuf>,
+    pub contents: editor::Contents,
+    pub validation: Option<Result<(), String>>,
+    /// Time of the last edit not validated yet.
+    pub edited_at: Option<Instant>,
+}
+
+/// Another job that would race a job on the same outputs.
+pub struct JobConflict {
+    pub path: PathBuf,
+    pub reason: &'static str,
+    /// Set if the conflicts of either job are ignored.
+    pub is_ignored: bool,
+}
+
+/// Search fields of the history panel.
+#[derive(Default)]
+pub struct HistorySearch {
+    pub location: String,
+    pub camera: String,
+    pub date: String,
+    pub error: Option<String>,
+}
+
+/// Steps through the output frames of a job to flag the ones that fail quality control.
+#[derive(Default)]
+pub struct ScrubberWindow {
+    pub path: Option<PathBuf>,
+    pub frames: Vec<PathBuf>,
+    pub index: usize,
+    /// Frame shown and its texture.
+    pub texture: Option<(PathBuf, egui::TextureHandle)>,
+    /// Frame being loaded in the background.
+    pub loading: Option<PathBuf>,
+    /// Frame loaded in the background, waiting to be uploaded as a texture.
+    pub loaded: Option<(PathBuf, egui::ColorImage)>,
+}
+
+/// Plays the video of a job from its frames, decoded into a temporary folder.
+#[derive(Default)]
+pub struct PlayerWindow {
+    pub video: Option<PathBuf>,
+    /// Folder of the decoded frames, removed when the window closes.
+    pub dir: Option<PathBuf>,
+    /// Stops decoding when the window closes.
+    pub cancel: Option<CancelToken>,
+    /// Decoded frames, once ffmpeg is done.
+    pub frames: Option<Result<Vec<PathBuf>, String>>,
+    pub frame_rate: f64,
+    /// Length the video was fitted to, which sets the frame rate instead.
+    pub target_duration: Option<u32>,
+    pub index: usize,
+    pub is_playing: bool,
+    /// When the shown frame came up while playing.
+    pub shown_at: Option<Instant>,
+    pub texture: Option<(usize, egui::TextureHandle)>,
+}
+
+/// Video settings of a single job, where `None` keeps the global setting.
+#[derive(Default)]
+pub struct OverridesWindow {
+    pub path: Option<PathBuf>,
+    pub video_codec: Option<Codec>,
+    pub frame_rate: Option<u32>,
+    /// Camera clock as typed, parsed when saved.
+    pub camera_clock: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct DateRangesWindow {
+    pub path: Option<PathBuf>,
+    pub ranges: Vec<(String, String)>,
+    pub error: Option<String>,
+}
+
+/// Named output settings to switch between, e.g. a quick preview and an archive render.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct SettingsPreset {
+    pub name: String,
+    pub is_forest_green_enabled: bool,
+    pub is_video_enabled: bool,
+    pub video_codec: Codec,
+    pub frame_rate: u32,
+    pub video_output_path: Option<PathBuf>,
+    /// Overrides the ffmpeg binary in the settings when set.
+    #[serde(default)]
+    pub ffmpeg_path: Option<PathBuf>,
 }
+
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct MigrationApp {
+    pub mode: Mode,
     pub is_forest_green_enabled: bool,
     pub is_video_enabled: bool,
-    pub video_codec: images_to_video::Codec,
+    pub missing_video: MissingVideo,
+    pub is_date_overlay_enabled: bool,
+    pub is_title_slate_enabled: bool,
+    /// Orders the frames of videos by their EXIF capture time instead of their names.
+    pub is_capture_order_enabled: bool,
+    pub is_video_failure_fatal: bool,
+    /// Renders a proxy of every job first and the full video only once it is approved.
+    pub is_review_enabled: bool,
+    pub video_codec: Codec,
+    pub codec_options: CodecOptions,
+    /// Picks the level, bitrate and scaling of h.264 and HEVC per job from the resolution of
+    /// its source images.
+    pub is_adaptive_encoding_enabled: bool,
+    pub ffmpeg_environment: ProcessEnvironment,
     pub ffmpeg_path: Option<PathBuf>,
     pub video_output_path: Option<PathBuf>,
+    /// File name of videos, see `NAME_PLACEHOLDERS`.
+    pub video_name_template: String,
+    pub date_format: DateFormat,
     pub frame_rate: u32,
+    /// Number of jobs that run at the same time.
+    pub max_parallel_jobs: usize,
+    pub throttle: ThrottleSettings,
+    #[serde(skip)]
+    pub load: LoadMonitor,
+    /// Why fewer jobs run at once than `max_parallel_jobs`, while the batch is throttled.
+    #[serde(skip)]
+    pub throttle_reason: Option<String>,
+    pub presets: Vec<SettingsPreset>,
+    #[serde(skip)]
+    pub preset_name: String,
+    pub target_duration: Option<u32>,
+    pub gap_filling: GapFilling,
+    pub is_preview_enabled: bool,
+    pub preview_format: PreviewFormat,
+    pub is_sequence_export_enabled: bool,
+    pub sequence_export_path: Option<PathBuf>,
+    pub is_archive_enabled: bool,
+    pub archive_output_path: Option<PathBuf>,
+    pub is_deposit_enabled: bool,
+    pub deposit_settings: DepositSettings,
+    pub is_manifest_enabled: bool,
+    pub is_source_quarantine_enabled: bool,
+    pub disk_space_check: DiskSpaceCheck,
+    /// Commands run after every job, e.g. to upload its outputs.
+    pub hooks: Hooks,
+    /// Receives a JSON summary of every finished job and batch, unless empty.
+    pub webhook_url: String,
+    #[serde(skip)]
+    is_checking_batch: bool,
+    /// Whether the running batch check is for a scheduled start.
+    #[serde(skip)]
+    is_scheduled_check: bool,
+    /// Disks that the outputs of the batch are estimated not to fit on, kept only when
+    /// `disk_space_check` refuses to start the batch because of them.
+    #[serde(skip)]
+    space_problems: Vec<String>,
+    /// Jobs whose video would be absurdly short or long, with a suggested frame rate.
+    #[serde(skip)]
+    duration_warnings: Vec<(PathBuf, u32, String)>,
+    pub is_profiling_enabled: bool,
+    /// Serves a read-only status page of the queue on the local network.
+    pub is_dashboard_enabled: bool,
+    pub is_notification_enabled: bool,
+    pub is_notification_sound_enabled: bool,
+    /// Failed jobs in a row after which the queued jobs are cancelled and an alert is sent.
+    pub max_consecutive_failures: Option<u32>,
+    /// Jobs that failed since the last one that succeeded, in the running batch.
+    #[serde(skip)]
+    consecutive_failures: u32,
+    pub dashboard_port: u16,
+    #[serde(skip)]
+    pub dashboard: Option<Dashboard>,
+    #[serde(skip)]
+    pub dashboard_error: Option<String>,
+    pub is_xmp_enabled: bool,
+    pub is_layout_enabled: bool,
+    pub layout_root: Option<PathBuf>,
+    pub layout_template: String,
+    /// Runs a Rhai script on every job when the batch starts.
+    pub is_script_enabled: bool,
+    pub script_path: Option<PathBuf>,
     #[serde(skip)]
-    pub state: AppState,
+    pub script_error: Option<String>,
+    pub open_with_path: Option<PathBuf>,
+    pub is_map_enabled: bool,
+    pub locations_path: Option<PathBuf>,
+    #[serde(skip)]
+    pub locations: HashMap<String, [f64; 2]>,
+    pub location_defaults_path: Option<PathBuf>,
+    #[serde(skip)]
+    pub location_defaults: HashMap<String, LocationDefaults>,
+    #[serde(skip)]
+    pub location_defaults_error: Option<String>,
+    pub transcode_settings: TranscodeSettings,
+    #[serde(skip)]
+    pub state: QueueState,
+    /// Time of day the batch was last scheduled to start at, as typed.
+    pub scheduled_time: String,
+    /// When the scheduled batch starts, while the state is `QueueState::Scheduled`.
+    #[serde(skip)]
+    scheduled_start: Option<chrono::NaiveDateTime>,
     #[serde(skip)]
     pub channel: (mpsc::Sender<Signal>, mpsc::Receiver<Signal>),
     #[serde(skip)]
-    pub dropped_files: HashMap<
-        PathBuf,
-        (
-            Result<tree_migration::Config, tree_migration::Error>,
-            Option<Result<(), tree_migration::Error>>,
-        ),
-    >,
+    pub dropped_files: BTreeMap<PathBuf, FileEntry>,
+    #[serde(skip)]
+    pub pending_cleanup: Option<PendingCleanup>,
+    #[serde(skip)]
+    pub import_window: ImportWindow,
+    #[serde(skip)]
+    pub date_ranges_window: DateRangesWindow,
+    #[serde(skip)]
+    pub overrides_window: OverridesWindow,
+    #[serde(skip)]
+    pub scrubber: ScrubberWindow,
+    #[serde(skip)]
+    pub player: PlayerWindow,
+    #[serde(skip)]
+    pub config_editor: ConfigEditorWindow,
+    #[serde(skip)]
+    pub transcode_files: HashMap<PathBuf, TranscodeEntry>,
+    #[serde(skip)]
+    pub queue: Option<Queue>,
+    pub site_cadences: BTreeMap<String, Cadence>,
+    #[serde(skip)]
+    pub renders: Vec<Render>,
+    #[serde(skip)]
+    pub render_history: Vec<RenderSample>,
+    #[serde(skip)]
+    pub is_schedule_open: bool,
+    #[serde(skip)]
+    pub is_history_open: bool,
+    #[serde(skip)]
+    pub history_search: HistorySearch,
+    /// Finished jobs matching `history_search`.
+    #[serde(skip)]
+    pub history: Vec<HistoryEntry>,
+    /// Jobs of the previous session waiting for the user to restore or discard them.
+    #[serde(skip)]
+    pub previous_session: Vec<JobRecord>,
+    /// Jobs whose log is shown in the table.
+    #[serde(skip)]
+    pub expanded_logs: HashSet<PathBuf>,
+    /// Jobs whose source image thumbnails are shown in the table.
+    #[serde(skip)]
+    pub expanded_previews: HashSet<PathBuf>,
+    pub sort_column: SortColumn,
+    pub is_sort_descending: bool,
+    /// Text the path or site of the shown jobs contains.
+    #[serde(skip)]
+    pub table_filter: String,
+    /// Statuses of the shown jobs, all when empty.
+    #[serde(skip)]
+    pub status_filters: HashSet<StatusFilter>,
+    /// ffmpeg version and problems found by the startup check, `None` while it runs.
+    #[serde(skip)]
+    pub health: Option<(Option<String>, Vec<String>)>,
+    #[serde(skip)]
+    pub is_safe_mode: bool,
+    /// Whether settings are saved on exit in safe mode, replacing the persisted ones.
+    #[serde(skip)]
+    pub is_settings_reset: bool,
+    #[serde(skip)]
+    pub safe_mode_message: Option<String>,
+    /// Custom folder for settings and the queue database, `None` for eframe's storage folder.
+    #[serde(skip)]
+    pub data_dir: Option<PathBuf>,
+    #[serde(skip)]
+    pub data_dir_message: Option<String>,
+    pub temp_path: Option<PathBuf>,
+    pub log_level: LogLevel,
+    /// Log file of this run.
+    #[serde(skip)]
+    log_path: Option<PathBuf>,
+    pub is_session_lock_enabled: bool,
+    pub session_lock_phrase: String,
+    #[serde(skip)]
+    pub locked_action: Option<LockedAction>,
+    #[serde(skip)]
+    pub session_lock_input: String,
+    #[serde(skip)]
+    pub drop_message: Option<String>,
+    #[serde(skip)]
+    pub usage: UsageMonitor,
+    #[serde(skip)]
+    batch_throughput: Throughput,
+    #[serde(skip)]
+    pending_jobs: JobQueue<JobRun>,
+    #[serde(skip)]
+    tasks: TaskRuntime,
+    #[serde(skip)]
+    is_ffmpeg_downloading: bool,
+    #[serde(skip)]
+    ffmpeg_error: Option<String>,
 }
 
 impl Default for MigrationApp {
     fn default() -> Self {
         Self {
+            mode: Mode::Migration,
             is_forest_green_enabled: false,
             is_video_enabled: false,
-            video_codec: images_to_video::Codec::None,
+            missing_video: MissingVideo::Block,
+            is_date_overlay_enabled: false,
+            is_capture_order_enabled: false,
+            is_title_slate_enabled: false,
+            is_video_failure_fatal: true,
+            is_review_enabled: false,
+            video_codec: Codec::None,
+            codec_options: Default::default(),
+            is_adaptive_encoding_enabled: false,
+            ffmpeg_environment: Default::default(),
             ffmpeg_path: None,
             video_output_path: None,
+            video_name_template: DEFAULT_NAME_TEMPLATE.to_owned(),
+            date_format: DateFormat::Iso,
             frame_rate: 4,
-            state: AppState::Init,
+            max_parallel_jobs: 2,
+            throttle: Default::default(),
+            load: Default::default(),
+            throttle_reason: None,
+            presets: Vec::new(),
+            preset_name: String::new(),
+            target_duration: None,
+            gap_filling: GapFilling::Skip,
+            is_preview_enabled: false,
+            preview_format: PreviewFormat::Gif,
+            is_sequence_export_enabled: false,
+            sequence_export_path: None,
+            is_archive_enabled: false,
+            archive_output_path: None,
+            is_deposit_enabled: false,
+            deposit_settings: Default::default(),
+            is_manifest_enabled: false,
+            is_source_quarantine_enabled: false,
+            disk_space_check: DiskSpaceCheck::Warn,
+            hooks: Hooks::default(),
+            webhook_url: String::new(),
+            is_checking_batch: false,
+            is_scheduled_check: false,
+            space_problems: Vec::new(),
+            duration_warnings: Vec::new(),
+            is_profiling_enabled: false,
+            is_dashboard_enabled: false,
+            is_notification_enabled: false,
+            is_notification_sound_enabled: false,
+            max_consecutive_failures: None,
+            consecutive_failures: 0,
+            dashboard_port: 8080,
+            dashboard: None,
+            dashboard_error: None,
+            is_xmp_enabled: false,
+            is_layout_enabled: false,
+            layout_root: None,
+            layout_template: layout::DEFAULT_TEMPLATE.to_owned(),
+            is_script_enabled: false,
+            script_path: None,
+            script_error: None,
+            open_with_path: None,
+            is_map_enabled: false,
+            locations_path: None,
+            locations: HashMap::new(),
+            location_defaults_path: None,
+            location_defaults: HashMap::new(),
+            location_defaults_error: None,
+            transcode_settings: Default::default(),
+            state: QueueState::Init,
+            scheduled_time: "22:00".to_owned(),
+            scheduled_start: None,
             channel: mpsc::channel::<Signal>(),
-            dropped_files: HashMap::new(),
+            dropped_files: BTreeMap::new(),
+            pending_cleanup: None,
+            import_window: Default::default(),
+            date_ranges_window: Default::default(),
+            overrides_window: Default::default(),
+            scrubber: Default::default(),
+            player: Default::default(),
+            config_editor: Default::default(),
+            transcode_files: HashMap::new(),
+            queue: None,
+            site_cadences: BTreeMap::new(),
+            renders: Vec::new(),
+            render_history: Vec::new(),
+            is_schedule_open: false,
+            is_history_open: false,
+            history_search: Default::default(),
+            history: Vec::new(),
+            previous_session: Vec::new(),
+            expanded_logs: HashSet::new(),
+            expanded_previews: HashSet::new(),
+            sort_column: SortColumn::Path,
+            is_sort_descending: false,
+            table_filter: String::new(),
+            status_filters: HashSet::new(),
+            health: None,
+            is_safe_mode: false,
+            is_settings_reset: false,
+            safe_mode_message: None,
+            data_dir: None,
+            data_dir_message: None,
+            temp_path: None,
+            log_level: LogLevel::Info,
+            log_path: None,
+            is_session_lock_enabled: false,
+            session_lock_phrase: "stop the batch".to_owned(),
+            locked_action: None,
+            session_lock_input: String::new(),
+            drop_message: None,
+            usage: UsageMonitor::default(),
+            batch_throughput: Throughput::default(),
+            pending_jobs: JobQueue::default(),
+            tasks: TaskRuntime::default(),
+            is_ffmpeg_downloading: false,
+            ffmpeg_error: None,
         }
     }
 }
 
 impl MigrationApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            let mut app: MigrationApp =
-                eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            if let Some(path) = &app.ffmpeg_path {
-                if !path.exists() {
-                    app.ffmpeg_path = None;
-                }
-            }
+    pub fn new(cc: &eframe::CreationContext<'_>, options: crate::LaunchOptions) -> Self {
+        let data_dir = options.data_dir.or_else(storage::custom_data_dir);
+        if options.is_safe_mode {
+            let mut app = Self {
+                is_safe_mode: true,
+                data_dir,
+                ..Default::default()
+            };
+            app.log_path = logging::init(app.app_data_dir().as_deref(), app.log_level);
+            app.check_health();
             return app;
         }
 
-        Default::default()
+        let mut app: MigrationApp = match &data_dir {
+            Some(data_dir) => storage::load_settings(data_dir),
+            None => cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY)),
+        }
+        .unwrap_or_default();
+        app.data_dir = data_dir;
+        app.log_path = logging::init(app.app_data_dir().as_deref(), app.log_level);
+        if options.temp_dir.is_some() {
+            app.temp_path = options.temp_dir;
+        }
+        if let Some(path) = &app.ffmpeg_path {
+            if !path.exists() {
+                app.ffmpeg_path = None;
+            }
+        }
+        if app.ffmpeg_path.is_none() {
+            app.ffmpeg_path = ffmpeg::detect(app.app_data_dir().as_deref());
+        }
+        if let Some(path) = &app.locations_path {
+            app.locations = locations::load(path).unwrap_or_default();
+        }
+        if let Some(path) = app.location_defaults_path.clone() {
+            app.load_location_defaults(path);
+        }
+        app.open_queue();
+        if let Some(path) = &options.batch_path {
+            if let Err(e) = app.add_batch(path) {
+                app.drop_message = Some(e);
+            }
+        }
+        app.check_health();
+        app
     }
 
-    pub fn build_settings_view(&mut self, ctx: &egui::Context) {
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            ui.add_space(10.0);
-
-            ui.checkbox(&mut self.is_forest_green_enabled, "Forest Green")
-                .on_hover_text("Check to enable forest green");
+    /// Empties the persisted queue, deleting the database if it cannot be opened.
+    /// Finished renders are kept if the database is readable.
+    fn reset_queue(&mut self) -> Result<(), String> {
+        let path = self.queue_path().ok_or("No storage folder".to_owned())?;
+        let mut queue = match Queue::open(&path) {
+            Ok(queue) => queue,
+            Err(_) => {
+                queue::remove_database(&path).map_err(|e| e.to_string())?;
+                Queue::open(&path).map_err(|e| e.to_string())?
+            }
+        };
+        queue.sync(Vec::new()).map_err(|e| e.to_string())?;
+        self.dropped_files.clear();
+        self.queue = Some(queue);
+        self.refresh_renders();
+        Ok(())
+    }
 
-            ui.add_space(10.0);
+    pub fn build_safe_mode_view(&mut self, ctx: &egui::Context) {
+        if !self.is_safe_mode {
+            return;
+        }
+        egui::TopBottomPanel::top("safe_mode_panel").show(ctx, |ui| {
+            ui.label(
+                egui::RichText::new(
+                    "Safe mode: persisted settings and the queue were not restored.",
+                )
+                .strong(),
+            );
+            ui.horizontal_wrapped(|ui| {
+                if ui
+                    .add_enabled(!self.is_settings_reset, egui::Button::new("Reset settings"))
+                    .on_hover_text(
+                        "Saves the current settings on exit, replacing the persisted ones",
+                    )
+                    .clicked()
+                {
+                    self.is_settings_reset = true;
+                    self.safe_mode_message =
+                        Some("Settings will be replaced when the app exits.".to_owned());
+                }
+                if ui.button("Reset queue").clicked() {
+                    self.safe_mode_message = Some(match self.reset_queue() {
+                        Ok(_) => "The queue was emptied.".to_owned(),
+                        Err(e) => format!("Cannot reset the queue: {}", e),
+                    });
+                }
+            });
+            if let Some(message) = &self.safe_mode_message {
+                ui.label(message);
+            }
+            ui.label("Restart without --safe-mode to restore the remaining state.");
+        });
+    }
 
-            ui.checkbox(&mut self.is_video_enabled, "Video processing")
-                .on_hover_text("Check to enable video processing");
+    /// Checks ffmpeg, write access to the configured output folders and whether the configured
+    /// upload targets answer, in the background.
+    pub fn check_health(&mut self) {
+        self.health = None;
+        let mut folders = vec![];
+        let mut add = |name, enabled: bool, path: &Option<PathBuf>| {
+            if let (true, Some(path)) = (enabled, path) {
+                folders.push((name, path.clone()));
+            }
+        };
+        add(
+            "Video output",
+            self.is_video_enabled,
+            &self.video_output_path,
+        );
+        add(
+            "Image sequence export",
+            self.is_sequence_export_enabled,
+            &self.sequence_export_path,
+        );
+        add(
+            "Archive",
+            self.is_archive_enabled,
+            &self.archive_output_path,
+        );
+        add("Output layout", self.is_layout_enabled, &self.layout_root);
+        let mut urls = vec![];
+        if self.is_archive_enabled && self.is_deposit_enabled {
+            urls.push((
+                self.deposit_settings.repository.label(),
+                self.deposit_settings.url.clone(),
+            ));
+        }
+        if !self.webhook_url.trim().is_empty() {
+            urls.push(("Webhook", self.webhook_url.clone()));
+        }
+        let ffmpeg_path = self.ffmpeg_path.clone();
+        let is_ffmpeg_missing = self.is_video_enabled && ffmpeg_path.is_none();
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let (version, mut problems) = health::check(ffmpeg_path.as_deref(), &folders, &urls);
+            if is_ffmpeg_missing {
+                problems.insert(
+                    0,
+                    "Video processing is enabled but no ffmpeg binary is set".to_owned(),
+                );
+            }
+            let _ = sender.send(Signal::HealthChecked((version, problems)));
+        });
+    }
+
+    pub fn build_health_view(&mut self, ctx: &egui::Context) {
+        let mut recheck = false;
+        egui::TopBottomPanel::top("health_panel").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| match &self.health {
+                None => {
+                    ui.spinner();
+                    ui.label("Checking setup…");
+                }
+                Some((version, problems)) => {
+                    if let Some(version) = version {
+                        ui.label(format!("ffmpeg {}", version));
+                    }
+                    if problems.is_empty() {
+                        ui.label(egui::RichText::new("Setup OK").color(egui::Color32::GREEN));
+                    } else {
+                        ui.vertical(|ui| {
+                            for problem in problems {
+                                ui.label(egui::RichText::new(problem).color(egui::Color32::RED));
+                            }
+                        });
+                    }
+                    recheck = ui.small_button("Check again").clicked();
+                }
+            });
+        });
+        if recheck {
+            self.check_health();
+        }
+    }
+
+    /// Returns the custom app data folder, or the default storage folder without one.
+    fn app_data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.clone().or_else(storage::default_dir)
+    }
+
+    fn queue_path(&self) -> Option<PathBuf> {
+        self.app_data_dir()
+            .map(|dir| dir.join(queue::DATABASE_NAME))
+    }
+
+    /// Downloads a static ffmpeg build into the app data folder in the background.
+    fn download_ffmpeg(&mut self) {
+        let Some(data_dir) = self.app_data_dir() else {
+            self.ffmpeg_error = Some("No app data folder".to_owned());
+            return;
+        };
+        self.is_ffmpeg_downloading = true;
+        self.ffmpeg_error = None;
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let _ = sender.send(Signal::FfmpegDownloaded(ffmpeg::download(&data_dir)));
+        });
+    }
+
+    /// Opens the queue database in the app's storage folder. Its jobs are offered for
+    /// restoring as the previous session.
+    fn open_queue(&mut self) {
+        let Some(path) = self.queue_path() else {
+            return;
+        };
+        match Queue::open(&path) {
+            Ok(queue) => {
+                self.previous_session = queue.records().to_vec();
+                self.queue = Some(queue);
+                self.refresh_renders();
+            }
+            Err(e) => log::error!("Error opening queue database {}: {}", path.display(), e),
+        }
+    }
+
+    fn refresh_renders(&mut self) {
+        if let Some(queue) = &self.queue {
+            match queue.last_renders() {
+                Ok(renders) => self.renders = renders,
+                Err(e) => log::error!("Error reading renders {}", e),
+            }
+            match queue.render_history() {
+                Ok(history) => self.render_history = history,
+                Err(e) => log::error!("Error reading render history {}", e),
+            }
+        }
+    }
+
+    /// Returns the history entry of the job of `path` as it is now.
+    fn history_entry(&self, path: &Path) -> Option<HistoryEntry> {
+        let file = self.dropped_files.get(path)?;
+        let (Some(image_config), Some((start_date, end_date))) =
+            (self.job_config(file), file.date_range())
+        else {
+            return None;
+        };
+        Some(HistoryEntry {
+            finished_at: chrono::Local::now().fixed_offset(),
+            config_path: path.to_path_buf(),
+            location: image_config.location.clone(),
+            camera: image_config.camera.clone(),
+            start_date,
+            end_date,
+            duration: file
+                .stage_timings
+                .iter()
+                .map(|(_, duration)| duration.as_secs_f64())
+                .sum(),
+            status: item_state(&self.state, file).label().to_owned(),
+            outputs: self
+                .partial_outputs(&image_config, file)
+                .into_iter()
+                .filter(|output| output.exists())
+                .collect(),
+        })
+    }
+
+    /// Records a job that finished, successfully or not, in the processing history, as a
+    /// render of its site if it is done, and reports it to the webhook.
+    fn record_history(&mut self, path: &Path) {
+        let Some(entry) = self.history_entry(path) else {
+            return;
+        };
+        self.post_webhook(webhook::job_finished(&entry));
+        let render = self.render(path);
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        if let Err(e) = queue.record_history(&entry, render.as_ref()) {
+            log::error!("Error recording history {}", e);
+        }
+        if render.is_some() {
+            self.refresh_renders();
+        }
+        if self.is_history_open {
+            self.search_history();
+        }
+    }
+
+    fn search_history(&mut self) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let date = self.history_search.date.trim();
+        let query = HistoryQuery {
+            location: self.history_search.location.trim().to_owned(),
+            camera: self.history_search.camera.trim().to_owned(),
+            date: if date.is_empty() {
+                None
+            } else {
+                match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) => Some(date),
+                    Err(_) => {
+                        self.history_search.error = Some(format!("Invalid date {}", date));
+                        return;
+                    }
+                }
+            },
+        };
+        self.history_search.error = None;
+        match queue.search_history(&query) {
+            Ok(history) => self.history = history,
+            Err(e) => self.history_search.error = Some(e.to_string()),
+        }
+    }
+
+    /// Side panel listing finished jobs, searchable by location, camera and a date within
+    /// their range, to check whether a site was already migrated.
+    pub fn build_history_view(&mut self, ctx: &egui::Context) {
+        if !self.is_history_open {
+            return;
+        }
+        egui::SidePanel::left("history_panel")
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("History");
+                    if ui.small_button("✕").clicked() {
+                        self.is_history_open = false;
+                    }
+                });
+                let mut is_changed = false;
+                egui::Grid::new("history_search").show(ui, |ui| {
+                    let search = &mut self.history_search;
+                    for (label, value, hint) in [
+                        ("Location", &mut search.location, ""),
+                        ("Camera", &mut search.camera, ""),
+                        ("Date", &mut search.date, "YYYY-MM-DD"),
+                    ] {
+                        ui.label(label);
+                        is_changed |= ui
+                            .add(egui::TextEdit::singleline(value).hint_text(hint))
+                            .changed();
+                        ui.end_row();
+                    }
+                });
+                if is_changed {
+                    self.search_history();
+                }
+                if let Some(error) = &self.history_search.error {
+                    ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                }
+                ui.separator();
+                if self.history.is_empty() {
+                    ui.label("No finished jobs found.");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, entry) in self.history.iter().enumerate() {
+                        egui::CollapsingHeader::new(format!(
+                            "{} {} to {}",
+                            site_name(&entry.location, &entry.camera),
+                            entry.start_date,
+                            entry.end_date
+                        ))
+                        .id_source(("history", i))
+                        .show(ui, |ui| {
+                            ui.label(format!(
+                                "Finished {}",
+                                entry.finished_at.format("%Y-%m-%d %H:%M")
+                            ));
+                            ui.label(format!("Status: {}", entry.status));
+                            ui.label(format!("Duration: {:.0} s", entry.duration));
+                            ui.label(entry.config_path.display().to_string());
+                            for output in &entry.outputs {
+                                if ui
+                                    .link(output.display().to_string())
+                                    .on_hover_text("Show in the file manager")
+                                    .clicked()
+                                {
+                                    show_in_file_manager(output);
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+    }
+
+    /// Returns the render of the job of `path` if it is done, so that its site's render
+    /// schedule can be tracked.
+    fn render(&self, path: &Path) -> Option<Render> {
+        let file = self.dropped_files.get(path)?;
+        if !item_state(&self.state, file).is_done() {
+            return None;
+        }
+        let (Some(image_config), Some((start_date, end_date))) =
+            (self.job_config(file), file.date_range())
+        else {
+            return None;
+        };
+        Some(Render {
+            config_path: path.to_path_buf(),
+            location: image_config.location.clone(),
+            camera: image_config.camera.clone(),
+            start_date,
+            end_date,
+            deposit_id: file.deposit_id.clone(),
+            metrics: (!file.stage_timings.is_empty()).then(|| {
+                let frames = utils::image_files(&image_config.output_path).unwrap_or_default();
+                RenderMetrics {
+                    duration: file
+                        .stage_timings
+                        .iter()
+                        .map(|(_, duration)| duration.as_secs_f64())
+                        .sum(),
+                    frames: frames.len(),
+                    bytes: frames
+                        .iter()
+                        .filter_map(|frame| std::fs::metadata(frame).ok())
+                        .map(|metadata| metadata.len())
+                        .sum(),
+                }
+            }),
+        })
+    }
+
+    /// Returns the sites with a render cadence together with the periods they are missing.
+    fn overdue_sites(&self) -> Vec<(&Render, Vec<dates::DateRange>)> {
+        let today = chrono::Local::now().date_naive();
+        self.renders
+            .iter()
+            .filter_map(|render| {
+                let cadence = self
+                    .site_cadences
+                    .get(&site_name(&render.location, &render.camera))?;
+                let periods = schedule::catch_up_periods(*cadence, render.end_date, today);
+                (!periods.is_empty()).then_some((render, periods))
+            })
+            .collect()
+    }
+
+    /// Queues one job per missing period of a site, based on its last rendered config.
+    fn queue_catch_up(&mut self, render: &Render, periods: &[dates::DateRange]) {
+        let image_config = match tree_migration::Config::from(&render.config_path) {
+            Ok(image_config) => image_config,
+            Err(e) => {
+                log::error!(
+                    "Error reading config {}: {}",
+                    render.config_path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        for period in periods {
+            self.queue_config_copy(&render.config_path, &image_config, *period, None);
+        }
+    }
+
+    /// Starts or stops the status page as configured and publishes the queue to it.
+    fn sync_dashboard(&mut self) {
+        if !self.is_dashboard_enabled {
+            self.dashboard = None;
+            self.dashboard_error = None;
+            return;
+        }
+        if self
+            .dashboard
+            .as_ref()
+            .is_some_and(|dashboard| dashboard.port != self.dashboard_port)
+        {
+            self.dashboard = None;
+        }
+        if self.dashboard.is_none() && self.dashboard_error.is_none() {
+            match Dashboard::start(self.dashboard_port) {
+                Ok(dashboard) => self.dashboard = Some(dashboard),
+                Err(e) => self.dashboard_error = Some(e.to_string()),
+            }
+        }
+        let Some(dashboard) = &self.dashboard else {
+            return;
+        };
+        dashboard.publish(dashboard::Snapshot {
+            jobs: self
+                .dropped_files
+                .iter()
+                .map(|(path, file)| {
+                    let state = item_state(&self.state, file);
+                    dashboard::JobStatus {
+                        path: path.display().to_string(),
+                        status: state.label().to_owned(),
+                        progress: file.progress.filter(|_| {
+                            state == JobStatus::Processing || state == JobStatus::Paused
+                        }),
+                        error: file.error_message(),
+                    }
+                })
+                .collect(),
+            renders: self
+                .renders
+                .iter()
+                .map(|render| dashboard::RenderStatus {
+                    site: site_name(&render.location, &render.camera),
+                    end_date: render.end_date.to_string(),
+                    deposit_id: render.deposit_id.clone(),
+                })
+                .collect(),
+        });
+    }
+
+    /// Returns the jobs in the table as queue records, with running jobs as processing.
+    fn job_records(&self) -> Vec<JobRecord> {
+        self.dropped_files
+            .iter()
+            .map(|(path, file)| {
+                let (status, error) = match item_state(&self.state, file) {
+                    JobStatus::Processing | JobStatus::Paused => {
+                        (queue::RecordStatus::Processing, None)
+                    }
+                    JobStatus::Cancelled => {
+                        (queue::RecordStatus::Error, Some("Cancelled".to_owned()))
+                    }
+                    JobStatus::AwaitingReview | JobStatus::Rejected => {
+                        (queue::RecordStatus::Done, None)
+                    }
+                    state if state.is_done() => (queue::RecordStatus::Done, None),
+                    JobStatus::ProcessingError => (
+                        queue::RecordStatus::Error,
+                        file.done
+                            .as_ref()
+                            .and_then(|done| done.as_ref().err())
+                            .map(|e| e.to_string())
+                            .or(file.crash.clone())
+                            .or(file.stage_error.clone()),
+                    ),
+                    _ if file.previous_error.is_some() => {
+                        (queue::RecordStatus::Error, file.previous_error.clone())
+                    }
+                    _ => (queue::RecordStatus::Queued, None),
+                };
+                JobRecord {
+                    path: path.clone(),
+                    status,
+                    error,
+                    output_override: file
+                        .default_output_path
+                        .as_ref()
+                        .and(file.config.as_ref().ok())
+                        .map(|config| config.output_path.clone()),
+                    date_ranges: file.date_ranges.clone(),
+                    ffmpeg_path: file.ffmpeg_path.clone(),
+                    overrides: file.overrides.clone(),
+                    video: file.video.clone(),
+                    video_name_collision: file
+                        .is_video_name_collision_fixed
+                        .then_some(file.video_name_collision),
+                    review: file.review,
+                    proxy: file.proxy.clone(),
+                    flagged_frames: file.flagged_frames.clone(),
+                    excluded_frames: file.excluded_frames.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the current queue and job states to the queue database.
+    /// Nothing is written while the previous session is neither restored nor discarded.
+    fn sync_queue(&mut self) {
+        if !self.previous_session.is_empty() {
+            return;
+        }
+        let records = self.job_records();
+        let Some(queue) = &mut self.queue else {
+            return;
+        };
+        if let Err(e) = queue.sync(records) {
+            log::error!("Error saving queue {}", e);
+        }
+    }
+
+    pub fn build_settings_view(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.mode, Mode::Migration, "Migration");
+                ui.selectable_value(&mut self.mode, Mode::Transcode, "Transcode");
+            });
+
+            ui.add_space(10.0);
+
+            if self.mode == Mode::Transcode {
+                self.transcode_settings_ui(ui);
+                ui.add_space(10.0);
+                return;
+            }
+
+            if !self.state.is_processing() {
+                self.presets_ui(ui);
+
+                ui.add_space(10.0);
+            }
+
+            ui.checkbox(&mut self.is_forest_green_enabled, "Forest Green")
+                .on_hover_text("Check to enable forest green");
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut self.max_parallel_jobs, 1..=16));
+                ui.label("Parallel jobs");
+            })
+            .response
+            .on_hover_text("Further jobs wait in the queue until a running job finishes");
+
+            ui.checkbox(&mut self.throttle.is_enabled, "Throttle when hot")
+                .on_hover_text("Run half as many jobs at once while the CPU is too hot or too busy, e.g. when rendering on a laptop in the field");
+            if self.throttle.is_enabled {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.throttle.max_temperature, 50.0..=105.0)
+                            .suffix(" °C"),
+                    );
+                    ui.label("Maximum CPU temperature");
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut self.throttle.max_cpu, 50.0..=100.0).suffix("%"));
+                    ui.label("Maximum CPU load over a minute");
+                });
+                if let Some(reason) = &self.throttle_reason {
+                    ui.label(
+                        egui::RichText::new(format!("Throttled: {}", reason))
+                            .color(egui::Color32::GOLD),
+                    );
+                }
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_video_enabled, "Video processing")
+                .on_hover_text("Check to enable video processing");
 
             ui.add_space(10.0);
 
             if self.is_video_enabled {
-                if self.state == AppState::Processing {
+                if self.state.is_processing() {
                     ui.label(
                         "Settings cannot be changed while files are being processed".to_owned(),
                     );
                 } else {
+                    ui.horizontal(|ui| {
+                        ui.label("Without ffmpeg or codec:");
+                        ui.radio_value(
+                            &mut self.missing_video,
+                            MissingVideo::Block,
+                            "Do not start",
+                        );
+                        ui.radio_value(
+                            &mut self.missing_video,
+                            MissingVideo::Skip,
+                            "Skip the video",
+                        );
+                    });
+
+                    ui.add_space(10.0);
+
                     ui.horizontal(|ui| {
                         if ui.button("Select output folder").clicked() {
                             self.video_output_path = rfd::FileDialog::new().pick_folder();
@@ -154,6 +1830,9 @@ impl MigrationApp {
 
                         if let Some(path) = &self.video_output_path {
                             ui.monospace(path.display().to_string());
+                            if ui.small_button("Open").clicked() {
+                                show_in_file_manager(path);
+                            }
                         } else {
                             ui.horizontal(|ui| {
                                 ui.label("Video ouput path not set.".to_owned());
@@ -185,26 +1864,148 @@ impl MigrationApp {
                             });
                         }
                     });
+                    if self.ffmpeg_path.is_none() {
+                        ui.horizontal(|ui| {
+                            if ui.button("Detect ffmpeg").clicked() {
+                                self.ffmpeg_path = ffmpeg::detect(self.app_data_dir().as_deref());
+                                if self.ffmpeg_path.is_none() {
+                                    self.ffmpeg_error = Some("ffmpeg not found".to_owned());
+                                }
+                            }
+                            if self.is_ffmpeg_downloading {
+                                ui.spinner();
+                                ui.label("Downloading ffmpeg…");
+                            } else if let Some(build) = &ffmpeg::BUILD {
+                                if ui
+                                    .button(format!("Download ffmpeg {}", build.version))
+                                    .on_hover_text(format!(
+                                        "Static build from {}, installed only if its checksum matches",
+                                        build.url
+                                    ))
+                                    .clicked()
+                                {
+                                    self.download_ffmpeg();
+                                }
+                            }
+                        });
+                    }
+                    if let Some(e) = &self.ffmpeg_error {
+                        ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                    }
 
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
                         egui::ComboBox::from_label("Video Codec")
-                            .selected_text(match self.video_codec {
-                                images_to_video::Codec::H264 => "h.264",
-                                images_to_video::Codec::ProRes => "Prores",
-                                images_to_video::Codec::None => "None",
+                            .selected_text(self.video_codec.label())
+                            .show_ui(ui, |ui| {
+                                for codec in Codec::ENCODERS {
+                                    ui.selectable_value(
+                                        &mut self.video_codec,
+                                        codec,
+                                        codec.label(),
+                                    );
+                                }
+                            });
+                    });
+
+                    if matches!(self.video_codec, Codec::H264 | Codec::H265) {
+                        ui.checkbox(
+                            &mut self.is_adaptive_encoding_enabled,
+                            "Adapt to source resolution",
+                        )
+                        .on_hover_text("Check to pick the level, bitrate and scaling of every video from the resolution of its source images instead of the codec options");
+                    }
+                    codec_options_ui(ui, self.video_codec, &mut self.codec_options);
+                    environment_ui(ui, &mut self.ffmpeg_environment);
+
+                    ui.add_space(10.0);
+
+                    ui.checkbox(&mut self.is_capture_order_enabled, "Order frames by capture time")
+                        .on_hover_text(
+                            "Check to order the frames of videos by their EXIF capture time instead of their names, read with the camera clock of the job",
+                        );
+
+                    ui.checkbox(&mut self.is_date_overlay_enabled, "Overlay capture date")
+                        .on_hover_text(
+                            "Check to stamp the date from the file name or EXIF data onto every frame of the video. The image outputs are left as they are",
+                        );
+
+                    ui.checkbox(&mut self.is_title_slate_enabled, "Title slate")
+                        .on_hover_text(
+                            "Check to open every video with a slate showing the job name, its dates and how much real time one second of video covers",
+                        );
+
+                    ui.checkbox(&mut self.is_video_failure_fatal, "Fail jobs without video")
+                        .on_hover_text(
+                            "Check to fail a job when its video cannot be encoded. Uncheck to only log the error as a warning and continue with the following stages",
+                        );
+
+                    ui.checkbox(&mut self.is_review_enabled, "Review proxies")
+                        .on_hover_text(
+                            "Check to render a fast low-resolution proxy of every job first. The full video, archive and upload only run once the proxy is approved",
+                        );
+
+                    ui.add_space(10.0);
+
+                    let mut is_target_duration_enabled = self.target_duration.is_some();
+                    ui.checkbox(&mut is_target_duration_enabled, "Target video length");
+                    match (is_target_duration_enabled, self.target_duration) {
+                        (true, None) => self.target_duration = Some(60),
+                        (false, Some(_)) => self.target_duration = None,
+                        _ => {}
+                    }
+
+                    if let Some(target_duration) = &mut self.target_duration {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(target_duration)
+                                    .clamp_range(1..=3600)
+                                    .suffix(" s"),
+                            );
+                            ui.label("Video length");
+                        });
+                        ui.label(format!(
+                            "The frame rate is computed from each job's image count, dropping frames above {} fps",
+                            video::MAX_FRAME_RATE
+                        ));
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Slider::new(&mut self.frame_rate, 1..=25));
+                            ui.label("Frame Rate".to_owned());
+                        });
+                    }
+
+                    ui.add_space(10.0);
+
+                    self.video_name_ui(ui);
+
+                    self.date_format_ui(ui);
+
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_label("Missing days")
+                            .selected_text(match self.gap_filling {
+                                GapFilling::Skip => "Skip",
+                                GapFilling::HoldLastFrame => "Hold last frame",
+                                GapFilling::Slate => "No data slate",
                             })
                             .show_ui(ui, |ui| {
                                 ui.selectable_value(
-                                    &mut self.video_codec,
-                                    images_to_video::Codec::H264,
-                                    "h.264",
+                                    &mut self.gap_filling,
+                                    GapFilling::Skip,
+                                    "Skip",
+                                );
+                                ui.selectable_value(
+                                    &mut self.gap_filling,
+                                    GapFilling::HoldLastFrame,
+                                    "Hold last frame",
                                 );
                                 ui.selectable_value(
-                                    &mut self.video_codec,
-                                    images_to_video::Codec::ProRes,
-                                    "Prores",
+                                    &mut self.gap_filling,
+                                    GapFilling::Slate,
+                                    "No data slate",
                                 );
                             });
                     });
@@ -212,204 +2013,4213 @@ impl MigrationApp {
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        ui.add(egui::Slider::new(&mut self.frame_rate, 1..=25));
-                        ui.label("Frame Rate".to_owned());
+                        ui.checkbox(&mut self.is_preview_enabled, "Preview animation")
+                            .on_hover_text(format!(
+                                "Writes {} evenly spaced frames at 480 px next to every video",
+                                video::PREVIEW_FRAMES
+                            ));
+                        if self.is_preview_enabled {
+                            ui.radio_value(&mut self.preview_format, PreviewFormat::Gif, "GIF");
+                            ui.radio_value(&mut self.preview_format, PreviewFormat::Webp, "WebP");
+                        }
                     });
                 }
             }
 
+            ui.horizontal(|ui| {
+                if ui.button("Select \"Open with\" application").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        self.open_with_path = Some(path);
+                    }
+                }
+
+                if let Some(path) = &self.open_with_path {
+                    ui.monospace(path.display().to_string());
+                } else {
+                    ui.label("Application for finished jobs not set.".to_owned());
+                }
+            });
+
             ui.add_space(10.0);
-        });
-    }
 
-    pub fn build_drag_and_drop_view(&mut self, ctx: &egui::Context) {
-        use egui::*;
-        CentralPanel::default().show(ctx, |ui| {
-            // Collect dropped files:
-            if !ctx.input(|input| input.raw.dropped_files.is_empty()) {
-                let dropped_files = ctx.input(|input| input.raw.dropped_files.clone());
-                for file in dropped_files {
-                    let config = tree_migration::Config::from(&file.path.as_ref().unwrap());
-                    self.dropped_files
-                        .insert(file.path.unwrap(), (config, None));
+            ui.horizontal(|ui| {
+                if ui.button("Select app data folder").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        self.data_dir_message = Some(
+                            match storage::save_settings(&path, self)
+                                .and_then(|_| storage::set_custom_data_dir(Some(&path)))
+                            {
+                                Ok(_) => "Settings and history use the new folder after a restart"
+                                    .to_owned(),
+                                Err(e) => e.to_string(),
+                            },
+                        );
+                    }
                 }
-            }
-            use egui_extras::{Size, StripBuilder};
-            StripBuilder::new(ui)
-                .size(Size::remainder().at_least(100.0)) // for the table
-                .size(Size::exact(10.5)) // for the source code link
-                .vertical(|mut strip| {
-                    strip.cell(|ui| {
-                        egui::ScrollArea::horizontal().show(ui, |ui| {
-                            self.table_ui(ui);
-                        });
+
+                match self.data_dir.clone().or_else(storage::default_dir) {
+                    Some(path) => ui.monospace(path.display().to_string()),
+                    None => ui.label("No app data folder available.".to_owned()),
+                };
+
+                if self.data_dir.is_some() && ui.small_button("Use default").clicked() {
+                    self.data_dir_message = Some(match storage::set_custom_data_dir(None) {
+                        Ok(_) => "Settings and history use the default folder after a restart"
+                            .to_owned(),
+                        Err(e) => e.to_string(),
                     });
-                });
-        });
-    }
+                }
+            });
+
+            if let Some(message) = &self.data_dir_message {
+                ui.label(message);
+            }
 
-    pub fn build_processing_view(&mut self, ctx: &egui::Context) {
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.add_space(10.0);
-            ui.horizontal_wrapped(|ui| {
-                ui.with_layout(
-                    egui::Layout::left_to_right(egui::Align::TOP),
-                    |ui| match self.state {
-                        AppState::Processing => {
-                            ui.spinner();
-                        }
-                        AppState::Init => {
-                            ui.label("Nothing to process: No Config Files");
-                        }
-                        AppState::InvalidConfigs => {
-                            ui.label("Cannot process: No or invalid Config Files");
-                        }
-                        AppState::ValidConfigs | AppState::ProcessingDone => {
+
+            ui.horizontal(|ui| {
+                if ui.button("Select temporary files folder").clicked() {
+                    self.temp_path = rfd::FileDialog::new().pick_folder();
+                }
+
+                if let Some(path) = &self.temp_path {
+                    ui.monospace(path.display().to_string());
+                } else {
+                    ui.label("Temporary files are written next to the outputs.".to_owned());
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Log level")
+                    .selected_text(self.log_level.label())
+                    .show_ui(ui, |ui| {
+                        for level in LogLevel::ALL {
                             if ui
-                                .button(egui::RichText::new("Process").heading())
-                                .clicked()
+                                .selectable_value(&mut self.log_level, level, level.label())
+                                .changed()
                             {
-                                self.state = AppState::Processing;
-                                self.process();
+                                logging::set_level(level);
                             }
                         }
-                        AppState::ProcessingErrors => {
-                            ui.label(
-                                egui::RichText::new("Processing error.".to_owned())
+                    });
+                if std::env::var_os("RUST_LOG").is_some() {
+                    ui.label("RUST_LOG is set and takes precedence");
+                }
+                match &self.log_path {
+                    Some(path) => {
+                        if ui
+                            .button("Open log file")
+                            .on_hover_text(path.display().to_string())
+                            .clicked()
+                        {
+                            if let Err(e) = launch::open(path) {
+                                log::error!("Error opening {} {}", path.display(), e);
+                            }
+                        }
+                    }
+                    None => {
+                        ui.label("No log file is written.");
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.is_dashboard_enabled, "Status page")
+                    .on_hover_text("Check to serve a read-only page showing the queue to browsers on the network");
+                let port = ui.add(egui::DragValue::new(&mut self.dashboard_port).prefix("Port "));
+                if port.changed() {
+                    self.dashboard_error = None;
+                }
+                if let Some(dashboard) = &self.dashboard {
+                    ui.label(format!("Serving on port {}", dashboard.port));
+                }
+            });
+
+            if let Some(error) = &self.dashboard_error {
+                ui.label(
+                    egui::RichText::new(format!("Cannot serve the status page: {}", error))
+                        .color(egui::Color32::RED),
+                );
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.is_notification_enabled, "Notify when done")
+                    .on_hover_text("Check to show a desktop notification when a batch finishes");
+                ui.add_enabled(
+                    self.is_notification_enabled,
+                    egui::Checkbox::new(&mut self.is_notification_sound_enabled, "Play a sound"),
+                );
+            });
+
+            ui.add_space(10.0);
+
+            let mut is_failure_limit_enabled = self.max_consecutive_failures.is_some();
+            ui.checkbox(&mut is_failure_limit_enabled, "Stop after failures in a row")
+                .on_hover_text("Check to cancel the queued jobs once this many jobs failed in a row, and alert through a desktop notification and the webhook");
+            match (is_failure_limit_enabled, self.max_consecutive_failures) {
+                (true, None) => self.max_consecutive_failures = Some(3),
+                (false, Some(_)) => self.max_consecutive_failures = None,
+                _ => {}
+            }
+            if let Some(max_consecutive_failures) = &mut self.max_consecutive_failures {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(max_consecutive_failures).clamp_range(1..=100));
+                    ui.label("Failed jobs");
+                });
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_session_lock_enabled, "Session lock")
+                .on_hover_text("Check to require a confirmation phrase before discarding a running batch");
+
+            if self.is_session_lock_enabled {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.session_lock_phrase);
+                    ui.label("Confirmation phrase");
+                });
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_map_enabled, "Map")
+                .on_hover_text("Check to show the locations of the queued sites on a map");
+
+            if self.is_map_enabled {
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Select locations file").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .pick_file()
+                        {
+                            self.locations = locations::load(&path).unwrap_or_default();
+                            self.locations_path = Some(path);
+                        }
+                    }
+
+                    if let Some(path) = &self.locations_path {
+                        ui.monospace(path.display().to_string());
+                    } else {
+                        ui.label(
+                            "Locations file (location,latitude,longitude) not set.".to_owned(),
+                        );
+                    }
+                });
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Select location defaults")
+                    .on_hover_text("A TOML file with the codec, frame rate and delivery folder of each location, applied to configs when they are added")
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("TOML", &["toml"])
+                        .pick_file()
+                    {
+                        self.load_location_defaults(path);
+                    }
+                }
+
+                if let Some(path) = &self.location_defaults_path {
+                    ui.monospace(path.display().to_string());
+                    if ui.small_button("✕").clicked() {
+                        self.location_defaults_path = None;
+                        self.location_defaults.clear();
+                        self.location_defaults_error = None;
+                    }
+                } else {
+                    ui.label("Location defaults not set.".to_owned());
+                }
+            });
+            if let Some(e) = &self.location_defaults_error {
+                ui.colored_label(egui::Color32::RED, format!("Invalid location defaults: {}", e));
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_layout_enabled, "Output layout")
+                .on_hover_text("Check to place images and videos in a folder structure under a root");
+
+            if self.is_layout_enabled {
+                ui.add_space(10.0);
+
+                if self.state.is_processing() {
+                    ui.label(
+                        "Settings cannot be changed while files are being processed".to_owned(),
+                    );
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("Select layout root").clicked() {
+                            self.layout_root = rfd::FileDialog::new().pick_folder();
+                        }
+
+                        if let Some(path) = &self.layout_root {
+                            ui.monospace(path.display().to_string());
+                        } else {
+                            ui.label("Layout root not set.".to_owned());
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.layout_template);
+                        ui.label("Layout").on_hover_text(
+                            "Placeholders: {year}, {month}, {location}, {camera}",
+                        );
+                    });
+
+                    if let Err(e) = layout::expand(&self.layout_template, &Default::default()) {
+                        ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                    }
+                }
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_script_enabled, "Job script").on_hover_text(
+                "Check to run a Rhai script on every job when processing starts, e.g. to route outputs by location or set the frame rate by image count",
+            );
+
+            if self.is_script_enabled {
+                ui.add_space(10.0);
+
+                if self.state.is_processing() {
+                    ui.label(
+                        "Settings cannot be changed while files are being processed".to_owned(),
+                    );
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("Select script").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Rhai", &["rhai"])
+                                .pick_file()
+                            {
+                                self.script_error = JobScript::load(&path).err();
+                                self.script_path = Some(path);
+                            }
+                        }
+
+                        if let Some(path) = &self.script_path {
+                            ui.monospace(path.display().to_string());
+                            if ui.small_button("Check").clicked() {
+                                self.script_error = JobScript::load(path).err();
+                            }
+                        } else {
+                            ui.label("Script not set.".to_owned());
+                        }
+                    });
+
+                    if let Some(e) = &self.script_error {
+                        ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                    }
+                }
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(
+                &mut self.is_sequence_export_enabled,
+                "Image sequence export",
+            )
+            .on_hover_text("Check to export the processed frames as a numbered image sequence");
+
+            if self.is_sequence_export_enabled {
+                ui.add_space(10.0);
+
+                if self.state.is_processing() {
+                    ui.label(
+                        "Settings cannot be changed while files are being processed".to_owned(),
+                    );
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("Select export folder").clicked() {
+                            self.sequence_export_path = rfd::FileDialog::new().pick_folder();
+                        }
+
+                        if let Some(path) = &self.sequence_export_path {
+                            ui.monospace(path.display().to_string());
+                        } else {
+                            ui.label("Image sequence export path not set.".to_owned());
+                        }
+                    });
+                }
+            }
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_xmp_enabled, "XMP sidecars")
+                .on_hover_text(
+                    "Check to tag the processed frames with location, camera and treatment",
+                );
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_manifest_enabled, "Checksum manifest")
+                .on_hover_text("Check to write a SHA-256 manifest of the outputs of every job");
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_source_quarantine_enabled, "Quarantine corrupt sources")
+                .on_hover_text(format!(
+                    "Check to scan the source images before migrating and move empty, unreadable or truncated ones into a \"{}\" subfolder of the input folder",
+                    integrity::QUARANTINE_NAME
+                ));
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Without enough free disk space:")
+                    .on_hover_text("The outputs of every job are estimated from the size of its source images and the video codec before processing");
+                ui.radio_value(&mut self.disk_space_check, DiskSpaceCheck::Off, "Do not check");
+                ui.radio_value(&mut self.disk_space_check, DiskSpaceCheck::Warn, "Warn");
+                ui.radio_value(&mut self.disk_space_check, DiskSpaceCheck::Refuse, "Do not start");
+            });
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_profiling_enabled, "Profiling")
+                .on_hover_text(
+                    "Check to write a per-stage timing breakdown of every job to attach to performance reports",
+                );
+
+            ui.add_space(10.0);
+
+            ui.checkbox(&mut self.is_archive_enabled, "Archive packaging")
+                .on_hover_text("Check to package the outputs of every job into a ZIP archive");
+
+            if self.is_archive_enabled {
+                ui.add_space(10.0);
+
+                if self.state.is_processing() {
+                    ui.label(
+                        "Settings cannot be changed while files are being processed".to_owned(),
+                    );
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.button("Select archive folder").clicked() {
+                            self.archive_output_path = rfd::FileDialog::new().pick_folder();
+                        }
+
+                        if let Some(path) = &self.archive_output_path {
+                            ui.monospace(path.display().to_string());
+                        } else {
+                            ui.label("Archive output path not set.".to_owned());
+                        }
+                    });
+
+                    ui.checkbox(&mut self.is_deposit_enabled, "Deposit archive")
+                        .on_hover_text(
+                            "Check to deposit the archive of every job to Zenodo or DSpace and record its DOI or handle",
+                        );
+
+                    if self.is_deposit_enabled {
+                        deposit_settings_ui(ui, &mut self.deposit_settings);
+                    }
+                }
+            }
+
+            ui.add_space(10.0);
+
+            let variables = hooks::VARIABLES
+                .map(|(name, description)| format!("${}: {}", name, description))
+                .join("\n");
+            ui.label("Commands run after every job")
+                .on_hover_text(format!("Run through the shell with the environment variables\n{}\nTheir output goes into the job log\nSaved in plain text with the settings", variables));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.hooks.on_success)
+                        .hint_text("rsync -a \"$TREE_MIGRATION_OUTPUT\" nas:archive/")
+                        .desired_width(360.0),
+                );
+                ui.label("On success");
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.hooks.on_failure).desired_width(360.0));
+                ui.label("On failure");
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.webhook_url)
+                        .hint_text("https://")
+                        .desired_width(360.0),
+                );
+                ui.label("Webhook URL");
+            })
+            .response
+            .on_hover_text("POST a JSON summary of every finished job and batch to this URL, retrying for about half a minute if it cannot be reached. Leave empty to send nothing");
+
+            ui.add_space(10.0);
+        });
+    }
+
+    fn current_preset(&self, name: String) -> SettingsPreset {
+        SettingsPreset {
+            name,
+            is_forest_green_enabled: self.is_forest_green_enabled,
+            is_video_enabled: self.is_video_enabled,
+            video_codec: self.video_codec,
+            frame_rate: self.frame_rate,
+            video_output_path: self.video_output_path.clone(),
+            ffmpeg_path: self.ffmpeg_path.clone(),
+        }
+    }
+
+    fn apply_preset(&mut self, preset: SettingsPreset) {
+        self.preset_name = preset.name;
+        self.is_forest_green_enabled = preset.is_forest_green_enabled;
+        self.is_video_enabled = preset.is_video_enabled;
+        self.video_codec = preset.video_codec;
+        self.frame_rate = preset.frame_rate;
+        self.video_output_path = preset.video_output_path;
+        if preset.ffmpeg_path.is_some() {
+            self.ffmpeg_path = preset.ffmpeg_path;
+        }
+    }
+
+    /// Lists the presets to apply and saves the current settings as a preset.
+    fn presets_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut applied = None;
+            egui::ComboBox::from_label("Preset")
+                .selected_text(self.preset_name.as_str())
+                .show_ui(ui, |ui| {
+                    for preset in &self.presets {
+                        if ui
+                            .selectable_label(preset.name == self.preset_name, &preset.name)
+                            .clicked()
+                        {
+                            applied = Some(preset.clone());
+                        }
+                    }
+                });
+            if let Some(preset) = applied {
+                self.apply_preset(preset);
+            }
+
+            ui.add(egui::TextEdit::singleline(&mut self.preset_name).desired_width(120.0));
+            let name = self.preset_name.trim().to_owned();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Save preset"))
+                .on_hover_text(
+                    "Save Forest Green, the video settings, the ffmpeg binary, the frame rate and the video output folder under this name",
+                )
+                .clicked()
+            {
+                let preset = self.current_preset(name.clone());
+                match self.presets.iter_mut().find(|preset| preset.name == name) {
+                    Some(existing) => *existing = preset,
+                    None => self.presets.push(preset),
+                }
+            }
+            if self.presets.iter().any(|preset| preset.name == name)
+                && ui.button("Delete preset").clicked()
+            {
+                self.presets.retain(|preset| preset.name != name);
+            }
+        });
+    }
+
+    fn transcode_settings_ui(&mut self, ui: &mut egui::Ui) {
+        if self.transcode_files.values().any(|file| file.is_running) {
+            ui.label("Settings cannot be changed while files are being transcoded".to_owned());
+            return;
+        }
+
+        if let Some(path) = &self.ffmpeg_path {
+            ui.monospace(path.display().to_string());
+        } else {
+            ui.label("Select an ffmpeg binary in the migration settings first.".to_owned());
+        }
+
+        ui.add_space(10.0);
+
+        let settings = &mut self.transcode_settings;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Codec")
+                .selected_text(settings.codec.label())
+                .show_ui(ui, |ui| {
+                    for codec in Codec::TRANSCODERS {
+                        ui.selectable_value(&mut settings.codec, codec, codec.label());
+                    }
+                });
+            egui::ComboBox::from_label("Container")
+                .selected_text(match settings.container {
+                    Container::Mov => "MOV",
+                    Container::Mp4 => "MP4",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.container, Container::Mov, "MOV");
+                    ui.selectable_value(&mut settings.container, Container::Mp4, "MP4");
+                });
+        });
+        if settings.codec == Codec::ProRes {
+            settings.container = Container::Mov;
+        }
+
+        codec_options_ui(ui, settings.codec, &mut self.codec_options);
+
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            let mut is_scaled = settings.height.is_some();
+            ui.checkbox(&mut is_scaled, "Scale to height");
+            match (is_scaled, settings.height.as_mut()) {
+                (true, Some(height)) => {
+                    ui.add(egui::Slider::new(height, 240..=2160));
+                }
+                (true, None) => settings.height = Some(1080),
+                (false, _) => settings.height = None,
+            }
+        });
+
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            if ui.button("Select output folder").clicked() {
+                settings.output_path = rfd::FileDialog::new().pick_folder();
+            }
+
+            if let Some(path) = &settings.output_path {
+                ui.monospace(path.display().to_string());
+            } else {
+                ui.label("Transcoded videos are written next to their source.".to_owned());
+            }
+        });
+    }
+
+    pub fn transcode(&mut self) {
+        let Some(ffmpeg_path) = self.ffmpeg_path.clone() else {
+            return;
+        };
+        for (path, file) in self.transcode_files.iter_mut() {
+            if file.is_running {
+                continue;
+            }
+            file.is_running = true;
+            file.done = None;
+            let sender = self.channel.0.clone();
+            let ffmpeg_path = ffmpeg_path.clone();
+            let settings = self.transcode_settings.clone();
+            let codec_options = self.codec_options.clone();
+            let output = transcode::output_path(path, settings.output_path.as_deref(), &settings);
+            let path = path.clone();
+            self.tasks.spawn_blocking(move || {
+                let result =
+                    transcode::transcode(&ffmpeg_path, &path, &output, &settings, &codec_options)
+                        .map(|_| output);
+                let _ = sender.send(Signal::Transcoded((path, result)));
+            });
+        }
+    }
+
+    fn transcode_table_ui(&mut self, ui: &mut egui::Ui) {
+        use egui::*;
+        use egui_extras::{Column, TableBuilder};
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::initial(100.0).range(40.0..=300.0))
+            .column(Column::remainder())
+            .min_scrolled_height(0.0)
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Status");
+                });
+                header.col(|ui| {
+                    ui.strong("Path");
+                });
+            })
+            .body(|mut body| {
+                for (path, file) in &self.transcode_files {
+                    let row_height = if file.done.is_some() { 36.0 } else { 18.0 };
+                    body.row(row_height, |mut row| {
+                        row.col(|ui| {
+                            ui.style_mut().wrap = Some(false);
+                            match &file.done {
+                                _ if file.is_running => {
+                                    ui.spinner();
+                                }
+                                Some(Ok(_)) => {
+                                    ui.label("Done");
+                                }
+                                Some(Err(_)) => {
+                                    ui.label("Error");
+                                }
+                                None => {
+                                    ui.label("Queued");
+                                }
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.style_mut().wrap = Some(false);
+                            ui.vertical(|ui| {
+                                ui.label(path.to_string_lossy());
+                                match &file.done {
+                                    Some(Ok(output)) => {
+                                        ui.monospace(output.display().to_string());
+                                    }
+                                    Some(Err(message)) => {
+                                        ui.label(RichText::new(message).color(Color32::RED));
+                                    }
+                                    None => {}
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+    }
+
+    pub fn build_map_view(&mut self, ctx: &egui::Context) {
+        if !self.is_map_enabled {
+            return;
+        }
+        egui::SidePanel::right("map_panel")
+            .default_width(250.0)
+            .show(ctx, |ui| {
+                ui.strong("Map");
+                if self.locations.is_empty() {
+                    ui.label("No locations loaded.");
+                    return;
+                }
+                egui_plot::Plot::new("map")
+                    .data_aspect(1.0)
+                    .show_axes([false, false])
+                    .show(ui, |plot_ui| {
+                        for file in self.dropped_files.values() {
+                            let Ok(image_config) = &file.config else {
+                                continue;
+                            };
+                            let Some([latitude, longitude]) =
+                                self.locations.get(&image_config.location)
+                            else {
+                                continue;
+                            };
+                            let color = match item_state(&self.state, file) {
+                                JobStatus::ProcessingDone => egui::Color32::GREEN,
+                                state if state.is_done() => egui::Color32::GOLD,
+                                JobStatus::ProcessingError => egui::Color32::RED,
+                                JobStatus::Processing | JobStatus::Paused => egui::Color32::YELLOW,
+                                _ => egui::Color32::GRAY,
+                            };
+                            let position = [*longitude, *latitude];
+                            plot_ui.points(
+                                egui_plot::Points::new(vec![position])
+                                    .radius(5.0)
+                                    .color(color)
+                                    .name(&image_config.location),
+                            );
+                            plot_ui.text(
+                                egui_plot::Text::new(
+                                    position.into(),
+                                    format!("{} {}", image_config.location, image_config.camera),
+                                )
+                                .anchor(egui::Align2::LEFT_BOTTOM),
+                            );
+                        }
+                    });
+            });
+    }
+
+    pub fn build_drag_and_drop_view(&mut self, ctx: &egui::Context) {
+        use egui::*;
+        CentralPanel::default().show(ctx, |ui| {
+            // Collect dropped files:
+            let (accepts, kind, _) = self.drop_target();
+            let dropped_files = ctx.input(|input| input.raw.dropped_files.clone());
+            if !dropped_files.is_empty() {
+                let temp_dir = self.temp_path.clone().unwrap_or_else(std::env::temp_dir);
+                let (paths, unreadable) =
+                    dropping::dropped_paths(dropped_files, &temp_dir, accepts);
+                let summary = DropSummary::new(paths, accepts);
+                let mut messages = Vec::new();
+                for path in summary.files(accepts) {
+                    if self.mode == Mode::Transcode {
+                        self.transcode_files.entry(path).or_default();
+                    } else if batch::is_batch(&path) {
+                        if let Err(e) = self.add_batch(&path) {
+                            messages.push(e);
+                        }
+                    } else {
+                        let file = self.new_entry(&path);
+                        self.dropped_files.insert(path, file);
+                    }
+                }
+                if !summary.unsupported.is_empty() {
+                    messages.push(format!(
+                        "Ignored {}: only {} are supported",
+                        summary
+                            .unsupported
+                            .iter()
+                            .filter_map(|path| path.file_name())
+                            .map(|name| name.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        kind
+                    ));
+                }
+                if unreadable > 0 {
+                    messages.push(format!(
+                        "Ignored {} dropped items without a readable file",
+                        unreadable
+                    ));
+                }
+                self.drop_message = (!messages.is_empty()).then(|| messages.join(". "));
+            }
+            if let Some(message) = &self.drop_message {
+                let mut is_dismissed = false;
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(RichText::new(message).color(Color32::RED));
+                    is_dismissed = ui.small_button("✕").clicked();
+                });
+                if is_dismissed {
+                    self.drop_message = None;
+                }
+            }
+            if self.mode == Mode::Transcode {
+                egui::ScrollArea::horizontal().show(ui, |ui| {
+                    self.transcode_table_ui(ui);
+                });
+                return;
+            }
+            use egui_extras::{Size, StripBuilder};
+            StripBuilder::new(ui)
+                .size(Size::remainder().at_least(100.0)) // for the table
+                .size(Size::exact(10.5)) // for the source code link
+                .vertical(|mut strip| {
+                    strip.cell(|ui| {
+                        self.table_filter_ui(ui);
+                        egui::ScrollArea::horizontal().show(ui, |ui| {
+                            self.table_ui(ui);
+                        });
+                    });
+                });
+        });
+    }
+
+    /// Returns which dropped files the current mode accepts, what they are called and
+    /// which files that excludes.
+    fn drop_target(&self) -> (fn(&Path) -> bool, &'static str, &'static str) {
+        match self.mode {
+            Mode::Migration => (
+                dropping::is_config,
+                "config files",
+                "Only .toml config files can be added",
+            ),
+            Mode::Transcode => (
+                transcode::is_video,
+                "videos",
+                "Only video files can be added",
+            ),
+        }
+    }
+
+    /// Covers the window while files are dragged over it, describing what dropping them does.
+    pub fn build_drop_overlay(&self, ctx: &egui::Context) {
+        let hovered_files = ctx.input(|input| input.raw.hovered_files.clone());
+        if hovered_files.is_empty() {
+            return;
+        }
+        let (accepts, kind, reason) = self.drop_target();
+        let paths = hovered_files
+            .iter()
+            .filter_map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        let lines = if paths.len() < hovered_files.len() {
+            vec![format!("Drop {} items", hovered_files.len())]
+        } else {
+            let summary = DropSummary::new(paths, accepts);
+            let mut lines = summary.describe(kind);
+            if !summary.unsupported.is_empty() {
+                lines.push(reason.to_owned());
+            }
+            lines
+        };
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("drop_overlay"),
+        ));
+        let screen_rect = ctx.screen_rect();
+        painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(192));
+        painter.text(
+            screen_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            lines.join("\n"),
+            egui::TextStyle::Heading.resolve(&ctx.style()),
+            egui::Color32::WHITE,
+        );
+    }
+
+    fn transcode_processing_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                if self.transcode_files.values().any(|file| file.is_running) {
+                    ui.spinner();
+                } else if self.transcode_files.is_empty() {
+                    ui.label("Nothing to transcode: Drop video files");
+                } else if self.ffmpeg_path.is_none() {
+                    ui.label("Cannot transcode: ffmpeg binary not set");
+                } else if ui
+                    .button(egui::RichText::new("Transcode").heading())
+                    .clicked()
+                {
+                    self.transcode();
+                }
+            });
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                if ui.button(egui::RichText::new("Clear").heading()).clicked() {
+                    self.transcode_files.retain(|_, file| file.is_running);
+                }
+            });
+        });
+    }
+
+    pub fn build_processing_view(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+            ui.add_space(10.0);
+            if self.mode == Mode::Transcode {
+                self.transcode_processing_ui(ui);
+                ui.add_space(10.0);
+                return;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.with_layout(
+                    egui::Layout::left_to_right(egui::Align::TOP),
+                    |ui| match self.state {
+                        QueueState::Processing => {
+                            ui.spinner();
+                            ui.add(
+                                egui::ProgressBar::new(self.batch_progress())
+                                    .show_percentage()
+                                    .desired_width(200.0),
+                            );
+                            if let Some(eta) = self.batch_eta() {
+                                ui.label(eta);
+                            }
+                            if ui
+                                .button(egui::RichText::new("Pause").heading())
+                                .on_hover_text(
+                                    "Stop starting queued jobs and suspend running encodes where possible",
+                                )
+                                .clicked()
+                            {
+                                self.pause();
+                            }
+                            if ui
+                                .button(egui::RichText::new("Cancel All").heading())
+                                .clicked()
+                            {
+                                self.request(LockedAction::CancelAll);
+                            }
+                        }
+                        QueueState::Paused => {
+                            ui.label("Paused");
+                            ui.add(
+                                egui::ProgressBar::new(self.batch_progress())
+                                    .show_percentage()
+                                    .desired_width(200.0),
+                            );
+                            if ui
+                                .button(egui::RichText::new("Resume").heading())
+                                .clicked()
+                            {
+                                self.resume();
+                            }
+                            if ui
+                                .button(egui::RichText::new("Cancel All").heading())
+                                .clicked()
+                            {
+                                self.request(LockedAction::CancelAll);
+                            }
+                        }
+                        QueueState::Init => {
+                            ui.label("Nothing to process: No Config Files");
+                        }
+                        QueueState::InvalidConfigs => {
+                            if self.dropped_files.values().any(|file| {
+                                item_state(&self.state, file) == JobStatus::Conflict
+                            }) {
+                                ui.label("Cannot process: resolve or ignore the conflicting jobs");
+                            } else {
+                                ui.label("Cannot process: No or invalid Config Files");
+                            }
+                            if ui
+                                .button(egui::RichText::new("Validate only").heading())
+                                .clicked()
+                            {
+                                self.validate_only();
+                            }
+                        }
+                        QueueState::ValidConfigs | QueueState::ProcessingDone => {
+                            if let Some(blocker) = self.video_blocker() {
+                                ui.label(
+                                    egui::RichText::new(blocker).color(egui::Color32::RED),
+                                );
+                            } else if self.is_checking_batch {
+                                ui.spinner();
+                                ui.label("Checking the batch…");
+                            } else if !self.space_problems.is_empty()
+                                || !self.duration_warnings.is_empty()
+                            {
+                                if !self.space_problems.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new(self.space_problems.join(". "))
+                                            .color(egui::Color32::RED),
+                                    );
+                                }
+                                if !self.duration_warnings.is_empty() {
+                                    let warnings = self
+                                        .duration_warnings
+                                        .iter()
+                                        .map(|(path, _, warning)| {
+                                            format!("{}: {}", path.display(), warning)
+                                        })
+                                        .collect::<Vec<_>>();
+                                    ui.label(
+                                        egui::RichText::new(warnings.join(". "))
+                                            .color(egui::Color32::YELLOW),
+                                    );
+                                    if ui
+                                        .button(egui::RichText::new("Use suggested rates").heading())
+                                        .clicked()
+                                    {
+                                        self.use_suggested_frame_rates();
+                                    }
+                                }
+                                if ui
+                                    .button(egui::RichText::new("Process anyway").heading())
+                                    .clicked()
+                                {
+                                    self.space_problems.clear();
+                                    self.duration_warnings.clear();
+                                    self.start_processing();
+                                }
+                                if ui.small_button("✕").clicked() {
+                                    self.space_problems.clear();
+                                    self.duration_warnings.clear();
+                                }
+                            } else {
+                                if ui
+                                    .button(egui::RichText::new("Process").heading())
+                                    .clicked()
+                                {
+                                    self.check_batch(false);
+                                }
+                                ui.label("Start at");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.scheduled_time)
+                                        .hint_text("HH:MM")
+                                        .desired_width(50.0),
+                                );
+                                if ui
+                                    .button(egui::RichText::new("Schedule").heading())
+                                    .on_hover_text(
+                                        "Start processing at this time of day, tomorrow if it has passed today",
+                                    )
+                                    .clicked()
+                                {
+                                    self.schedule_batch();
+                                }
+                            }
+                            if ui
+                                .button(egui::RichText::new("Validate only").heading())
+                                .on_hover_text(
+                                    "Check the configs, folders, source images and free disk space of every job without processing or writing anything",
+                                )
+                                .clicked()
+                            {
+                                self.validate_only();
+                            }
+                        }
+                        QueueState::Scheduled => {
+                            if let Some(start) = self.scheduled_start {
+                                let remaining = (start - chrono::Local::now().naive_local())
+                                    .to_std()
+                                    .unwrap_or_default();
+                                ui.label(format!(
+                                    "Starts at {} (in {})",
+                                    start.format("%H:%M"),
+                                    throughput::describe(remaining)
+                                ));
+                            }
+                            if ui
+                                .button(egui::RichText::new("Start now").heading())
+                                .clicked()
+                            {
+                                self.cancel_schedule();
+                                self.check_batch(false);
+                            }
+                            if ui
+                                .button(egui::RichText::new("Cancel schedule").heading())
+                                .clicked()
+                            {
+                                self.cancel_schedule();
+                            }
+                        }
+                        QueueState::ProcessingErrors => {
+                            ui.label(
+                                egui::RichText::new("Processing error.".to_owned())
+                                    .color(egui::Color32::RED),
+                            );
+                        }
+                    },
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if ui.button(egui::RichText::new("Clear").heading()).clicked() {
+                        self.request(LockedAction::Clear);
+                    }
+                    if !self.dropped_files.is_empty()
+                        && ui
+                            .button(egui::RichText::new("Export queue").heading())
+                            .clicked()
+                    {
+                        self.export_queue();
+                    }
+                    if !self.dropped_files.is_empty()
+                        && ui
+                            .button(egui::RichText::new("Export report").heading())
+                            .clicked()
+                    {
+                        self.export_report();
+                    }
+                    if ui
+                        .button(egui::RichText::new("Import SD card").heading())
+                        .clicked()
+                    {
+                        self.import_window.is_open = true;
+                    }
+                    let overdue = self.overdue_sites().len();
+                    let schedule = if overdue > 0 {
+                        egui::RichText::new(format!("{} sites overdue", overdue))
+                            .heading()
+                            .color(egui::Color32::RED)
+                    } else {
+                        egui::RichText::new("Render schedule").heading()
+                    };
+                    if ui.button(schedule).clicked() {
+                        self.is_schedule_open = true;
+                    }
+                    if ui
+                        .button(egui::RichText::new("History").heading())
+                        .clicked()
+                    {
+                        self.is_history_open = !self.is_history_open;
+                        if self.is_history_open {
+                            self.search_history();
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.dropped_files.is_empty() && !self.state.is_processing(),
+                            egui::Button::new(egui::RichText::new("Export session").heading()),
+                        )
+                        .on_hover_text("Save the queue and output settings to continue the batch on another machine. Unavailable while a batch runs.")
+                        .clicked()
+                    {
+                        self.export_session();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.state.is_processing(),
+                            egui::Button::new(egui::RichText::new("Import session").heading()),
+                        )
+                        .on_hover_text("Continue a batch exported on another machine")
+                        .clicked()
+                    {
+                        self.import_session();
+                    }
+                });
+            });
+            if self.state.is_processing() {
+                egui::CollapsingHeader::new("System").show(ui, |ui| {
+                    self.usage_ui(ui);
+                });
+            }
+            ui.add_space(10.0);
+        });
+    }
+
+    /// Returns the names and values of the settings that shape the outputs of a batch.
+    fn settings_summary(&self) -> [(&'static str, String); 3] {
+        let video = if self.is_video_enabled {
+            self.video_codec.label().to_owned()
+        } else {
+            Codec::None.label().to_owned()
+        };
+        let frame_rate = match self.target_duration {
+            Some(seconds) => format!("{} s video", seconds),
+            None => format!("{} fps", self.frame_rate),
+        };
+        let gap_filling = match self.gap_filling {
+            GapFilling::Skip => "Skip",
+            GapFilling::HoldLastFrame => "Hold last frame",
+            GapFilling::Slate => "No data slate",
+        };
+        [
+            ("Video", video),
+            ("Frame rate", frame_rate),
+            ("Missing days", gap_filling.to_owned()),
+        ]
+    }
+
+    /// Writes the queued jobs with their metadata, status and the current settings to a CSV file.
+    pub fn export_queue(&self) {
+        let Some(csv_path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("queue.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let [video, frame_rate, gap_filling] = self.settings_summary().map(|(_, value)| value);
+        let mut csv = utils::csv_line(
+            &[
+                "Path",
+                "Status",
+                "Location",
+                "Camera",
+                "Start date",
+                "End date",
+                "Date ranges",
+                "Output path",
+                "Forest Green",
+                "Video",
+                "Frame rate",
+                "Missing days",
+                "Error",
+                "Stage timings",
+            ]
+            .map(String::from),
+        );
+        for (path, file) in &self.dropped_files {
+            let status = item_state(&self.state, file).label();
+            let error = file.error_message();
+            let date_ranges = file
+                .date_ranges
+                .iter()
+                .map(|(start_date, end_date)| format!("{} to {}", start_date, end_date))
+                .collect::<Vec<_>>()
+                .join("; ");
+            let metadata = match self.job_config(file) {
+                Some(image_config) => vec![
+                    image_config.location.clone(),
+                    image_config.camera.clone(),
+                    image_config.start_date.to_string(),
+                    image_config.end_date.to_string(),
+                    date_ranges,
+                    image_config.output_path.display().to_string(),
+                ],
+                None => vec![String::new(); 6],
+            };
+            let mut fields = vec![path.display().to_string(), status.to_owned()];
+            fields.extend(metadata);
+            fields.extend([
+                self.is_forest_green_enabled.to_string(),
+                video.clone(),
+                frame_rate.clone(),
+                gap_filling.clone(),
+                error,
+                runner::describe_timings(&file.stage_timings),
+            ]);
+            csv += utils::csv_line(&fields).as_str();
+        }
+        if let Err(e) = std::fs::write(&csv_path, csv) {
+            log::error!("Error exporting queue to {}: {}", csv_path.display(), e);
+        }
+    }
+
+    /// Writes a record of the run of every queued job to a CSV, a JSON or a PDF file, depending
+    /// on the extension chosen.
+    pub fn export_report(&self) {
+        let Some(report_path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .add_filter("PDF", &["pdf"])
+            .set_file_name("report.csv")
+            .save_file()
+        else {
+            return;
+        };
+        if report_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+        {
+            self.export_pdf_report(report_path);
+            return;
+        }
+        let entries = self
+            .dropped_files
+            .iter()
+            .map(|(path, file)| {
+                let (location, camera) = match &file.config {
+                    Ok(image_config) => {
+                        (image_config.location.clone(), image_config.camera.clone())
+                    }
+                    Err(_) => (String::new(), String::new()),
+                };
+                let video = file.video().map(Path::to_path_buf);
+                let (start_date, end_date) = file.date_range().map_or_else(
+                    || (String::new(), String::new()),
+                    |(start_date, end_date)| (start_date.to_string(), end_date.to_string()),
+                );
+                let duration = file
+                    .stage_timings
+                    .iter()
+                    .map(|(_, duration)| duration.as_secs_f64())
+                    .sum::<f64>();
+                let manifest = self
+                    .job_config(file)
+                    .map(|image_config| runner::manifest_path(&image_config))
+                    .filter(|manifest| manifest.exists());
+                let manifest_digest = manifest
+                    .as_deref()
+                    .and_then(|manifest| utils::sha256(manifest).ok());
+                serde_json::json!({
+                    "path": path.display().to_string(),
+                    "location": location,
+                    "camera": camera,
+                    "start_date": start_date,
+                    "end_date": end_date,
+                    "status": item_state(&self.state, file).label(),
+                    "error": file.error_message(),
+                    "duration_seconds": duration,
+                    "video_path": video.map(|video| video.display().to_string()),
+                    "manifest_path": manifest.map(|manifest| manifest.display().to_string()),
+                    "manifest_sha256": manifest_digest,
+                    "flagged_frames": file
+                        .flagged_frames
+                        .iter()
+                        .filter_map(|frame| frame.file_name())
+                        .map(|name| name.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                })
+            })
+            .collect::<Vec<_>>();
+        let is_json = report_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let report = if is_json {
+            serde_json::to_string_pretty(&entries).unwrap_or_default()
+        } else {
+            let keys = [
+                "path",
+                "location",
+                "camera",
+                "start_date",
+                "end_date",
+                "status",
+                "error",
+                "duration_seconds",
+                "video_path",
+                "manifest_path",
+                "manifest_sha256",
+                "flagged_frames",
+            ];
+            let mut csv = utils::csv_line(
+                &[
+                    "Path",
+                    "Location",
+                    "Camera",
+                    "Start date",
+                    "End date",
+                    "Status",
+                    "Error",
+                    "Duration (s)",
+                    "Video path",
+                    "Manifest path",
+                    "Manifest SHA-256",
+                    "Flagged frames",
+                ]
+                .map(String::from),
+            );
+            for entry in &entries {
+                let fields = keys.map(|key| match &entry[key] {
+                    serde_json::Value::String(value) => value.clone(),
+                    serde_json::Value::Number(value) => {
+                        format!("{:.1}", value.as_f64().unwrap_or_default())
+                    }
+                    _ => String::new(),
+                });
+                csv += utils::csv_line(&fields).as_str();
+            }
+            csv
+        };
+        if let Err(e) = std::fs::write(&report_path, report) {
+            log::error!("Error exporting report to {}: {}", report_path.display(), e);
+        }
+    }
+
+    /// Writes the PDF report of the batch in the background, as it loads a frame of every site.
+    fn export_pdf_report(&self, report_path: PathBuf) {
+        let mut sites = Vec::new();
+        let mut failures = Vec::new();
+        for (path, file) in &self.dropped_files {
+            let state = item_state(&self.state, file);
+            if let (Some(image_config), Some(date_range)) =
+                (self.job_config(file), file.date_range())
+            {
+                sites.push(report::SiteSummary {
+                    site: site_name(&image_config.location, &image_config.camera),
+                    date_range,
+                    status: state.label().to_owned(),
+                    source_path: image_config.input_path.clone(),
+                    output_path: image_config.output_path.clone(),
+                    manifest_path: Some(runner::manifest_path(&image_config))
+                        .filter(|manifest| manifest.exists()),
+                    flagged_frames: file
+                        .flagged_frames
+                        .iter()
+                        .filter_map(|frame| frame.file_name())
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .collect(),
+                });
+            }
+            let error = file.error_message();
+            if !error.is_empty() {
+                failures.push((path.display().to_string(), error));
+            }
+        }
+        let mut settings = vec![(
+            "Forest Green".to_owned(),
+            if self.is_forest_green_enabled {
+                "On"
+            } else {
+                "Off"
+            }
+            .to_owned(),
+        )];
+        settings.extend(
+            self.settings_summary()
+                .map(|(name, value)| (name.to_owned(), value)),
+        );
+        let report = report::BatchReport {
+            title: format!(
+                "Tree Migration batch report {}",
+                chrono::Local::now().format("%Y-%m-%d")
+            ),
+            settings,
+            sites,
+            failures,
+        };
+        self.tasks.spawn_blocking(move || {
+            if let Err(e) = report::write_pdf(&report, &report_path) {
+                log::error!("Error exporting report to {}: {}", report_path.display(), e);
+            }
+        });
+    }
+
+    /// Applies what a running job reported.
+    fn on_job_event(&mut self, path: PathBuf, event: JobEvent) {
+        if !self.dropped_files.contains_key(&path) {
+            return;
+        }
+        match event {
+            JobEvent::SourcesCounted(count) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.source_count = Some(count);
+                }
+            }
+            JobEvent::EncodeAdapted(encode) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.adaptive_encode = Some(encode);
+                }
+            }
+            JobEvent::Progress(fraction) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.progress = Some(fraction);
+                    file.throughput.record(fraction);
+                }
+            }
+            JobEvent::StagesTimed(timings) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.stage_timings = timings;
+                }
+            }
+            JobEvent::Finished(Outcome::Done(delivery)) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    if let Some(deposit) = delivery.deposit {
+                        file.deposit_id = Some(deposit.id);
+                        file.deposit_draft = deposit.draft;
+                    }
+                    file.video = delivery.video;
+                }
+                self.dropped_files.entry(path.clone()).and_modify(|value| {
+                    value.done = Some(Ok(()));
+                    value.shortfalls = delivery.shortfalls;
+                    value.cancel = None;
+                });
+                self.record_history(&path);
+                self.consecutive_failures = 0;
+            }
+            JobEvent::Finished(Outcome::Failed(error)) => {
+                let message = error.to_string();
+                self.dropped_files.entry(path.clone()).and_modify(|value| {
+                    value.done = Some(Err(error));
+                    value.cancel = None;
+                });
+                self.record_history(&path);
+                self.on_job_failed(&message);
+            }
+            JobEvent::Finished(Outcome::ProxyReady(proxy)) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.done = Some(Ok(()));
+                    file.proxy = Some(proxy);
+                    file.review = Review::Pending;
+                    file.cancel = None;
+                }
+                self.consecutive_failures = 0;
+            }
+            JobEvent::Finished(Outcome::StageFailed(error)) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    let message = error.to_string();
+                    file.stage_error = Some(error);
+                    file.cancel = None;
+                    self.record_history(&path);
+                    self.on_job_failed(&message);
+                }
+            }
+            JobEvent::Deferred(run) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.is_queued = true;
+                    file.log.line(format!(
+                        "Waiting for another job with frames over {} MP",
+                        largeframe::LARGE_FRAME_PIXELS / 1_000_000
+                    ));
+                    let cancel = file.cancel.clone().unwrap_or_default();
+                    self.pending_jobs.defer(path, cancel, *run);
+                }
+            }
+            JobEvent::Finished(Outcome::Cancelled) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.cancel = None;
+                    file.is_cancelled = true;
+                }
+            }
+        }
+    }
+
+    pub fn poll(&mut self) {
+        while let Ok(signal) = self.channel.1.try_recv() {
+            match signal {
+                Signal::Job((path, event)) => self.on_job_event(path, event),
+                Signal::Verified((path, discrepancies)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.verification = Some(discrepancies);
+                    }
+                }
+                Signal::Validated((path, validation)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.validation = Some(validation);
+                    }
+                }
+                Signal::CleanedUp((path, result)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        match result {
+                            Ok(_) => {
+                                file.done = None;
+                                file.video = None;
+                                file.verification = None;
+                                file.cleanup_error = None;
+                                file.is_cancelled = false;
+                                file.crash = None;
+                                file.stage_error = None;
+                                file.shortfalls = Shortfalls::default();
+                                file.proxy = None;
+                                file.review = Review::None;
+                                file.flagged_frames.clear();
+                                file.excluded_frames.clear();
+                            }
+                            Err(e) => file.cleanup_error = Some(e.to_string()),
+                        }
+                    }
+                }
+                Signal::DatesDetected((path, result)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.detected_dates = Some(result);
+                    }
+                }
+                Signal::ScriptApplied((script_error, results)) => {
+                    self.script_error = script_error;
+                    for (path, result) in results {
+                        match result {
+                            Ok(result) => self.enqueue(&path, Some(result)),
+                            Err(e) => {
+                                if let Some(file) = self.dropped_files.get_mut(&path) {
+                                    file.done = None;
+                                    file.shortfalls = Shortfalls::default();
+                                    file.stage_error = Some(e);
+                                }
+                            }
+                        }
+                    }
+                    self.start_pending_jobs();
+                }
+                Signal::Published((path, result)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.is_publishing = false;
+                        match result {
+                            Ok(doi) => {
+                                file.deposit_id = Some(doi);
+                                file.deposit_draft = None;
+                            }
+                            Err(e) => {
+                                log::error!("Error publishing {} {}", path.display(), e);
+                                file.shortfalls.deposit_error = Some(e);
+                            }
+                        }
+                    }
+                }
+                Signal::BatchChecked((problems, duration_warnings)) => {
+                    self.is_checking_batch = false;
+                    let is_refused =
+                        !problems.is_empty() && self.disk_space_check == DiskSpaceCheck::Refuse;
+                    if !problems.is_empty() && !is_refused {
+                        log::warn!("{}", problems.join(". "));
+                        self.drop_message = Some(problems.join(". "));
+                    }
+                    if is_refused {
+                        if self.is_scheduled_check {
+                            log::error!(
+                                "The scheduled batch did not start: {}",
+                                problems.join(". ")
+                            );
+                        }
+                        self.space_problems = problems;
+                        self.duration_warnings = duration_warnings;
+                    } else if self.is_scheduled_check {
+                        for (path, _, warning) in &duration_warnings {
+                            log::warn!("{}: {}", path.display(), warning);
+                        }
+                        self.start_processing();
+                    } else if !duration_warnings.is_empty() {
+                        self.duration_warnings = duration_warnings;
+                    } else {
+                        self.start_processing();
+                    }
+                }
+                Signal::SourcesCounted((path, count)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.source_count = Some(count);
+                    }
+                }
+                Signal::FfmpegDownloaded(result) => {
+                    self.is_ffmpeg_downloading = false;
+                    match result {
+                        Ok(path) => {
+                            self.ffmpeg_path = Some(path);
+                            self.ffmpeg_error = None;
+                            self.check_health();
+                        }
+                        Err(e) => self.ffmpeg_error = Some(e),
+                    }
+                }
+                Signal::HealthChecked(health) => {
+                    self.health = Some(health);
+                }
+                Signal::FrameLoaded((frame, result)) => {
+                    if self.scrubber.loading.as_ref() == Some(&frame) {
+                        self.scrubber.loading = None;
+                    }
+                    match result {
+                        Ok(image) => self.scrubber.loaded = Some((frame, image)),
+                        Err(e) => log::error!("Error loading frame {} {}", frame.display(), e),
+                    }
+                }
+                Signal::PlayerFramesExtracted((dir, step, result)) => {
+                    if self.player.dir.as_ref() == Some(&dir) {
+                        match (&result, self.player.target_duration) {
+                            (Ok(frames), Some(seconds)) => {
+                                self.player.frame_rate = frames.len() as f64 / seconds.max(1) as f64
+                            }
+                            _ => self.player.frame_rate /= step as f64,
+                        }
+                        self.player.frames = Some(result);
+                        self.player.is_playing = true;
+                    }
+                }
+                Signal::SourcesPreviewed((path, result)) => {
+                    if let Some(file) = self.dropped_files.get_mut(&path) {
+                        file.source_preview = match result {
+                            Ok(images) => SourcePreview::Loaded(images),
+                            Err(e) => SourcePreview::Failed(e),
+                        };
+                    }
+                }
+                Signal::Transcoded((path, result)) => {
+                    if let Some(file) = self.transcode_files.get_mut(&path) {
+                        file.is_running = false;
+                        file.done = Some(result);
+                    }
+                }
+                Signal::Imported(result) => {
+                    self.import_window.is_importing = false;
+                    self.import_window.result = Some(match result {
+                        Ok(summary) => {
+                            for path in &summary.configs {
+                                let config = tree_migration::Config::from(path);
+                                self.dropped_files
+                                    .insert(path.clone(), FileEntry::new(config));
+                            }
+                            Ok(format!(
+                                "Imported {} images, skipped {} without EXIF data, created {} configs",
+                                summary.imported,
+                                summary.skipped.len(),
+                                summary.configs.len()
+                            ))
+                        }
+                        Err(e) => Err(e.to_string()),
+                    });
+                }
+            }
+        }
+        for (path, message) in self.tasks.reap() {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.cancel = None;
+                file.crash = Some(message);
+            }
+        }
+    }
+
+    /// Saves the queue and the output settings for another machine to continue the batch.
+    /// Refused while a batch runs, as both machines would then process its jobs.
+    pub fn export_session(&mut self) {
+        if self.state.is_processing() {
+            self.drop_message =
+                Some("Cannot export the session while a batch is processing".to_owned());
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("session.json")
+            .save_file()
+        else {
+            return;
+        };
+        let handoff = Handoff::new(
+            self.current_preset(self.preset_name.clone()),
+            &self.job_records(),
+        );
+        if let Err(e) = handoff.save(&path) {
+            log::error!("Error exporting session {}", e);
+        }
+    }
+
+    /// Adds the unfinished jobs of a session exported on another machine and applies its
+    /// output settings.
+    pub fn import_session(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let handoff = match Handoff::load(&path) {
+            Ok(handoff) => handoff,
+            Err(e) => {
+                self.drop_message = Some(format!("Invalid session file {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let mut done = 0;
+        let mut missing = Vec::new();
+        for record in handoff.records() {
+            if record.status == queue::RecordStatus::Done {
+                done += 1;
+                continue;
+            }
+            if !record.path.is_file() {
+                missing.push(record.path.display().to_string());
+            }
+            self.dropped_files
+                .insert(record.path.clone(), FileEntry::restore(&record));
+        }
+        self.apply_preset(handoff.settings);
+        let mut messages = vec![format!(
+            "Imported the session exported {}, {} jobs already done there",
+            handoff.exported_at, done
+        )];
+        if !missing.is_empty() {
+            messages.push(format!(
+                "Configs not found on this machine: {}",
+                missing.join(", ")
+            ));
+        }
+        self.drop_message = Some(messages.join(". "));
+    }
+
+    /// Creates the job of the config at `path` with the defaults of its location.
+    fn new_entry(&self, path: &Path) -> FileEntry {
+        let mut file = FileEntry::new(tree_migration::Config::from(path));
+        let defaults = file
+            .config
+            .as_ref()
+            .ok()
+            .and_then(|config| self.location_defaults.get(&config.location))
+            .cloned();
+        if let Some(defaults) = defaults {
+            if defaults.output_path.is_some() {
+                file.override_output_path(defaults.output_path);
+            }
+            file.overrides = defaults.overrides;
+        }
+        file
+    }
+
+    fn load_location_defaults(&mut self, path: PathBuf) {
+        match locations::load_defaults(&path) {
+            Ok(defaults) => {
+                self.location_defaults = defaults;
+                self.location_defaults_error = None;
+            }
+            Err(e) => {
+                self.location_defaults = HashMap::new();
+                self.location_defaults_error = Some(e);
+            }
+        }
+        self.location_defaults_path = Some(path);
+    }
+
+    /// Adds the jobs of a batch file with their overrides, replacing queued jobs of the same
+    /// configs. Settings the batch file leaves out come from the location defaults.
+    pub fn add_batch(&mut self, path: &Path) -> Result<(), String> {
+        let jobs = batch::load(path)
+            .map_err(|e| format!("Invalid batch file {}: {}", path.display(), e))?;
+        for job in jobs {
+            let mut file = self.new_entry(&job.config);
+            if job.output_path.is_some() {
+                file.override_output_path(job.output_path);
+            }
+            file.overrides.video_codec = job.overrides.video_codec.or(file.overrides.video_codec);
+            file.overrides.frame_rate = job.overrides.frame_rate.or(file.overrides.frame_rate);
+            file.overrides.camera_clock =
+                job.overrides.camera_clock.or(file.overrides.camera_clock);
+            self.dropped_files.insert(job.config, file);
+        }
+        Ok(())
+    }
+
+    /// Returns the folder the output layout assigns to a job, if the layout is enabled.
+    fn layout_dir(&self, image_config: &tree_migration::Config) -> Option<PathBuf> {
+        match &self.layout_root {
+            Some(root) if self.is_layout_enabled => {
+                layout::expand(&self.layout_template, image_config)
+                    .ok()
+                    .map(|dir| root.join(dir))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the config of a job with the output folder it runs with, which is the folder the
+    /// output layout assigns to it unless its output folder was overridden.
+    fn job_config(&self, file: &FileEntry) -> Option<tree_migration::Config> {
+        let image_config = file.config.as_ref().ok()?;
+        let output_path = match self.layout_dir(image_config) {
+            Some(dir) if file.default_output_path.is_none() => output_in(&dir, image_config, ""),
+            _ => image_config.output_path.clone(),
+        };
+        Some(tree_migration::Config {
+            output_path,
+            ..image_config.clone()
+        })
+    }
+
+    /// Returns the folder a job's video is written to.
+    fn job_video_output_path(&self, image_config: &tree_migration::Config) -> Option<PathBuf> {
+        self.layout_dir(image_config)
+            .or_else(|| self.video_output_path.clone())
+    }
+
+    fn video_name(&self, overrides: &JobOverrides, collision: usize) -> VideoName {
+        VideoName {
+            template: self.video_name_template.clone(),
+            frame_rate: overrides.frame_rate.unwrap_or(self.frame_rate),
+            codec: overrides.video_codec.unwrap_or(self.video_codec),
+            date_format: self.date_format.clone(),
+            collision,
+        }
+    }
+
+    /// Picks the format of dates in video names, the overlay and the slates.
+    fn date_format_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Dates")
+                .selected_text(self.date_format.label())
+                .show_ui(ui, |ui| {
+                    for date_format in [
+                        DateFormat::Iso,
+                        DateFormat::Dmy,
+                        DateFormat::Custom("%Y%m%d".to_owned()),
+                    ] {
+                        let is_selected = std::mem::discriminant(&self.date_format)
+                            == std::mem::discriminant(&date_format);
+                        let label = date_format.label();
+                        if ui.selectable_label(is_selected, label).clicked() && !is_selected {
+                            self.date_format = date_format;
+                        }
+                    }
+                });
+            if let DateFormat::Custom(pattern) = &mut self.date_format {
+                ui.add(egui::TextEdit::singleline(pattern).desired_width(100.0))
+                    .on_hover_text("strftime pattern, e.g. %Y%m%d or %d.%m.%Y");
+            }
+        })
+        .response
+        .on_hover_text("Format of the dates in video names, the capture date overlay and the slates. Image folders keep ISO dates");
+        if let Err(e) = self.date_format.validate() {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+    }
+
+    /// Edits the file name template of videos with a preview of the name of the first job.
+    fn video_name_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.video_name_template).desired_width(260.0))
+                .on_hover_text(format!(
+                    "Placeholders: {}",
+                    NAME_PLACEHOLDERS
+                        .map(|placeholder| format!("{{{}}}", placeholder))
+                        .join(", ")
+                ));
+            ui.label("File name");
+            if self.video_name_template != DEFAULT_NAME_TEMPLATE && ui.button("Reset").clicked() {
+                self.video_name_template = DEFAULT_NAME_TEMPLATE.to_owned();
+            }
+        });
+        match validate_name_template(&self.video_name_template) {
+            Ok(()) => {
+                let name = self.video_name(&JobOverrides::default(), 0);
+                let preview = match self
+                    .dropped_files
+                    .values()
+                    .find_map(|file| file.config.as_ref().ok())
+                {
+                    Some(image_config) => name.file_name(image_config),
+                    None => name.fill(
+                        "site",
+                        "camera1",
+                        &self.date_format.format(
+                            chrono::NaiveDate::from_ymd_opt(2023, 4, 1).unwrap_or_default(),
+                        ),
+                        &self.date_format.format(
+                            chrono::NaiveDate::from_ymd_opt(2023, 9, 30).unwrap_or_default(),
+                        ),
+                    ),
+                };
+                ui.label(format!("Preview: {}", preview));
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
+    }
+
+    /// Returns why a job with its own `ffmpeg_path`, if any, would be rendered without video
+    /// although video is enabled.
+    fn missing_video_reason(
+        &self,
+        ffmpeg_path: Option<&PathBuf>,
+        overrides: &JobOverrides,
+    ) -> Option<&'static str> {
+        if !self.is_video_enabled {
+            None
+        } else if overrides.video_codec.unwrap_or(self.video_codec) == Codec::None {
+            Some("no codec")
+        } else if ffmpeg_path.or(self.ffmpeg_path.as_ref()).is_none() {
+            Some("no ffmpeg")
+        } else {
+            None
+        }
+    }
+
+    /// Returns why the batch cannot start when jobs would be rendered without video and that
+    /// is not allowed.
+    fn video_blocker(&self) -> Option<String> {
+        if self.is_video_enabled {
+            if let Err(e) = validate_name_template(&self.video_name_template) {
+                return Some(format!("Cannot process: {}", e));
+            }
+            if let Err(e) = self.date_format.validate() {
+                return Some(format!("Cannot process: {}", e));
+            }
+        }
+        if self.missing_video == MissingVideo::Skip {
+            return None;
+        }
+        let reasons = self
+            .dropped_files
+            .values()
+            .filter(|file| file.config.is_ok())
+            .filter_map(|file| {
+                self.missing_video_reason(file.ffmpeg_path.as_ref(), &file.overrides)
+            })
+            .collect::<BTreeSet<_>>();
+        (!reasons.is_empty()).then(|| {
+            format!(
+                "Cannot process: video is enabled but there is {}",
+                reasons.into_iter().collect::<Vec<_>>().join(" and ")
+            )
+        })
+    }
+
+    /// Queues the jobs of `paths` and starts them. With a job script, the script runs for them
+    /// in the background first and the jobs are queued with what it changed for this run
+    /// once it finished.
+    fn queue_jobs(&mut self, paths: Vec<PathBuf>) {
+        let Some(script_path) = self.script_path.clone().filter(|_| self.is_script_enabled) else {
+            for path in &paths {
+                self.enqueue(path, None);
+            }
+            self.start_pending_jobs();
+            return;
+        };
+        let jobs = paths
+            .into_iter()
+            .filter_map(|path| {
+                let file = self.dropped_files.get(&path)?;
+                let image_config = self.job_config(file)?;
+                Some((path, image_config, file.overrides.clone()))
+            })
+            .collect::<Vec<_>>();
+        let (video_codec, frame_rate) = (self.video_codec, self.frame_rate);
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let script = JobScript::load(&script_path);
+            let results = jobs
+                .into_iter()
+                .map(|(path, image_config, overrides)| {
+                    let result = match &script {
+                        Ok(script) => script
+                            .apply(&image_config, &overrides, video_codec, frame_rate)
+                            .map_err(|e| format!("Job script failed: {}", e)),
+                        Err(e) => Err(format!("Cannot load job script: {}", e)),
+                    };
+                    (path, result)
+                })
+                .collect();
+            let _ = sender.send(Signal::ScriptApplied((script.err(), results)));
+        });
+    }
+
+    fn start_processing(&mut self) {
+        self.state = QueueState::Processing;
+        self.consecutive_failures = 0;
+        self.process();
+    }
+
+    /// Schedules the batch to start at `scheduled_time`, today or, if that has passed, tomorrow.
+    fn schedule_batch(&mut self) {
+        let time = match chrono::NaiveTime::parse_from_str(self.scheduled_time.trim(), "%H:%M") {
+            Ok(time) => time,
+            Err(_) => {
+                self.drop_message = Some(format!(
+                    "Cannot schedule the batch: \"{}\" is not a time like 22:30",
+                    self.scheduled_time
+                ));
+                return;
+            }
+        };
+        let now = chrono::Local::now().naive_local();
+        let mut start = now.date().and_time(time);
+        if start <= now {
+            start += chrono::Duration::days(1);
+        }
+        log::info!("Batch scheduled to start at {}", start);
+        self.scheduled_start = Some(start);
+        self.state = QueueState::Scheduled;
+    }
+
+    fn cancel_schedule(&mut self) {
+        self.scheduled_start = None;
+        self.state = QueueState::ValidConfigs;
+    }
+
+    /// Starts the scheduled batch once its time has come, and repaints every second until then
+    /// so that the countdown runs.
+    fn start_scheduled_batch(&mut self, ctx: &egui::Context) {
+        if self.state != QueueState::Scheduled {
+            self.scheduled_start = None;
+            return;
+        }
+        match self.scheduled_start {
+            Some(start) if start > chrono::Local::now().naive_local() => {
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+            _ => {
+                log::info!("Starting the scheduled batch");
+                self.cancel_schedule();
+                self.check_batch(true);
+            }
+        }
+    }
+
+    /// Estimates the outputs of every valid job and the length of their videos in the
+    /// background and starts processing once they are known to fit on their disks, as
+    /// configured by `disk_space_check`, and to make videos of a sensible length.
+    /// A scheduled batch runs unattended, so it only logs the warnings and starts anyway.
+    fn check_batch(&mut self, is_scheduled: bool) {
+        let is_space_checked = self.disk_space_check != DiskSpaceCheck::Off;
+        let is_duration_checked = self.is_video_enabled && self.target_duration.is_none();
+        if !is_space_checked && !is_duration_checked {
+            self.start_processing();
+            return;
+        }
+        let jobs = self
+            .dropped_files
+            .iter()
+            .filter_map(|(path, file)| {
+                let (Some(image_config), Some((start_date, end_date))) =
+                    (self.job_config(file), file.date_range())
+                else {
+                    return None;
+                };
+                let codec = file.overrides.video_codec.unwrap_or(self.video_codec);
+                let video = (self.is_video_enabled && codec != Codec::None).then(|| {
+                    let folder = self
+                        .job_video_output_path(&image_config)
+                        .unwrap_or_else(|| image_config.output_path.clone());
+                    (codec, folder)
+                });
+                let frame_rate = (is_duration_checked && video.is_some())
+                    .then(|| file.overrides.frame_rate.unwrap_or(self.frame_rate));
+                let image_config = tree_migration::Config {
+                    start_date,
+                    end_date,
+                    ..image_config
+                };
+                Some((path.clone(), image_config, video, frame_rate))
+            })
+            .collect::<Vec<_>>();
+        self.is_checking_batch = true;
+        self.is_scheduled_check = is_scheduled;
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let mut writes = Vec::new();
+            let mut duration_warnings = Vec::new();
+            for (path, image_config, video, frame_rate) in jobs {
+                if let Some(frame_rate) = frame_rate {
+                    let frames =
+                        utils::source_images(&image_config).map_or(0, |images| images.len());
+                    if let Some((suggested, warning)) =
+                        preflight::check_duration(frames, frame_rate)
+                    {
+                        duration_warnings.push((path, suggested, warning));
+                    }
+                }
+                if is_space_checked {
+                    writes.extend(
+                        preflight::estimate_writes(&image_config, video)
+                            .into_iter()
+                            .flatten(),
+                    );
+                }
+            }
+            let problems = preflight::check_space(&writes);
+            let _ = sender.send(Signal::BatchChecked((problems, duration_warnings)));
+        });
+    }
+
+    /// Overrides the frame rate of the jobs with duration warnings with the suggested rates,
+    /// and starts processing unless the disk space check refused the batch.
+    fn use_suggested_frame_rates(&mut self) {
+        for (path, suggested, _) in std::mem::take(&mut self.duration_warnings) {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.overrides.frame_rate = Some(suggested);
+            }
+        }
+        if self.space_problems.is_empty() {
+            self.start_processing();
+        }
+    }
+
+    pub fn process(&mut self) {
+        for file in self.dropped_files.values_mut() {
+            file.previous_error = None;
+        }
+        self.batch_throughput.clear();
+        let paths = self.dropped_files.keys().cloned().collect::<Vec<_>>();
+        self.queue_jobs(paths);
+    }
+
+    /// Queues the job of `path` with the current settings and what the job script changed
+    /// for this run, if one ran. In review mode only its proxy is rendered unless it was
+    /// approved.
+    fn enqueue(&mut self, path: &Path, script_result: Option<ScriptResult>) {
+        let Some(file) = self.dropped_files.get(path) else {
+            return;
+        };
+        let (Some(image_config), Some((start_date, end_date))) =
+            (self.job_config(file), file.date_range())
+        else {
+            return;
+        };
+        let (output_path, overrides) = match script_result {
+            Some(result) => (result.output_path, result.overrides),
+            None => (image_config.output_path.clone(), file.overrides.clone()),
+        };
+        let image_config = tree_migration::Config {
+            output_path,
+            start_date,
+            end_date,
+            ..image_config
+        };
+        let mut settings = self.job_settings(&image_config, file.ffmpeg_path.as_ref(), &overrides);
+        settings.excluded_frames = file.excluded_frames.clone();
+        settings.config_path = path.to_path_buf();
+        settings.video_name_collision = file.video_name_collision;
+        let is_approved = file.review == Review::Approved;
+        settings.is_review_proxy =
+            self.is_review_enabled && !is_approved && settings.ffmpeg_path.is_some();
+        let video_skipped = self.missing_video_reason(file.ffmpeg_path.as_ref(), &overrides);
+        let date_ranges = file.date_ranges.clone();
+        let cancel = CancelToken::default();
+        if let Some(file) = self.dropped_files.get_mut(path) {
+            file.video_skipped = video_skipped;
+            file.is_video_name_collision_fixed = true;
+            file.cancel = Some(cancel.clone());
+            file.is_cancelled = false;
+            file.is_queued = true;
+            file.progress = None;
+            file.throughput.clear();
+            file.source_count = None;
+            file.adaptive_encode = None;
+            file.stage_timings.clear();
+            file.crash = None;
+            file.stage_error = None;
+            file.shortfalls = Shortfalls::default();
+            file.video = None;
+            if !is_approved {
+                file.proxy = None;
+                file.review = Review::None;
+            }
+        }
+        self.pending_jobs.push(
+            path.to_path_buf(),
+            cancel,
+            JobRun {
+                image_config,
+                date_ranges,
+                settings,
+                is_large: false,
+            },
+        );
+    }
+
+    /// Runs the full render of a job whose proxy was approved.
+    fn approve(&mut self, path: &Path) {
+        if let Some(file) = self.dropped_files.get_mut(path) {
+            file.review = Review::Approved;
+            file.done = None;
+        }
+        self.restart(path);
+    }
+
+    /// Renders the video of a done job again without its flagged frames. The frames are
+    /// taken from the frame cache, so only the video and the later stages run.
+    fn exclude_flagged_frames(&mut self, path: &Path) {
+        if let Some(file) = self.dropped_files.get_mut(path) {
+            file.excluded_frames = file.flagged_frames.clone();
+            file.done = None;
+            file.log.line(format!(
+                "Excluding {} flagged frames from the video",
+                file.excluded_frames.len()
+            ));
+        }
+        self.restart(path);
+    }
+
+    /// Queues the job of `path` again and starts processing if the queue is idle.
+    fn restart(&mut self, path: &Path) {
+        if !self.state.is_processing() {
+            self.state = QueueState::Processing;
+        }
+        self.queue_jobs(vec![path.to_path_buf()]);
+    }
+
+    /// Stops starting pending jobs and suspends the running encodes.
+    fn pause(&mut self) {
+        self.state = QueueState::Paused;
+        self.batch_throughput.clear();
+        children::pause();
+    }
+
+    fn resume(&mut self) {
+        self.state = QueueState::Processing;
+        children::resume();
+    }
+
+    /// Returns how many jobs may run at once, fewer than `max_parallel_jobs` while throttling
+    /// is enabled and the machine runs too hot or too busy.
+    fn parallel_job_limit(&mut self) -> usize {
+        let reason = if self.throttle.is_enabled {
+            self.load.sample();
+            self.throttle.reason(&self.load)
+        } else {
+            None
+        };
+        match (&self.throttle_reason, &reason) {
+            (None, Some(reason)) => log::info!("Throttling the batch: {}", reason),
+            (Some(_), None) => log::info!("Throttling lifted"),
+            _ => {}
+        }
+        self.throttle_reason = reason;
+        self.throttle
+            .parallel_jobs(self.max_parallel_jobs, self.throttle_reason.is_some())
+    }
+
+    /// Starts pending jobs while fewer than the parallel job limit run, unless processing is
+    /// paused. Pending jobs that were cancelled are marked as such without starting.
+    fn start_pending_jobs(&mut self) {
+        for path in self.pending_jobs.remove_cancelled() {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.is_queued = false;
+                file.is_cancelled = true;
+            }
+        }
+        let running = self
+            .dropped_files
+            .values()
+            .filter(|file| file.cancel.is_some() && !file.is_queued)
+            .count();
+        let max_jobs = self.parallel_job_limit();
+        for job in self
+            .pending_jobs
+            .start(&self.state, running, max_jobs, JobRun::can_start)
+        {
+            let Some(file) = self.dropped_files.get_mut(&job.path) else {
+                continue;
+            };
+            file.is_queued = false;
+            file.log = JobLog::default();
+            let log = file.log.clone();
+            let is_adaptive = self.is_adaptive_encoding_enabled
+                && job.job.settings.ffmpeg_path.is_some()
+                && !job.job.settings.is_review_proxy;
+            let on_event = {
+                let sender = self.channel.0.clone();
+                let path = job.path.clone();
+                Arc::new(move |event| {
+                    let _ = sender.send(Signal::Job((path.clone(), event)));
+                })
+            };
+            self.tasks.spawn_job(
+                job.path,
+                job.job.run(is_adaptive, job.cancel, log, on_event),
+            );
+        }
+    }
+
+    /// Finds the valid jobs that would race each other: those covering overlapping days of the
+    /// same site and those writing to the same output folder.
+    fn detect_conflicts(&mut self) {
+        let jobs = self
+            .dropped_files
+            .iter()
+            .filter_map(
+                |(path, file)| match (self.job_config(file), file.date_range()) {
+                    (Some(image_config), Some(date_range)) => Some((
+                        path.clone(),
+                        image_config,
+                        date_range,
+                        file.is_conflict_ignored,
+                    )),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<_>>();
+        for (path, file) in self.dropped_files.iter_mut() {
+            file.conflicts.clear();
+            let Some((_, image_config, (start_date, end_date), is_ignored)) =
+                jobs.iter().find(|(other, ..)| other == path)
+            else {
+                continue;
+            };
+            for (other, other_config, (other_start, other_end), is_other_ignored) in &jobs {
+                if other == path {
+                    continue;
+                }
+                let reason = if runner::sanitize(&other_config.location)
+                    == runner::sanitize(&image_config.location)
+                    && runner::sanitize(&other_config.camera)
+                        == runner::sanitize(&image_config.camera)
+                    && other_start <= end_date
+                    && start_date <= other_end
+                {
+                    "Same site and overlapping dates as"
+                } else if other_config.output_path == image_config.output_path {
+                    "Same output folder as"
+                } else {
+                    continue;
+                };
+                file.conflicts.push(JobConflict {
+                    path: other.clone(),
+                    reason,
+                    is_ignored: *is_ignored || *is_other_ignored,
+                });
+            }
+        }
+        self.number_video_name_collisions();
+    }
+
+    /// Numbers the jobs whose videos would get the same path, e.g. sites that only differ in
+    /// characters left out of file names, so that they overwrite neither each other nor videos
+    /// already on disk. Jobs that started keep their number.
+    fn number_video_name_collisions(&mut self) {
+        let numbered_video = |file: &FileEntry, collision| {
+            let image_config = file.config.as_ref().ok()?;
+            Some(video_path(
+                image_config,
+                &self.job_video_output_path(image_config),
+                &self.video_name(&file.overrides, collision),
+            ))
+        };
+        let mut videos = HashSet::new();
+        for file in self
+            .dropped_files
+            .values()
+            .filter(|file| file.is_video_name_collision_fixed)
+        {
+            videos.extend(numbered_video(file, file.video_name_collision));
+            videos.extend(file.video.clone());
+        }
+        let collisions = self
+            .dropped_files
+            .iter()
+            .filter(|(_, file)| !file.is_video_name_collision_fixed)
+            .map(|(path, file)| {
+                let mut collision = 0;
+                while let Some(video) = numbered_video(file, collision) {
+                    // The job's own video of an earlier run is overwritten, not numbered.
+                    let is_taken = videos.contains(&video)
+                        || (video.exists() && file.video.as_ref() != Some(&video));
+                    if !is_taken {
+                        videos.insert(video);
+                        break;
+                    }
+                    collision += 1;
+                }
+                (path.clone(), collision)
+            })
+            .collect::<Vec<_>>();
+        for (path, collision) in collisions {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.video_name_collision = collision;
+            }
+        }
+    }
+
+    /// Returns, for each queued file, the other queued files whose parsed config is identical,
+    /// as happens when synced folders keep copies of the same config.
+    fn duplicate_configs(&self) -> HashMap<PathBuf, Vec<PathBuf>> {
+        let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for (path, file) in &self.dropped_files {
+            if let Some(hash) = file.config.as_ref().ok().and_then(utils::config_hash) {
+                by_hash.entry(hash).or_default().push(path.clone());
+            }
+        }
+        let mut duplicates = HashMap::new();
+        for paths in by_hash.into_values().filter(|paths| paths.len() > 1) {
+            for path in &paths {
+                let mut others = paths.clone();
+                others.retain(|other| other != path);
+                others.sort();
+                duplicates.insert(path.clone(), others);
+            }
+        }
+        duplicates
+    }
+
+    /// Lists the CPU and memory usage of the app and of the ffmpeg processes of running jobs.
+    fn usage_ui(&mut self, ui: &mut egui::Ui) {
+        self.usage.sample();
+        ui.ctx().request_repaint_after(crate::usage::INTERVAL);
+        if let Some(usage) = self.usage.app() {
+            ui.label(format!(
+                "App (migrations of all jobs): {}",
+                usage.describe()
+            ));
+        }
+        let mut running = self
+            .dropped_files
+            .iter()
+            .filter(|(_, file)| {
+                let state = item_state(&self.state, file);
+                state == JobStatus::Processing || state == JobStatus::Paused
+            })
+            .filter_map(|(path, file)| match (&file.config, file.date_range()) {
+                (Ok(image_config), Some((start_date, end_date))) => Some((
+                    path,
+                    output_name(&tree_migration::Config {
+                        start_date,
+                        end_date,
+                        ..image_config.clone()
+                    }),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        running.sort();
+        for (path, output_name) in running {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            match self.usage.children_with(&output_name) {
+                Some(usage) => ui.label(format!("{}: ffmpeg {}", name, usage.describe())),
+                None => ui.label(format!("{}: runs in the app", name)),
+            };
+        }
+        let children = children::list();
+        if children.is_empty() {
+            return;
+        }
+        ui.add_space(10.0);
+        ui.label("ffmpeg processes");
+        for (pid, child) in children {
+            ui.horizontal(|ui| {
+                ui.monospace(pid.to_string());
+                ui.label(format!("{} s", child.started.elapsed().as_secs()));
+                if self.usage.is_zombie(pid) {
+                    ui.label(egui::RichText::new("Zombie").color(egui::Color32::RED));
+                }
+                if ui.small_button("Kill").clicked() {
+                    children::kill(pid);
+                }
+                ui.label(&child.command).on_hover_text(&child.command);
+            });
+        }
+    }
+
+    /// Returns the images per second of a running job at its recent rate.
+    fn images_per_second(file: &FileEntry) -> Option<f32> {
+        Some(file.throughput.rate()? * file.source_count? as f32)
+    }
+
+    /// Returns the position of every waiting job in the queue, from 1, with the estimated time
+    /// until it starts. The estimate needs the throughput of every running job, and assumes
+    /// that waiting jobs take as long as the running ones.
+    fn queue_estimates(&self) -> HashMap<PathBuf, (usize, Option<Duration>)> {
+        let running = self
+            .dropped_files
+            .values()
+            .filter(|file| item_state(&self.state, file) == JobStatus::Processing)
+            .map(|file| Some((file.throughput.remaining()?, file.throughput.rate()?)))
+            .collect::<Option<Vec<_>>>()
+            .filter(|running| !running.is_empty());
+        let start_times = running.map(|running| {
+            let job_seconds =
+                running.iter().map(|(_, rate)| 1.0 / rate).sum::<f32>() / running.len() as f32;
+            throughput::start_times(
+                &running
+                    .iter()
+                    .map(|(remaining, _)| *remaining)
+                    .collect::<Vec<_>>(),
+                self.max_parallel_jobs,
+                Duration::from_secs_f32(job_seconds),
+                self.pending_jobs.len(),
+            )
+        });
+        self.pending_jobs
+            .paths()
+            .enumerate()
+            .map(|(i, path)| {
+                let start = start_times.as_ref().map(|start_times| start_times[i]);
+                (path.to_path_buf(), (i + 1, start))
+            })
+            .collect()
+    }
+
+    /// Describes the recent throughput of the batch and the time left at that rate.
+    fn batch_eta(&self) -> Option<String> {
+        let remaining = self.batch_throughput.remaining()?;
+        let images_per_second = self
+            .dropped_files
+            .values()
+            .filter(|file| item_state(&self.state, file) == JobStatus::Processing)
+            .filter_map(Self::images_per_second)
+            .sum::<f32>();
+        Some(format!(
+            "About {} left, {:.1} images/s",
+            throughput::describe(remaining),
+            images_per_second
+        ))
+    }
+
+    /// Returns the average progress of all valid jobs, counting finished jobs as complete.
+    fn batch_progress(&self) -> f32 {
+        let progress = self
+            .dropped_files
+            .values()
+            .filter_map(|file| match item_state(&self.state, file) {
+                JobStatus::Processing | JobStatus::Paused => Some(file.progress.unwrap_or(0.0)),
+                JobStatus::Queued => Some(0.0),
+                state if state.is_done() => Some(1.0),
+                JobStatus::ProcessingError | JobStatus::Cancelled => Some(1.0),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        progress.iter().sum::<f32>() / progress.len().max(1) as f32
+    }
+
+    /// Takes a snapshot of the settings a job runs with, using the job's own ffmpeg binary if
+    /// it has one.
+    fn job_settings(
+        &self,
+        image_config: &tree_migration::Config,
+        ffmpeg_path: Option<&PathBuf>,
+        overrides: &JobOverrides,
+    ) -> JobSettings {
+        let video_codec = overrides.video_codec.unwrap_or(self.video_codec);
+        JobSettings {
+            is_forest_green_enabled: self.is_forest_green_enabled,
+            is_source_quarantine_enabled: self.is_source_quarantine_enabled,
+            is_date_overlay_enabled: self.is_date_overlay_enabled,
+            is_title_slate_enabled: self.is_title_slate_enabled,
+            is_video_failure_fatal: self.is_video_failure_fatal,
+            gap_filling: self.gap_filling,
+            is_xmp_enabled: self.is_xmp_enabled,
+            sequence_export_path: if self.is_sequence_export_enabled {
+                self.sequence_export_path.clone()
+            } else {
+                None
+            },
+            ffmpeg_path: if self.is_video_enabled && video_codec != Codec::None {
+                ffmpeg_path.or(self.ffmpeg_path.as_ref()).cloned()
+            } else {
+                None
+            },
+            is_review_proxy: false,
+            excluded_frames: BTreeSet::new(),
+            capture_order_clock: self
+                .is_capture_order_enabled
+                .then(|| overrides.camera_clock.unwrap_or_default()),
+            config_path: PathBuf::new(),
+            hooks: self.hooks.clone(),
+            video_codec,
+            codec_options: self.codec_options.clone(),
+            ffmpeg_environment: self.ffmpeg_environment.clone(),
+            video_output_path: self.job_video_output_path(image_config),
+            video_name_template: self.video_name_template.clone(),
+            date_format: self.date_format.clone(),
+            video_name_collision: 0,
+            frame_rate: overrides.frame_rate.unwrap_or(self.frame_rate),
+            target_duration: self.target_duration,
+            temp_path: self.temp_path.clone(),
+            preview_format: if self.is_preview_enabled {
+                Some(self.preview_format)
+            } else {
+                None
+            },
+            is_manifest_enabled: self.is_manifest_enabled,
+            is_profiling_enabled: self.is_profiling_enabled,
+            archive_output_path: if self.is_archive_enabled {
+                self.archive_output_path.clone()
+            } else {
+                None
+            },
+            deposit: if self.is_archive_enabled && self.is_deposit_enabled {
+                Some(self.deposit_settings.clone())
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn verify(&self, path: &PathBuf) {
+        let Some(
+            file @ FileEntry {
+                ffmpeg_path,
+                overrides,
+                video_name_collision,
+                ..
+            },
+        ) = self.dropped_files.get(path)
+        else {
+            return;
+        };
+        let Some(image_config) = self.job_config(file) else {
+            return;
+        };
+        let ffmpeg_path = ffmpeg_path.as_ref().or(self.ffmpeg_path.as_ref());
+        let video_name = self.video_name(overrides, *video_name_collision);
+        let is_video_expected = self.is_video_enabled && video_name.codec != Codec::None;
+        let video = match (ffmpeg_path, &file.video) {
+            (Some(ffmpeg_path), Some(video)) => Some((
+                verify::ffprobe_path(ffmpeg_path),
+                video.clone(),
+                self.target_duration,
+            )),
+            (Some(ffmpeg_path), None) if is_video_expected => Some((
+                verify::ffprobe_path(ffmpeg_path),
+                video_path(
+                    &image_config,
+                    &self.job_video_output_path(&image_config),
+                    &video_name,
+                ),
+                self.target_duration,
+            )),
+            _ => None,
+        };
+        let excluded_frames = file.excluded_frames.clone();
+        let gap_ranges = (self.gap_filling != GapFilling::Skip).then(|| file.date_ranges.clone());
+        let sender = self.channel.0.clone();
+        let path = path.clone();
+        self.tasks.spawn_blocking(move || {
+            let discrepancies = verify::verify(
+                &image_config,
+                &excluded_frames,
+                gap_ranges.as_deref(),
+                video,
+            );
+            let _ = sender.send(Signal::Verified((path, discrepancies)));
+        });
+    }
+
+    /// Checks every job as far as possible without running it or writing anything, reloading
+    /// its config file.
+    pub fn validate_only(&mut self) {
+        for (path, file) in self.dropped_files.iter_mut() {
+            file.validation = None;
+            let output_override = file
+                .default_output_path
+                .as_ref()
+                .and(file.config.as_ref().ok())
+                .map(|config| config.output_path.clone());
+            let date_range = (!file.date_ranges.is_empty())
+                .then(|| file.date_range())
+                .flatten();
+            let sender = self.channel.0.clone();
+            let path = path.clone();
+            self.tasks.spawn_blocking(move || {
+                let validation = match tree_migration::Config::from(&path) {
+                    Ok(image_config) => {
+                        let (start_date, end_date) =
+                            date_range.unwrap_or((image_config.start_date, image_config.end_date));
+                        preflight::validate(&tree_migration::Config {
+                            output_path: output_override
+                                .unwrap_or_else(|| image_config.output_path.clone()),
+                            start_date,
+                            end_date,
+                            ..image_config
+                        })
+                    }
+                    Err(e) => Validation::failed(e.to_string()),
+                };
+                let _ = sender.send(Signal::Validated((path, validation)));
+            });
+        }
+    }
+
+    pub fn detect_dates(&self, path: &Path) {
+        let sender = self.channel.0.clone();
+        let path = path.to_path_buf();
+        self.tasks.spawn_blocking(move || {
+            let result = dates::detect(&path);
+            let _ = sender.send(Signal::DatesDetected((path, result)));
+        });
+    }
+
+    /// Writes the detected date range into the config file and validates it again.
+    pub fn apply_dates(&mut self, path: &Path) {
+        let Some(file) = self.dropped_files.get_mut(path) else {
+            return;
+        };
+        let Some(Ok(range)) = file.detected_dates else {
+            return;
+        };
+        match dates::apply(path, range) {
+            Ok(_) => file.reload(path),
+            Err(e) => file.detected_dates = Some(Err(e)),
+        }
+    }
+
+    /// Writes a copy of a finished job's config for the following period next to it and queues it.
+    pub fn rerun_next_period(&mut self, path: &Path) {
+        let Some(file) = self.dropped_files.get(path) else {
+            return;
+        };
+        let (Ok(image_config), Some(range)) = (&file.config, file.date_range()) else {
+            return;
+        };
+        let image_config = image_config.clone();
+        let output_override = file
+            .default_output_path
+            .as_ref()
+            .map(|_| image_config.output_path.clone());
+        self.queue_config_copy(
+            path,
+            &image_config,
+            dates::next_period(range),
+            output_override,
+        );
+    }
+
+    /// Queues a copy of the config at `path` for `range`, written next to it unless it exists.
+    fn queue_config_copy(
+        &mut self,
+        path: &Path,
+        image_config: &tree_migration::Config,
+        (start_date, end_date): dates::DateRange,
+        output_override: Option<PathBuf>,
+    ) {
+        let new_path = path.with_file_name(
+            output_name(&tree_migration::Config {
+                start_date,
+                end_date,
+                ..image_config.clone()
+            }) + ".toml",
+        );
+        if self.dropped_files.contains_key(&new_path) {
+            return;
+        }
+        if !new_path.exists() {
+            if let Err(e) = dates::clone_config(path, &new_path, (start_date, end_date)) {
+                log::error!("Error writing config {}: {}", new_path.display(), e);
+                return;
+            }
+        }
+        let mut file = FileEntry::new(tree_migration::Config::from(&new_path));
+        file.override_output_path(output_override);
+        self.dropped_files.insert(new_path, file);
+    }
+
+    pub fn open_date_ranges(&mut self, path: &Path) {
+        let Some(FileEntry {
+            config: Ok(image_config),
+            date_ranges,
+            ..
+        }) = self.dropped_files.get(path)
+        else {
+            return;
+        };
+        let ranges = if date_ranges.is_empty() {
+            vec![(image_config.start_date, image_config.end_date)]
+        } else {
+            date_ranges.clone()
+        };
+        self.date_ranges_window = DateRangesWindow {
+            path: Some(path.to_path_buf()),
+            ranges: ranges
+                .iter()
+                .map(|(start_date, end_date)| (start_date.to_string(), end_date.to_string()))
+                .collect(),
+            error: None,
+        };
+    }
+
+    pub fn build_date_ranges_view(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.date_ranges_window.path.clone() else {
+            return;
+        };
+        let mut is_open = true;
+        let mut date_ranges = None;
+        egui::Window::new("Date ranges")
+            .collapsible(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let window = &mut self.date_ranges_window;
+                ui.label(path.display().to_string());
+                ui.label("Disjoint ranges are rendered into a single video.");
+                ui.add_space(10.0);
+                let mut removed = None;
+                for (index, (start_date, end_date)) in window.ranges.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(start_date).desired_width(90.0));
+                        ui.label("to");
+                        ui.add(egui::TextEdit::singleline(end_date).desired_width(90.0));
+                        if ui.small_button("✕").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    window.ranges.remove(index);
+                }
+                if ui.button("Add range").clicked() {
+                    window.ranges.push(Default::default());
+                }
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        match dates::parse_ranges(&window.ranges) {
+                            Ok(ranges) => date_ranges = Some(ranges),
+                            Err(e) => window.error = Some(e),
+                        }
+                    }
+                    if ui.button("Use config range").clicked() {
+                        date_ranges = Some(Vec::new());
+                    }
+                });
+                if let Some(error) = &window.error {
+                    ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+                }
+            });
+        if let Some(date_ranges) = date_ranges {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.date_ranges = date_ranges;
+            }
+            self.date_ranges_window = Default::default();
+        } else if !is_open {
+            self.date_ranges_window = Default::default();
+        }
+    }
+
+    pub fn open_overrides(&mut self, path: &Path) {
+        let Some(file) = self.dropped_files.get(path) else {
+            return;
+        };
+        self.overrides_window = OverridesWindow {
+            path: Some(path.to_path_buf()),
+            video_codec: file.overrides.video_codec,
+            frame_rate: file.overrides.frame_rate,
+            camera_clock: file.overrides.camera_clock.map(|clock| clock.name()),
+            error: None,
+        };
+    }
+
+    /// Edits the codec and frame rate of a single job, which take precedence over the global
+    /// settings.
+    pub fn build_overrides_view(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.overrides_window.path.clone() else {
+            return;
+        };
+        let mut is_open = true;
+        let mut overrides = None;
+        egui::Window::new("Job video settings")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let window = &mut self.overrides_window;
+                ui.label(path.display().to_string());
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let mut is_overridden = window.video_codec.is_some();
+                    if ui.checkbox(&mut is_overridden, "Codec").changed() {
+                        window.video_codec = is_overridden.then_some(self.video_codec);
+                    }
+                    match &mut window.video_codec {
+                        Some(video_codec) => {
+                            egui::ComboBox::from_id_source("override_codec")
+                                .selected_text(video_codec.label())
+                                .show_ui(ui, |ui| {
+                                    for codec in Codec::ENCODERS {
+                                        ui.selectable_value(video_codec, codec, codec.label());
+                                    }
+                                });
+                        }
+                        None => {
+                            ui.label(format!("{} (global)", self.video_codec.label()));
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mut is_overridden = window.frame_rate.is_some();
+                    if ui.checkbox(&mut is_overridden, "Frame rate").changed() {
+                        window.frame_rate = is_overridden.then_some(self.frame_rate);
+                    }
+                    match &mut window.frame_rate {
+                        Some(frame_rate) => {
+                            ui.add(egui::Slider::new(frame_rate, 1..=25));
+                        }
+                        None => {
+                            ui.label(format!("{} fps (global)", self.frame_rate));
+                        }
+                    }
+                });
+                if self.target_duration.is_some() {
+                    ui.label("The frame rate is computed from the video length setting.");
+                }
+                ui.horizontal(|ui| {
+                    let mut is_overridden = window.camera_clock.is_some();
+                    if ui
+                        .checkbox(&mut is_overridden, "Camera clock")
+                        .on_hover_text("Time zone of the camera's clock, used to order frames by capture time: local to follow the daylight saving time of this computer, or a fixed offset like +01:00")
+                        .changed()
+                    {
+                        window.camera_clock = is_overridden.then(|| CameraClock::Local.name());
+                    }
+                    match &mut window.camera_clock {
+                        Some(camera_clock) => {
+                            ui.add(egui::TextEdit::singleline(camera_clock).desired_width(80.0));
+                        }
+                        None => {
+                            ui.label("local (global)");
+                        }
+                    }
+                });
+                if let Some(e) = &window.error {
+                    ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                }
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        match window.camera_clock.as_deref().map(CameraClock::parse) {
+                            Some(None) => {
+                                window.error = Some(
+                                    "The camera clock must be local or an offset like +01:00"
+                                        .to_owned(),
+                                );
+                            }
+                            camera_clock => {
+                                overrides = Some(JobOverrides {
+                                    video_codec: window.video_codec,
+                                    frame_rate: window.frame_rate,
+                                    camera_clock: camera_clock.flatten(),
+                                });
+                            }
+                        }
+                    }
+                    if ui.button("Use global settings").clicked() {
+                        overrides = Some(JobOverrides::default());
+                    }
+                });
+            });
+        if let Some(overrides) = overrides {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.overrides = overrides;
+            }
+            self.overrides_window = Default::default();
+        } else if !is_open {
+            self.overrides_window = Default::default();
+        }
+    }
+
+    pub fn open_scrubber(&mut self, path: &Path) {
+        self.scrubber = ScrubberWindow {
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        };
+        self.reload_scrubber_frames();
+    }
+
+    /// Decodes the video of a job in the background and opens the player once it is done.
+    fn open_player(&mut self, path: &Path) {
+        self.close_player();
+        let Some(file) = self.dropped_files.get(path) else {
+            return;
+        };
+        let Some(ffmpeg_path) = file
+            .ffmpeg_path
+            .clone()
+            .or_else(|| self.ffmpeg_path.clone())
+        else {
+            return;
+        };
+        let Some(video) = file.video().map(Path::to_path_buf) else {
+            return;
+        };
+        let video_name = self.video_name(&file.overrides, file.video_name_collision);
+        let dir = self
+            .temp_path
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(format!(
+                "player-{}-{}",
+                std::process::id(),
+                PLAYER_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+        let cancel = CancelToken::default();
+        self.player = PlayerWindow {
+            video: Some(video.clone()),
+            dir: Some(dir.clone()),
+            cancel: Some(cancel.clone()),
+            frame_rate: video_name.frame_rate as f64,
+            target_duration: self.target_duration,
+            ..Default::default()
+        };
+        let environment = self.ffmpeg_environment.clone();
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            // Without ffprobe, the player shows the start of long videos.
+            let step = verify::video_frame_count(&verify::ffprobe_path(&ffmpeg_path), &video)
+                .map_or(1, |count| count.div_ceil(PLAYER_FRAMES).max(1));
+            let context = video::EncodeContext {
+                cancel,
+                environment,
+                ..Default::default()
+            };
+            let result = video::extract_frames(
+                &ffmpeg_path,
+                &video,
+                960,
+                (step, PLAYER_FRAMES),
+                &dir,
+                &context,
+            );
+            if context.cancel.is_cancelled() {
+                // The window closed, possibly before the folder was created.
+                let _ = std::fs::remove_dir_all(&dir);
+            }
+            let _ = sender.send(Signal::PlayerFramesExtracted((dir, step, result)));
+        });
+    }
+
+    fn close_player(&mut self) {
+        if let Some(cancel) = self.player.cancel.take() {
+            cancel.cancel();
+        }
+        if let Some(dir) = self.player.dir.take() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+        self.player = Default::default();
+    }
+
+    /// Plays the decoded frames of a video at its frame rate. Space plays and pauses, the
+    /// arrow keys step through the frames.
+    pub fn build_player_view(&mut self, ctx: &egui::Context) {
+        let Some(video) = self.player.video.clone() else {
+            return;
+        };
+        let frame_count = match &self.player.frames {
+            Some(Ok(frames)) => frames.len(),
+            _ => 0,
+        };
+        let player = &mut self.player;
+        if frame_count > 0 && !ctx.wants_keyboard_input() {
+            ctx.input(|input| {
+                if input.key_pressed(egui::Key::Space) {
+                    player.is_playing = !player.is_playing;
+                }
+                if input.key_pressed(egui::Key::ArrowRight) {
+                    player.is_playing = false;
+                    player.index = (player.index + 1).min(frame_count - 1);
+                }
+                if input.key_pressed(egui::Key::ArrowLeft) {
+                    player.is_playing = false;
+                    player.index = player.index.saturating_sub(1);
+                }
+            });
+        }
+        if player.is_playing && frame_count > 0 {
+            let frame_duration = Duration::from_secs_f64(1.0 / player.frame_rate.max(0.1));
+            let now = Instant::now();
+            let shown_at = *player.shown_at.get_or_insert(now);
+            // Frames that came up since the last repaint are skipped to keep the pace.
+            let due = (now.duration_since(shown_at).as_secs_f64() / frame_duration.as_secs_f64())
+                as usize;
+            if due > 0 {
+                player.index = (player.index + due).min(frame_count - 1);
+                player.shown_at = Some(shown_at + frame_duration * due as u32);
+            }
+            if player.index + 1 >= frame_count {
+                player.is_playing = false;
+            }
+            ctx.request_repaint_after(frame_duration);
+        }
+        if !player.is_playing {
+            player.shown_at = None;
+        }
+        if let Some(Ok(frames)) = &player.frames {
+            if player.texture.as_ref().map(|(index, _)| *index) != Some(player.index) {
+                match image::open(&frames[player.index]) {
+                    Ok(image) => {
+                        let image = image.to_rgba8();
+                        let image = egui::ColorImage::from_rgba_unmultiplied(
+                            [image.width() as usize, image.height() as usize],
+                            image.as_raw(),
+                        );
+                        match &mut player.texture {
+                            Some((index, texture)) => {
+                                texture.set(image, Default::default());
+                                *index = player.index;
+                            }
+                            None => {
+                                let texture =
+                                    ctx.load_texture("player_frame", image, Default::default());
+                                player.texture = Some((player.index, texture));
+                            }
+                        }
+                    }
+                    Err(e) => log::error!(
+                        "Error loading frame {} {}",
+                        frames[player.index].display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        let mut is_open = true;
+        egui::Window::new("Video preview")
+            .default_width(800.0)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let player = &mut self.player;
+                ui.label(video.display().to_string());
+                match &player.frames {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Decoding the video…");
+                        });
+                        return;
+                    }
+                    Some(Err(e)) => {
+                        ui.label(
+                            egui::RichText::new(format!("Cannot decode the video: {}", e))
+                                .color(egui::Color32::RED),
+                        );
+                        return;
+                    }
+                    Some(Ok(frames)) if frames.is_empty() => {
+                        ui.label("The video has no frames.");
+                        return;
+                    }
+                    Some(Ok(_)) => {}
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("⏮").clicked() {
+                        player.index = 0;
+                    }
+                    if ui.button("◀").on_hover_text("Left arrow").clicked() {
+                        player.is_playing = false;
+                        player.index = player.index.saturating_sub(1);
+                    }
+                    let play = if player.is_playing { "⏸" } else { "▶" };
+                    if ui.button(play).on_hover_text("Space").clicked() {
+                        if !player.is_playing && player.index + 1 >= frame_count {
+                            player.index = 0;
+                        }
+                        player.is_playing = !player.is_playing;
+                    }
+                    if ui.button("▶|").on_hover_text("Right arrow").clicked() {
+                        player.is_playing = false;
+                        player.index = (player.index + 1).min(frame_count - 1);
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut player.index, 0..=frame_count - 1).show_value(false),
+                    );
+                    ui.label(format!(
+                        "{} / {} at {:.1} fps",
+                        player.index + 1,
+                        frame_count,
+                        player.frame_rate
+                    ));
+                });
+                if let Some((_, texture)) = &player.texture {
+                    ui.add(egui::Image::new(texture).shrink_to_fit());
+                }
+            });
+        if !is_open {
+            self.close_player();
+        }
+    }
+
+    /// Lists the output frames of the scrubbed job again, keeping the current frame.
+    fn reload_scrubber_frames(&mut self) {
+        let Some(image_config) = self
+            .scrubber
+            .path
+            .as_ref()
+            .and_then(|path| self.dropped_files.get(path))
+            .and_then(|file| self.job_config(file))
+        else {
+            return;
+        };
+        let current = self.scrubber.frames.get(self.scrubber.index).cloned();
+        self.scrubber.frames = utils::image_files(&image_config.output_path).unwrap_or_default();
+        self.scrubber.index = current
+            .and_then(|current| {
+                self.scrubber
+                    .frames
+                    .iter()
+                    .position(|frame| frame == &current)
+            })
+            .unwrap_or(0);
+    }
+
+    /// Loads the first, middle and last source image of a job scaled down in the background.
+    fn load_source_preview(&mut self, path: &Path) {
+        let Some(file) = self.dropped_files.get_mut(path) else {
+            return;
+        };
+        let Ok(image_config) = &file.config else {
+            return;
+        };
+        file.source_preview = SourcePreview::Loading;
+        let image_config = image_config.clone();
+        let sender = self.channel.0.clone();
+        let path = path.to_path_buf();
+        self.tasks.spawn_blocking(move || {
+            let result = utils::source_images(&image_config)
+                .map_err(|e| e.to_string())
+                .and_then(|images| {
+                    let _ = sender.send(Signal::SourcesCounted((path.clone(), images.len())));
+                    let mut picked = [0, images.len() / 2, images.len().saturating_sub(1)]
+                        .into_iter()
+                        .filter_map(|i| images.get(i))
+                        .collect::<Vec<_>>();
+                    picked.dedup();
+                    if picked.is_empty() {
+                        return Err("No source images".to_owned());
+                    }
+                    picked
+                        .into_iter()
+                        .map(|image| {
+                            let thumbnail = largeframe::open_scaled(image, PREVIEW_PIXELS)
+                                .map_err(|e| format!("{}: {}", image.display(), e))?
+                                .to_rgba8();
+                            let name = image
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+                            Ok((
+                                name,
+                                egui::ColorImage::from_rgba_unmultiplied(
+                                    [thumbnail.width() as usize, thumbnail.height() as usize],
+                                    thumbnail.as_raw(),
+                                ),
+                            ))
+                        })
+                        .collect()
+                });
+            let _ = sender.send(Signal::SourcesPreviewed((path, result)));
+        });
+    }
+
+    /// Loads a frame scaled down for display in the background.
+    fn load_frame(&mut self, frame: PathBuf) {
+        self.scrubber.loading = Some(frame.clone());
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let result = largeframe::open_scaled(&frame, 1280)
+                .map(|image| {
+                    let image = image.to_rgba8();
+                    egui::ColorImage::from_rgba_unmultiplied(
+                        [image.width() as usize, image.height() as usize],
+                        image.as_raw(),
+                    )
+                })
+                .map_err(|e| e.to_string());
+            let _ = sender.send(Signal::FrameLoaded((frame, result)));
+        });
+    }
+
+    /// Shows the output frames of a job one at a time. The arrow keys step through them and F
+    /// flags the current frame, which is listed in the report.
+    pub fn build_scrubber_view(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.scrubber.path.clone() else {
+            return;
+        };
+        if let Some((frame, image)) = self.scrubber.loaded.take() {
+            let texture = ctx.load_texture("qc_frame", image, Default::default());
+            self.scrubber.texture = Some((frame, texture));
+        }
+        let frame_count = self.scrubber.frames.len();
+        if frame_count > 0 && !ctx.wants_keyboard_input() {
+            let index = &mut self.scrubber.index;
+            ctx.input(|input| {
+                if input.key_pressed(egui::Key::ArrowRight) {
+                    *index = (*index + 1).min(frame_count - 1);
+                }
+                if input.key_pressed(egui::Key::ArrowLeft) {
+                    *index = index.saturating_sub(1);
+                }
+                if input.key_pressed(egui::Key::Home) {
+                    *index = 0;
+                }
+                if input.key_pressed(egui::Key::End) {
+                    *index = frame_count - 1;
+                }
+            });
+        }
+        let frame = self.scrubber.frames.get(self.scrubber.index).cloned();
+        let mut is_flag_toggled = frame.is_some()
+            && !ctx.wants_keyboard_input()
+            && ctx.input(|i| i.key_pressed(egui::Key::F));
+        let mut is_reloaded = false;
+        let mut is_open = true;
+        egui::Window::new("Frame QC")
+            .default_width(800.0)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let file = self.dropped_files.get(&path);
+                let flagged = file.map(|file| &file.flagged_frames);
+                ui.label(path.display().to_string());
+                let Some(frame) = &frame else {
+                    ui.label("No frames written yet.");
+                    is_reloaded = ui.button("Reload frames").clicked();
+                    return;
+                };
+                let is_flagged = flagged.is_some_and(|flagged| flagged.contains(frame));
+                ui.horizontal(|ui| {
+                    let window = &mut self.scrubber;
+                    if ui.button("⏮").clicked() {
+                        window.index = 0;
+                    }
+                    if ui.button("◀").clicked() {
+                        window.index = window.index.saturating_sub(1);
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut window.index, 0..=frame_count - 1).show_value(false),
+                    );
+                    if ui.button("▶").clicked() {
+                        window.index = (window.index + 1).min(frame_count - 1);
+                    }
+                    if ui.button("⏭").clicked() {
+                        window.index = frame_count - 1;
+                    }
+                    let flag = if is_flagged {
+                        "Unflag frame"
+                    } else {
+                        "Flag frame"
+                    };
+                    if ui.button(flag).on_hover_text("F").clicked() {
+                        is_flag_toggled = true;
+                    }
+                    if ui
+                        .button("Reload frames")
+                        .on_hover_text("List the frames again while the job is running")
+                        .clicked()
+                    {
+                        is_reloaded = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "Frame {} of {}: {}",
+                        self.scrubber.index + 1,
+                        frame_count,
+                        frame
+                            .file_name()
+                            .map(|name| name.to_string_lossy())
+                            .unwrap_or_default()
+                    ));
+                    if let Some(date) = utils::frame_date(frame) {
+                        ui.label(date.to_string());
+                    }
+                    if is_flagged {
+                        ui.colored_label(egui::Color32::GOLD, "Flagged");
+                    }
+                    if file.is_some_and(|file| file.excluded_frames.contains(frame)) {
+                        ui.label("Excluded from the video");
+                    }
+                    if self.scrubber.texture.as_ref().map(|(shown, _)| shown) != Some(frame) {
+                        ui.spinner();
+                    }
+                    ui.label(format!(
+                        "{} flagged",
+                        flagged.map_or(0, |flagged| flagged.len())
+                    ));
+                });
+                match &self.scrubber.texture {
+                    Some((_, texture)) => {
+                        ui.add(egui::Image::new(texture).shrink_to_fit());
+                    }
+                    None => {
+                        ui.spinner();
+                    }
+                }
+            });
+        if is_flag_toggled {
+            if let (Some(file), Some(frame)) = (self.dropped_files.get_mut(&path), &frame) {
+                if !file.flagged_frames.remove(frame) {
+                    file.flagged_frames.insert(frame.clone());
+                }
+            }
+        }
+        if is_reloaded {
+            self.reload_scrubber_frames();
+        }
+        if !is_open {
+            self.scrubber = Default::default();
+            return;
+        }
+        let shown = self.scrubber.texture.as_ref().map(|(frame, _)| frame);
+        if let Some(frame) = self.scrubber.frames.get(self.scrubber.index).cloned() {
+            if shown != Some(&frame) && self.scrubber.loading.is_none() {
+                self.load_frame(frame);
+            }
+        }
+    }
+
+    /// Opens a config file in the editor, as raw text if it is not valid TOML.
+    pub fn open_config_editor(&mut self, path: &Path) {
+        let (contents, validation) = match editor::read(path) {
+            Ok(contents) => {
+                let validation = editor::validate(path, &contents);
+                (contents, Some(validation))
+            }
+            Err(e) => (editor::Contents::default(), Some(Err(e))),
+        };
+        self.config_editor = ConfigEditorWindow {
+            path: Some(path.to_path_buf()),
+            contents,
+            validation,
+            edited_at: None,
+        };
+    }
+
+    /// Shows the fields of a config file, or its text if it is not valid TOML, validating them
+    /// once editing pauses and saving them back into the file, after which the entry is
+    /// parsed again.
+    pub fn build_config_editor_view(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.config_editor.path.clone() else {
+            return;
+        };
+        let mut is_open = true;
+        let mut is_saved = false;
+        egui::Window::new("Edit config")
+            .collapsible(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let window = &mut self.config_editor;
+                ui.label(path.display().to_string());
+                ui.add_space(10.0);
+                let is_changed = match &mut window.contents {
+                    editor::Contents::Fields(values) => {
+                        let mut is_changed = false;
+                        egui::Grid::new("config_editor").show(ui, |ui| {
+                            for ((_, label), value) in editor::FIELDS.iter().zip(values) {
+                                ui.label(*label);
+                                is_changed |= ui.text_edit_singleline(value).changed();
+                                ui.end_row();
+                            }
+                        });
+                        is_changed
+                    }
+                    editor::Contents::Raw(text) => {
+                        ui.label("The file is not valid TOML, edit it as text");
+                        egui::ScrollArea::vertical()
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                ui.add(
+                                    egui::TextEdit::multiline(text)
+                                        .code_editor()
+                                        .desired_width(480.0),
+                                )
+                                .changed()
+                            })
+                            .inner
+                    }
+                };
+                if is_changed {
+                    window.validation = None;
+                    window.edited_at = Some(Instant::now());
+                }
+                if let Some(edited_at) = window.edited_at {
+                    if edited_at.elapsed() >= editor::VALIDATION_DELAY {
+                        window.validation = Some(editor::validate(&path, &window.contents));
+                        window.edited_at = None;
+                    } else {
+                        ctx.request_repaint_after(editor::VALIDATION_DELAY);
+                    }
+                }
+                ui.add_space(10.0);
+                match &window.validation {
+                    Some(Ok(())) => {
+                        ui.label(egui::RichText::new("Valid config").color(egui::Color32::GREEN));
+                    }
+                    Some(Err(e)) => {
+                        ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+                    }
+                    None => {
+                        ui.label("Checking…");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Check").clicked() {
+                        window.validation = Some(editor::validate(&path, &window.contents));
+                        window.edited_at = None;
+                    }
+                    let is_valid = matches!(window.validation, Some(Ok(())));
+                    if ui
+                        .add_enabled(is_valid, egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        match editor::save(&path, &window.contents) {
+                            Ok(()) => is_saved = true,
+                            Err(e) => window.validation = Some(Err(e)),
+                        }
+                    }
+                });
+            });
+        if is_saved {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.reload(&path);
+            }
+            self.config_editor = Default::default();
+        } else if !is_open {
+            self.config_editor = Default::default();
+        }
+    }
+
+    /// Opens the video of a finished job, or its image output folder if there is no video,
+    /// in the configured application.
+    pub fn open_with(&self, path: &Path) {
+        let (Some(application_path), Some(file)) =
+            (&self.open_with_path, self.dropped_files.get(path))
+        else {
+            return;
+        };
+        let Some(image_config) = self.job_config(file) else {
+            return;
+        };
+        let target = file
+            .video()
+            .map_or_else(|| image_config.output_path.clone(), Path::to_path_buf);
+        if let Err(e) = launch::open_with(application_path, &target) {
+            log::error!("Error opening {} {}", target.display(), e);
+        }
+    }
+
+    /// Lists the outputs a job may have left behind.
+    fn partial_outputs(
+        &self,
+        image_config: &tree_migration::Config,
+        file: &FileEntry,
+    ) -> Vec<PathBuf> {
+        let mut outputs = vec![image_config.output_path.clone()];
+        outputs.extend(self.video_outputs(image_config, file));
+        outputs
+    }
+
+    /// Lists the video, previews and proxy of a job. The video is the one its last run wrote,
+    /// or the one the current settings give if it wrote none.
+    fn video_outputs(
+        &self,
+        image_config: &tree_migration::Config,
+        file: &FileEntry,
+    ) -> Vec<PathBuf> {
+        let video_output_path = self.job_video_output_path(image_config);
+        let video_name = self.video_name(&file.overrides, file.video_name_collision);
+        let video = file
+            .video
+            .clone()
+            .unwrap_or_else(|| video_path(image_config, &video_output_path, &video_name));
+        let mut outputs = preview_paths(&video);
+        outputs.insert(0, video);
+        outputs.push(proxy_path(image_config, &video_output_path, &video_name));
+        outputs
+    }
+
+    /// Lists the files the job of `path` wrote that still exist. The image output folder
+    /// itself is never included, as other jobs may share it.
+    fn cleanup_files(&self, path: &Path) -> Vec<PathBuf> {
+        let Some(file) = self.dropped_files.get(path) else {
+            return Vec::new();
+        };
+        let (Some(image_config), Some((start_date, end_date))) =
+            (self.job_config(file), file.date_range())
+        else {
+            return Vec::new();
+        };
+        let image_config = tree_migration::Config {
+            start_date,
+            end_date,
+            ..image_config.clone()
+        };
+        let mut files = cleanup::job_files(&image_config);
+        files.extend(
+            self.video_outputs(&image_config, file)
+                .into_iter()
+                .filter(|output| output.is_file()),
+        );
+        files
+    }
+
+    /// Publishes the draft Zenodo deposition of the job of `path`.
+    fn publish_deposit(&mut self, path: PathBuf, draft: u64) {
+        let Some(file) = self.dropped_files.get_mut(&path) else {
+            return;
+        };
+        file.is_publishing = true;
+        file.shortfalls.deposit_error = None;
+        let settings = self.deposit_settings.clone();
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let result = deposit::publish(&settings, draft);
+            let _ = sender.send(Signal::Published((path, result)));
+        });
+    }
+
+    /// Offers to clean up the outputs of the job of `path`.
+    fn request_cleanup(&mut self, path: PathBuf) {
+        let files = self.cleanup_files(&path);
+        self.pending_cleanup = Some(PendingCleanup {
+            path,
+            files,
+            is_confirmed: false,
+        });
+    }
+
+    pub fn cleanup(&self, path: &PathBuf, outputs: Vec<PathBuf>, cleanup: Cleanup) {
+        let Some(image_config) = self
+            .dropped_files
+            .get(path)
+            .and_then(|file| self.job_config(file))
+        else {
+            return;
+        };
+        let quarantine_path = image_config
+            .output_path
+            .parent()
+            .unwrap_or(&image_config.output_path)
+            .join("quarantine")
+            .join(
+                output_name(&image_config)
+                    + chrono::Local::now()
+                        .format("-%Y%m%d-%H%M%S")
+                        .to_string()
+                        .as_str(),
+            );
+        let sender = self.channel.0.clone();
+        let path = path.clone();
+        self.tasks.spawn_blocking(move || {
+            let result = match cleanup {
+                Cleanup::Delete => cleanup::delete(&outputs),
+                Cleanup::Quarantine => cleanup::quarantine(&outputs, &quarantine_path),
+            };
+            let _ = sender.send(Signal::CleanedUp((path, result)));
+        });
+    }
+
+    pub fn import(&mut self) {
+        let (Some(card_path), Some(archive_path)) = (
+            self.import_window.card_path.clone(),
+            self.import_window.archive_path.clone(),
+        ) else {
+            return;
+        };
+        let location = self.import_window.location.trim().to_owned();
+        self.import_window.is_importing = true;
+        self.import_window.result = None;
+        let sender = self.channel.0.clone();
+        self.tasks.spawn_blocking(move || {
+            let result = import::import(&card_path, &archive_path, &location);
+            let _ = sender.send(Signal::Imported(result));
+        });
+    }
+
+    /// Offers to restore the jobs of the previous session, re-validating their configs.
+    pub fn build_previous_session_view(&mut self, ctx: &egui::Context) {
+        if self.previous_session.is_empty() {
+            return;
+        }
+        let count = |status: queue::RecordStatus| {
+            self.previous_session
+                .iter()
+                .filter(|record| record.status == status)
+                .count()
+        };
+        let summary = format!(
+            "{} queued, {} interrupted, {} done, {} failed",
+            count(queue::RecordStatus::Queued),
+            count(queue::RecordStatus::Processing),
+            count(queue::RecordStatus::Done),
+            count(queue::RecordStatus::Error)
+        );
+        let mut is_restored = None;
+        egui::Window::new("Previous session")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The previous session left {} config files in the table.",
+                    self.previous_session.len()
+                ));
+                ui.label(summary);
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Restore previous session").clicked() {
+                        is_restored = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        is_restored = Some(false);
+                    }
+                });
+            });
+        match is_restored {
+            Some(true) => {
+                for record in std::mem::take(&mut self.previous_session) {
+                    self.dropped_files
+                        .entry(record.path.clone())
+                        .or_insert_with(|| FileEntry::restore(&record));
+                }
+            }
+            Some(false) => self.previous_session.clear(),
+            None => {}
+        }
+    }
+
+    pub fn build_schedule_view(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.is_schedule_open;
+        let mut catch_up = None;
+        let overdue = self
+            .overdue_sites()
+            .into_iter()
+            .map(|(render, periods)| (site_name(&render.location, &render.camera), periods))
+            .collect::<HashMap<_, _>>();
+        egui::Window::new("Render schedule")
+            .collapsible(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                if self.renders.is_empty() {
+                    ui.label("No finished renders yet.");
+                    return;
+                }
+                egui::Grid::new("schedule").striped(true).show(ui, |ui| {
+                    ui.strong("Site");
+                    ui.strong("Rendered until");
+                    ui.strong("Cadence");
+                    ui.strong("Status");
+                    ui.end_row();
+                    for render in &self.renders {
+                        let site = site_name(&render.location, &render.camera);
+                        ui.label(&site);
+                        ui.label(render.end_date.to_string());
+                        let mut cadence = self.site_cadences.get(&site).copied();
+                        egui::ComboBox::from_id_source(&site)
+                            .selected_text(cadence.map_or("None", |cadence| cadence.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut cadence, None, "None");
+                                for value in [Cadence::Monthly, Cadence::Quarterly] {
+                                    ui.selectable_value(&mut cadence, Some(value), value.label());
+                                }
+                            });
+                        match cadence {
+                            Some(cadence) => self.site_cadences.insert(site.clone(), cadence),
+                            None => self.site_cadences.remove(&site),
+                        };
+                        match overdue.get(&site) {
+                            Some(periods) => {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} periods overdue",
+                                        periods.len()
+                                    ))
                                     .color(egui::Color32::RED),
-                            );
+                                );
+                                if ui.small_button("Queue catch-up jobs").clicked() {
+                                    catch_up = Some((render.clone(), periods.clone()));
+                                }
+                            }
+                            None if cadence.is_some() => {
+                                ui.label(
+                                    egui::RichText::new("Up to date").color(egui::Color32::GREEN),
+                                );
+                            }
+                            None => {}
                         }
-                    },
-                );
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                    if ui.button(egui::RichText::new("Clear").heading()).clicked() {
-                        self.dropped_files.clear();
+                        ui.end_row();
                     }
                 });
+                if !self.render_history.is_empty() {
+                    egui::CollapsingHeader::new("Durations and throughput").show(ui, |ui| {
+                        history_plots_ui(ui, &self.render_history);
+                    });
+                }
             });
-            ui.add_space(10.0);
-        });
+        if let Some((render, periods)) = catch_up {
+            self.queue_catch_up(&render, &periods);
+        }
+        self.is_schedule_open = is_open;
     }
 
-    pub fn poll(&mut self) {
-        while let Ok(signal) = self.channel.1.try_recv() {
-            match signal {
-                Signal::Success(path) => {
-                    if self.dropped_files.contains_key(&path) {
-                        self.dropped_files
-                            .entry(path)
-                            .and_modify(|value| value.1 = Some(Ok(())));
+    pub fn build_import_view(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.import_window.is_open;
+        let mut start = false;
+        egui::Window::new("Import SD card")
+            .collapsible(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let window = &mut self.import_window;
+                if window.is_importing {
+                    ui.spinner();
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Select card").clicked() {
+                        window.card_path = rfd::FileDialog::new().pick_folder();
+                    }
+                    if let Some(path) = &window.card_path {
+                        ui.monospace(path.display().to_string());
+                    } else {
+                        ui.label("Card path not set.".to_owned());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Select archive folder").clicked() {
+                        window.archive_path = rfd::FileDialog::new().pick_folder();
                     }
+                    if let Some(path) = &window.archive_path {
+                        ui.monospace(path.display().to_string());
+                    } else {
+                        ui.label("Archive path not set.".to_owned());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Location".to_owned());
+                    ui.text_edit_singleline(&mut window.location);
+                });
+                ui.add_space(10.0);
+                let is_ready = window.card_path.is_some()
+                    && window.archive_path.is_some()
+                    && !window.location.trim().is_empty();
+                if ui
+                    .add_enabled(is_ready, egui::Button::new("Import"))
+                    .clicked()
+                {
+                    start = true;
                 }
-                Signal::Error((path, error)) => {
-                    if self.dropped_files.contains_key(&path) {
-                        self.dropped_files
-                            .entry(path)
-                            .and_modify(|value| value.1 = Some(Err(error)));
+                match &window.result {
+                    Some(Ok(message)) => {
+                        ui.label(message);
                     }
+                    Some(Err(message)) => {
+                        ui.label(egui::RichText::new(message).color(egui::Color32::RED));
+                    }
+                    None => {}
                 }
-            }
+            });
+        self.import_window.is_open = is_open;
+        if start {
+            self.import();
+        }
+    }
+
+    /// Runs `action`, or asks for the confirmation phrase first while a locked batch is running.
+    pub fn request(&mut self, action: LockedAction) {
+        if self.is_session_lock_enabled
+            && !self.session_lock_phrase.is_empty()
+            && self.state.is_processing()
+        {
+            self.session_lock_input.clear();
+            self.locked_action = Some(action);
+        } else {
+            self.perform(action);
         }
     }
 
-    pub fn process(&self) {
-        let mut configs: Vec<(PathBuf, tree_migration::Config)> = Vec::new();
-        for (path, (config, _)) in &self.dropped_files {
-            if let Ok(image_config) = config {
-                configs.push((path.clone(), image_config.clone()));
+    fn perform(&mut self, action: LockedAction) {
+        match action {
+            LockedAction::Clear => {
+                self.dropped_files.clear();
+                self.pending_jobs.clear();
+            }
+            LockedAction::Cancel(path) => {
+                if let Some(cancel) = self
+                    .dropped_files
+                    .get(&path)
+                    .and_then(|file| file.cancel.as_ref())
+                {
+                    cancel.cancel();
+                }
+            }
+            LockedAction::CancelAll => {
+                for cancel in self
+                    .dropped_files
+                    .values()
+                    .filter_map(|file| file.cancel.as_ref())
+                {
+                    cancel.cancel();
+                }
             }
         }
+    }
 
-        for (path, image_config) in configs {
-            let sender = self.channel.0.clone();
-            let is_forest_green_enabled = self.is_forest_green_enabled;
-            let is_video_enabled = self.is_video_enabled;
-            let video_codec = self.video_codec.clone();
-            let ffmpeg_path = self.ffmpeg_path.clone();
-            let video_output_path = self.video_output_path.clone();
-            let frame_rate = self.frame_rate;
-            async_std::task::spawn(async move {
-                match tree_migration::run(image_config.clone(), is_forest_green_enabled).await {
-                    Ok(_) => {
-                        if is_video_enabled
-                            && video_codec != images_to_video::Codec::None
-                            && ffmpeg_path.is_some()
-                        {
-                            let video_config_opt = match build_video_config(
-                                &image_config,
-                                &ffmpeg_path.as_ref().unwrap(),
-                                video_codec.clone(),
-                                frame_rate,
-                                video_output_path,
-                            ) {
-                                Err(e) => {
-                                    println!("Error Config {}", e);
-                                    None
-                                }
-                                Ok(config) => Some(config),
-                            };
+    pub fn build_session_lock_view(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.locked_action.clone() else {
+            return;
+        };
+        let mut is_open = true;
+        let mut is_confirmed = false;
+        egui::Window::new("Batch is running")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Type \"{}\" to discard the running batch.",
+                    self.session_lock_phrase
+                ));
+                ui.text_edit_singleline(&mut self.session_lock_input);
+                is_confirmed = ui
+                    .add_enabled(
+                        self.session_lock_input.trim() == self.session_lock_phrase.trim(),
+                        egui::Button::new("Confirm"),
+                    )
+                    .clicked();
+            });
+        if is_confirmed {
+            self.perform(action);
+        }
+        if is_confirmed || !is_open {
+            self.locked_action = None;
+            self.session_lock_input.clear();
+        }
+    }
 
-                            if let Some(video_config) = video_config_opt {
-                                if let Err(e) = images_to_video::run(video_config).await {
-                                    println!("Eorrro {}", e);
-                                }
-                            }
-                        }
-                        let _ = sender.send(Signal::Success(path));
-                    }
-                    Err(e) => {
-                        let _ = sender.send(Signal::Error((path, e)));
-                    }
+    pub fn build_cleanup_view(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &mut self.pending_cleanup else {
+            return;
+        };
+        let mut cleanup = None;
+        let mut is_open = true;
+        egui::Window::new("Clean up partial output")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                if pending.files.is_empty() {
+                    ui.label("The failed job left no files behind.");
+                    return;
                 }
+                ui.label(format!(
+                    "The following {} files written by the failed job will be removed. \
+                     Other files in its output folders are kept.",
+                    pending.files.len()
+                ));
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for file in &pending.files {
+                            ui.monospace(file.display().to_string());
+                        }
+                    });
+                ui.add_space(10.0);
+                ui.checkbox(&mut pending.is_confirmed, "I checked the list of files");
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(pending.is_confirmed, |ui| {
+                        if ui.button("Delete").clicked() {
+                            cleanup = Some(Cleanup::Delete);
+                        }
+                        if ui.button("Move to quarantine").clicked() {
+                            cleanup = Some(Cleanup::Quarantine);
+                        }
+                    });
+                });
             });
+        if let Some(cleanup) = cleanup {
+            if let Some(pending) = self.pending_cleanup.take() {
+                self.cleanup(&pending.path, pending.files, cleanup);
+            }
+        } else if !is_open {
+            self.pending_cleanup = None;
+        }
+    }
+
+    /// Notifies the desktop that the batch finished, if enabled.
+    fn notify_batch_finished(&self) {
+        if !self.is_notification_enabled {
+            return;
+        }
+        let count = |is_counted: fn(&JobStatus) -> bool| {
+            self.dropped_files
+                .values()
+                .filter(|file| is_counted(&item_state(&self.state, file)))
+                .count()
+        };
+        let (done, failed) = (
+            count(JobStatus::is_done),
+            count(|state| state == &JobStatus::ProcessingError),
+        );
+        let is_sound_enabled = self.is_notification_sound_enabled;
+        self.tasks.spawn_blocking(move || {
+            if let Err(e) = notification::batch_finished(done, failed, is_sound_enabled) {
+                log::error!("Error showing notification {}", e);
+            }
+        });
+    }
+
+    /// Counts a failed job and, once `max_consecutive_failures` jobs failed in a row, cancels
+    /// the queued jobs and alerts through a desktop notification and the webhook. The jobs
+    /// already running are left to finish.
+    fn on_job_failed(&mut self, error: &str) {
+        self.consecutive_failures += 1;
+        let Some(max_consecutive_failures) = self.max_consecutive_failures else {
+            return;
+        };
+        if self.consecutive_failures != max_consecutive_failures {
+            return;
+        }
+        let mut cancelled = 0;
+        for file in self.dropped_files.values() {
+            if let (true, Some(cancel)) = (file.is_queued, &file.cancel) {
+                cancel.cancel();
+                cancelled += 1;
+            }
+        }
+        log::error!(
+            "Stopped the batch after {} failed jobs in a row, cancelling {} queued jobs: {}",
+            self.consecutive_failures,
+            cancelled,
+            error
+        );
+        self.drop_message = Some(format!(
+            "Stopped the batch after {} failed jobs in a row. The last error was: {}",
+            self.consecutive_failures, error
+        ));
+        let (failures, is_sound_enabled) = (
+            self.consecutive_failures,
+            self.is_notification_sound_enabled,
+        );
+        self.tasks.spawn_blocking(move || {
+            if let Err(e) = notification::batch_stopped(failures, cancelled, is_sound_enabled) {
+                log::error!("Error showing notification {}", e);
+            }
+        });
+        self.post_webhook(webhook::batch_stopped(
+            self.consecutive_failures,
+            cancelled,
+            error,
+        ));
+    }
+
+    /// Sends `payload` to the webhook in the background, if one is set.
+    fn post_webhook(&self, payload: serde_json::Value) {
+        if self.webhook_url.trim().is_empty() {
+            return;
         }
+        let url = self.webhook_url.clone();
+        self.tasks.spawn_blocking(move || {
+            if let Err(e) = webhook::post(&url, &payload) {
+                log::warn!("Error posting to the webhook {}", e);
+            }
+        });
+    }
+
+    /// Reports the jobs of the batch to the webhook once the last of them ended, whether the
+    /// batch succeeded or not.
+    fn post_batch_webhook(&self) {
+        let count = |is_counted: fn(&JobStatus) -> bool| {
+            self.dropped_files
+                .values()
+                .filter(|file| is_counted(&item_state(&self.state, file)))
+                .count()
+        };
+        let (done, failed, cancelled) = (
+            count(JobStatus::is_done),
+            count(|state| state == &JobStatus::ProcessingError),
+            count(|state| state == &JobStatus::Cancelled),
+        );
+        let entries = self
+            .dropped_files
+            .iter()
+            .filter(|(_, file)| {
+                let state = item_state(&self.state, file);
+                state != JobStatus::ValidConfig
+                    && state != JobStatus::InvalidConfig
+                    && state != JobStatus::Conflict
+            })
+            .filter_map(|(path, _)| self.history_entry(path))
+            .collect::<Vec<_>>();
+        self.post_webhook(webhook::batch_finished(&entries, done, failed, cancelled));
     }
 
     fn update_state(&mut self) {
-        if self.dropped_files.is_empty() {
-            self.state = AppState::Init;
-        } else {
-            if self.state == AppState::Processing {
-                if self
-                    .dropped_files
-                    .iter()
-                    .find(|(_, (config, done))| {
-                        item_state(&self.state, &config, &done) == ItemState::Processing
-                    })
-                    .is_none()
-                {
-                    self.state = AppState::ProcessingDone;
-                } else if self
+        if !self.state.is_processing() {
+            self.detect_conflicts();
+        }
+        let statuses = self
+            .dropped_files
+            .values()
+            .map(|file| item_state(&self.state, file))
+            .collect::<Vec<_>>();
+        if self.state == QueueState::Processing {
+            self.batch_throughput.record(self.batch_progress());
+        }
+        let (state, event) = pipeline::next_state(&self.state, &statuses);
+        if self.state == QueueState::Paused && event == Some(QueueEvent::Finished) {
+            children::resume();
+        }
+        self.state = state;
+        if event == Some(QueueEvent::Finished) {
+            self.notify_batch_finished();
+            self.post_batch_webhook();
+        }
+    }
+
+    /// Shows the filter box and the status chips narrowing the table.
+    fn table_filter_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.table_filter)
+                    .hint_text("Filter by path or site")
+                    .desired_width(200.0),
+            );
+            for filter in StatusFilter::ALL {
+                let count = self
                     .dropped_files
-                    .iter()
-                    .find(|(_, (config, done))| {
-                        item_state(&self.state, &config, &done) == ItemState::ProcessingError
-                    })
-                    .is_some()
+                    .values()
+                    .filter(|file| filter.matches(&item_state(&self.state, file)))
+                    .count();
+                let is_selected = self.status_filters.contains(&filter);
+                if ui
+                    .selectable_label(is_selected, format!("{} ({})", filter.label(), count))
+                    .clicked()
                 {
-                    self.state = AppState::ProcessingErrors;
+                    if is_selected {
+                        self.status_filters.remove(&filter);
+                    } else {
+                        self.status_filters.insert(filter);
+                    }
                 }
-            } else {
-                if self
-                    .dropped_files
-                    .iter()
-                    .find(|(_, (config, done))| {
-                        item_state(&self.state, &config, &done) == ItemState::InvalidConfig
-                    })
-                    .is_none()
-                {
-                    self.state = AppState::ValidConfigs;
-                } else {
-                    self.state = AppState::InvalidConfigs;
+            }
+            let shown = self.shown_paths().len();
+            if shown < self.dropped_files.len() {
+                ui.label(format!("Showing {} of {}", shown, self.dropped_files.len()));
+                if ui.small_button("Clear").clicked() {
+                    self.table_filter.clear();
+                    self.status_filters.clear();
                 }
             }
+        });
+    }
+
+    /// Returns the paths of the jobs passing the filters, in the order of the table.
+    fn shown_paths(&self) -> Vec<PathBuf> {
+        let filter = self.table_filter.trim().to_lowercase();
+        let mut paths = self
+            .dropped_files
+            .iter()
+            .filter(|(path, file)| {
+                filter.is_empty()
+                    || path.to_string_lossy().to_lowercase().contains(&filter)
+                    || file.config.as_ref().is_ok_and(|config| {
+                        site_name(&config.location, &config.camera)
+                            .to_lowercase()
+                            .contains(&filter)
+                    })
+            })
+            .filter(|(_, file)| {
+                let item_state = item_state(&self.state, file);
+                self.status_filters.is_empty()
+                    || self
+                        .status_filters
+                        .iter()
+                        .any(|filter| filter.matches(&item_state))
+            })
+            .map(|(path, file)| (path, item_state(&self.state, file).label()))
+            .collect::<Vec<_>>();
+        if self.sort_column == SortColumn::Status {
+            paths.sort_by_key(|(_, status)| *status);
         }
+        if self.is_sort_descending {
+            paths.reverse();
+        }
+        paths.into_iter().map(|(path, _)| path.clone()).collect()
     }
 
-    fn table_ui(&self, ui: &mut egui::Ui) {
+    fn table_ui(&mut self, ui: &mut egui::Ui) {
         use egui::*;
         use egui_extras::{Column, TableBuilder};
 
+        let ctx = ui.ctx().clone();
         let table = TableBuilder::new(ui)
             .striped(true)
             .resizable(true)
@@ -418,79 +6228,846 @@ impl MigrationApp {
             .column(Column::remainder())
             .min_scrolled_height(0.0);
 
+        let mut pending_cleanup = None;
+        let mut pending_publish = None;
+        let mut accepted_dates = None;
+        let mut edited_date_ranges = None;
+        let mut edited_overrides = None;
+        let mut conflict_ignored = None;
+        let mut output_override = None;
+        let mut ffmpeg_override = None;
+        let mut rerun = None;
+        let mut reviewed = None;
+        let mut scrubbed = None;
+        let mut excluded = None;
+        let mut played = None;
+        let mut cancelled = None;
+        let mut removed = None;
+        let mut edited_config = None;
+        let mut toggled_log = None;
+        let mut toggled_preview = None;
+        let mut sorted_column = None;
+        let mut unpreviewed = Vec::new();
+        for (path, file) in self.dropped_files.iter_mut() {
+            match std::mem::take(&mut file.source_preview) {
+                SourcePreview::Loaded(images) => {
+                    let textures = images
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (name, image))| {
+                            let id = format!("source_{}_{}", path.display(), i);
+                            (name, ctx.load_texture(id, image, Default::default()))
+                        })
+                        .collect();
+                    file.source_preview = SourcePreview::Shown(textures);
+                }
+                SourcePreview::None if file.config.is_ok() => unpreviewed.push(path.clone()),
+                preview => file.source_preview = preview,
+            }
+        }
+        for path in unpreviewed {
+            self.load_source_preview(&path);
+        }
+        let duplicates = self.duplicate_configs();
+        let queue_estimates = self.queue_estimates();
+        let paths = self.shown_paths();
         table
             .header(20.0, |mut header| {
-                header.col(|ui| {
-                    ui.strong("Status");
-                });
-                header.col(|ui| {
-                    ui.strong("Path");
-                });
+                for (column, name) in [(SortColumn::Status, "Status"), (SortColumn::Path, "Path")] {
+                    header.col(|ui| {
+                        let text = if self.sort_column != column {
+                            name.to_owned()
+                        } else if self.is_sort_descending {
+                            format!("{} ⏷", name)
+                        } else {
+                            format!("{} ⏶", name)
+                        };
+                        if ui
+                            .add(egui::Button::new(RichText::new(text).strong()).frame(false))
+                            .on_hover_text("Sort by this column")
+                            .clicked()
+                        {
+                            sorted_column = Some(column);
+                        }
+                    });
+                }
             })
             .body(|mut body| {
-                for (path, (config, done)) in &self.dropped_files {
-                    let row_height = 18.0;
-                    let item_state = item_state(&self.state, &config, &done);
+                for path in &paths {
+                    let file = &self.dropped_files[path];
+                    let item_state = item_state(&self.state, file);
+                    let verification = file.verification.as_ref();
+                    let speed = self
+                        .is_video_enabled
+                        .then(|| file.speed(self.frame_rate, self.target_duration))
+                        .flatten();
+                    let is_editable = item_state == JobStatus::ValidConfig
+                        || item_state == JobStatus::Conflict;
+                    let is_config_state = is_editable || item_state == JobStatus::InvalidConfig;
+                    let status_lines = match item_state {
+                        JobStatus::Processing | JobStatus::Paused | JobStatus::Unkown => 1,
+                        JobStatus::ValidConfig | JobStatus::Conflict => 3,
+                        _ => 2,
+                    };
+                    let detail_lines =
+                        1 + match item_state {
+                            JobStatus::InvalidConfig => 1,
+                            JobStatus::ProcessingError => 1 + file.cleanup_error.iter().count(),
+                            _ if item_state.is_done() => {
+                                file.shortfalls.video_error.iter().count()
+                                    + file.shortfalls.deposit_error.iter().count()
+                                    + file.shortfalls.warnings.len()
+                            }
+                            _ => 0,
+                        } + verification.map_or(0, |v| v.len().max(1))
+                            + file.validation.as_ref().map_or(0, |validation| {
+                                validation.notes.len() + validation.problems.len().max(1)
+                            })
+                            + file.detected_dates.iter().count()
+                            + (!file.date_ranges.is_empty()) as usize
+                            + file.default_output_path.iter().count()
+                            + file.ffmpeg_path.iter().count()
+                            + (!file.overrides.is_empty()) as usize
+                            + file.video_skipped.iter().count()
+                            + file.previous_error.iter().count()
+                            + duplicates.contains_key(path) as usize
+                            + file.conflicts.len()
+                            + file.deposit_id.iter().count()
+                            + (!file.flagged_frames.is_empty()) as usize
+                            + (!file.excluded_frames.is_empty()) as usize
+                            + (!file.stage_timings.is_empty()) as usize
+                            + file.adaptive_encode.iter().count()
+                            + speed.iter().count()
+                            + match (&file.source_preview, self.expanded_previews.contains(path)) {
+                                (SourcePreview::None, _) => 0,
+                                (SourcePreview::Shown(_), true) => 1 + PREVIEW_LINES,
+                                _ => 1,
+                            }
+                            + if file.log.is_empty() {
+                                0
+                            } else if self.expanded_logs.contains(path) {
+                                1 + LOG_LINES
+                            } else {
+                                1
+                            };
+                    let row_height = 18.0 * status_lines.max(detail_lines) as f32;
                     let status = match item_state {
-                        ItemState::ProcessingDone => String::from("Done"),
-                        ItemState::ProcessingError => String::from("Error"),
-                        ItemState::ValidConfig => String::from("Valid Config"),
-                        ItemState::InvalidConfig => String::from("Invalid Config"),
+                        JobStatus::ProcessingDone => String::from("Done"),
+                        JobStatus::VideoFailed
+                        | JobStatus::UploadPending
+                        | JobStatus::DoneWithWarnings
+                        | JobStatus::AwaitingReview
+                        | JobStatus::Rejected => String::from(item_state.label()),
+                        JobStatus::ProcessingError => String::from("Error"),
+                        JobStatus::Cancelled => String::from("Cancelled"),
+                        JobStatus::Queued => match queue_estimates.get(path) {
+                            Some((position, Some(start))) => format!(
+                                "Queued, #{}\nstarts in about {}",
+                                position,
+                                throughput::describe(*start)
+                            ),
+                            Some((position, None)) => format!("Queued, #{}", position),
+                            None => String::from("Queued"),
+                        },
+                        JobStatus::Paused => String::from("Paused"),
+                        JobStatus::ValidConfig => String::from("Valid Config"),
+                        JobStatus::Conflict => String::from("Conflict"),
+                        JobStatus::InvalidConfig => String::from("Invalid Config"),
                         _ => String::from("Unkown"),
                     };
                     body.row(row_height, |mut row| {
                         row.col(|ui| {
                             ui.style_mut().wrap = Some(false);
                             ui.vertical(|ui| {
-                                if item_state == ItemState::Processing {
-                                    ui.spinner();
+                                if item_state == JobStatus::Processing
+                                    || item_state == JobStatus::Paused
+                                {
+                                    ui.horizontal(|ui| {
+                                        if item_state == JobStatus::Paused {
+                                            ui.label(status.clone());
+                                        }
+                                        match file.progress {
+                                            Some(progress) => {
+                                                ui.add(
+                                                    egui::ProgressBar::new(progress)
+                                                        .show_percentage()
+                                                        .desired_width(80.0),
+                                                );
+                                                if let (Some(rate), Some(remaining)) = (
+                                                    Self::images_per_second(file),
+                                                    file.throughput.remaining(),
+                                                ) {
+                                                    ui.label(format!(
+                                                        "{:.1} images/s, {} left",
+                                                        rate,
+                                                        throughput::describe(remaining)
+                                                    ));
+                                                }
+                                            }
+                                            None => {
+                                                ui.spinner();
+                                            }
+                                        }
+                                        if ui
+                                            .small_button("QC")
+                                            .on_hover_text("Step through the frames written so far")
+                                            .clicked()
+                                        {
+                                            scrubbed = Some(path.clone());
+                                        }
+                                        if file.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                                            ui.label("Stopping…");
+                                        } else if ui
+                                            .small_button("Stop")
+                                            .on_hover_text(
+                                                "Stop this job and its ffmpeg processes, the rest of the queue continues",
+                                            )
+                                            .clicked()
+                                        {
+                                            cancelled = Some(path.clone());
+                                        }
+                                    });
+                                } else if item_state == JobStatus::Queued {
+                                    ui.horizontal(|ui| {
+                                        ui.label(status.clone());
+                                        if ui.small_button("Cancel").clicked() {
+                                            cancelled = Some(path.clone());
+                                        }
+                                    });
                                 } else {
-                                    ui.label(status.clone());
+                                    ui.label(match item_state {
+                                        JobStatus::VideoFailed => {
+                                            RichText::new(status.clone()).color(Color32::LIGHT_RED)
+                                        }
+                                        JobStatus::UploadPending => {
+                                            RichText::new(status.clone()).color(Color32::LIGHT_BLUE)
+                                        }
+                                        JobStatus::DoneWithWarnings => {
+                                            RichText::new(status.clone()).color(Color32::GOLD)
+                                        }
+                                        _ => RichText::new(status.clone()),
+                                    });
+                                }
+                                if (item_state == JobStatus::ProcessingError
+                                    || item_state == JobStatus::Cancelled)
+                                    && ui.small_button("Clean up").clicked()
+                                {
+                                    pending_cleanup = Some(path.clone());
+                                }
+                                if item_state.is_done() {
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("Verify").clicked() {
+                                            self.verify(path);
+                                        }
+                                        if ui.small_button("Re-run next period").clicked() {
+                                            rerun = Some(path.clone());
+                                        }
+                                        if ui
+                                            .small_button("QC")
+                                            .on_hover_text(
+                                                "Step through the frames and flag the ones that fail quality control",
+                                            )
+                                            .clicked()
+                                        {
+                                            scrubbed = Some(path.clone());
+                                        }
+                                        if let Some(image_config) = &self.job_config(file) {
+                                            if ui.small_button("Open output").clicked() {
+                                                show_in_file_manager(&image_config.output_path);
+                                            }
+                                            if let Some(video) = file.video() {
+                                                if ui.small_button("Show video").clicked() {
+                                                    show_in_file_manager(video);
+                                                }
+                                            }
+                                            if file.video().is_some()
+                                                && file
+                                                    .ffmpeg_path
+                                                    .as_ref()
+                                                    .or(self.ffmpeg_path.as_ref())
+                                                    .is_some()
+                                                && ui
+                                                    .small_button("Play")
+                                                    .on_hover_text("Preview the video in the app")
+                                                    .clicked()
+                                            {
+                                                played = Some(path.clone());
+                                            }
+                                        }
+                                        if self.open_with_path.is_some()
+                                            && ui.small_button("Open with…").clicked()
+                                        {
+                                            self.open_with(path);
+                                        }
+                                    });
+                                }
+                                if item_state == JobStatus::AwaitingReview {
+                                    ui.horizontal(|ui| {
+                                        if let Some(proxy) = &file.proxy {
+                                            if ui.small_button("Show proxy").clicked() {
+                                                show_in_file_manager(proxy);
+                                            }
+                                        }
+                                        if ui
+                                            .small_button("Approve")
+                                            .on_hover_text(
+                                                "Render the full video and run the remaining stages",
+                                            )
+                                            .clicked()
+                                        {
+                                            reviewed = Some((path.clone(), true));
+                                        }
+                                        if ui.small_button("Reject").clicked() {
+                                            reviewed = Some((path.clone(), false));
+                                        }
+                                    });
+                                }
+                                if is_config_state {
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("Detect dates").clicked() {
+                                            self.detect_dates(path);
+                                        }
+                                        if ui.small_button("Edit").clicked() {
+                                            edited_config = Some(path.clone());
+                                        }
+                                    });
                                 }
-                                if item_state == ItemState::ProcessingError {
-                                    ui.label("");
+                                if is_editable {
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("Date ranges").clicked() {
+                                            edited_date_ranges = Some(path.clone());
+                                        }
+                                        if ui
+                                            .small_button("⚙")
+                                            .on_hover_text(
+                                                "Use another codec or frame rate for this job",
+                                            )
+                                            .clicked()
+                                        {
+                                            edited_overrides = Some(path.clone());
+                                        }
+                                        if ui.small_button("Output folder…").clicked() {
+                                            if let Some(folder) =
+                                                rfd::FileDialog::new().pick_folder()
+                                            {
+                                                output_override =
+                                                    Some((path.clone(), Some(folder)));
+                                            }
+                                        }
+                                        if ui
+                                            .small_button("ffmpeg…")
+                                            .on_hover_text("Use another ffmpeg binary for this job")
+                                            .clicked()
+                                        {
+                                            if let Some(binary) = rfd::FileDialog::new().pick_file()
+                                            {
+                                                ffmpeg_override =
+                                                    Some((path.clone(), Some(binary)));
+                                            }
+                                        }
+                                    });
                                 }
                             });
                         });
                         row.col(|ui| {
                             ui.style_mut().wrap = Some(false);
                             ui.vertical(|ui| {
-                                ui.label(path.to_string_lossy());
-                                if item_state == ItemState::InvalidConfig {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add_enabled(
+                                            item_state != JobStatus::Processing
+                                                && item_state != JobStatus::Queued
+                                                && item_state != JobStatus::Paused,
+                                            egui::Button::new("✕").small(),
+                                        )
+                                        .on_hover_text("Remove from the table")
+                                        .clicked()
+                                    {
+                                        removed = Some(path.clone());
+                                    }
+                                    ui.label(path.to_string_lossy());
+                                });
+                                if item_state == JobStatus::InvalidConfig {
                                     ui.label(
                                         RichText::new(format!("{}", status)).color(Color32::RED),
                                     );
                                 }
-                                if item_state == ItemState::ProcessingError {
-                                    if let Err(message) = done.as_ref().unwrap() {
+                                if item_state == JobStatus::ProcessingError {
+                                    if let Some(crash) = &file.crash {
+                                        ui.label(
+                                            RichText::new(format!("Crashed: {}", crash))
+                                                .color(Color32::RED),
+                                        );
+                                    }
+                                    if let Some(error) = &file.stage_error {
+                                        ui.label(RichText::new(error).color(Color32::RED));
+                                    }
+                                    if let Some(Err(message)) = &file.done {
                                         ui.label(
                                             RichText::new(format!("{}", message))
                                                 .color(Color32::RED),
                                         );
                                     }
+                                    if let Some(error) = &file.cleanup_error {
+                                        ui.label(
+                                            RichText::new(format!("Clean up failed: {}", error))
+                                                .color(Color32::RED),
+                                        );
+                                    }
+                                }
+                                if !file.flagged_frames.is_empty() {
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "{} frames flagged in QC",
+                                                file.flagged_frames.len()
+                                            ))
+                                            .color(Color32::GOLD),
+                                        );
+                                        if item_state.is_done()
+                                            && file.flagged_frames != file.excluded_frames
+                                            && ui
+                                                .small_button("Exclude and re-render")
+                                                .on_hover_text(
+                                                    "Render the video again without the flagged frames",
+                                                )
+                                                .clicked()
+                                        {
+                                            excluded = Some(path.clone());
+                                        }
+                                    });
+                                }
+                                if !file.excluded_frames.is_empty() {
+                                    ui.label(format!(
+                                        "{} frames excluded from the video",
+                                        file.excluded_frames.len()
+                                    ))
+                                    .on_hover_text(
+                                        file.excluded_frames
+                                            .iter()
+                                            .filter_map(|frame| frame.file_name())
+                                            .map(|name| name.to_string_lossy())
+                                            .collect::<Vec<_>>()
+                                            .join("\n"),
+                                    );
+                                }
+                                if item_state.is_done() {
+                                    if let Some(error) = &file.shortfalls.video_error {
+                                        ui.label(
+                                            RichText::new(format!("Video failed: {}", error))
+                                                .color(Color32::LIGHT_RED),
+                                        );
+                                    }
+                                    if let Some(error) = &file.shortfalls.deposit_error {
+                                        ui.label(
+                                            RichText::new(format!("Deposit failed: {}", error))
+                                                .color(Color32::LIGHT_BLUE),
+                                        );
+                                    }
+                                    for warning in &file.shortfalls.warnings {
+                                        ui.label(RichText::new(warning).color(Color32::GOLD));
+                                    }
+                                }
+                                if let Some(error) = &file.previous_error {
+                                    ui.label(
+                                        RichText::new(format!("Previous run: {}", error))
+                                            .color(Color32::RED),
+                                    );
+                                }
+                                if let Some(deposit_id) = &file.deposit_id {
+                                    if let Some(draft) = file.deposit_draft {
+                                        ui.horizontal(|ui| {
+                                            ui.label(format!("Draft deposit reserving {}", deposit_id));
+                                            if ui
+                                                .add_enabled(
+                                                    !file.is_publishing,
+                                                    egui::Button::new("Publish").small(),
+                                                )
+                                                .on_hover_text("Publish the deposition on Zenodo. Published depositions cannot be deleted.")
+                                                .clicked()
+                                            {
+                                                pending_publish = Some((path.clone(), draft));
+                                            }
+                                        });
+                                    } else {
+                                        ui.label(format!("Deposited as {}", deposit_id));
+                                    }
+                                }
+                                if !file.stage_timings.is_empty() {
+                                    ui.label(format!(
+                                        "Stages: {}",
+                                        runner::describe_timings(&file.stage_timings)
+                                    ));
+                                }
+                                if let Some(encode) = &file.adaptive_encode {
+                                    ui.label(format!("Encode: {}", encode.describe()));
+                                }
+                                if let Some(speed) = &speed {
+                                    ui.label(format!("Speed: {}", speed));
+                                }
+                                match &file.source_preview {
+                                    SourcePreview::None => {}
+                                    SourcePreview::Loading | SourcePreview::Loaded(_) => {
+                                        ui.horizontal(|ui| {
+                                            ui.spinner();
+                                            ui.label("Loading source images…");
+                                        });
+                                    }
+                                    SourcePreview::Shown(textures) => {
+                                        let is_expanded = self.expanded_previews.contains(path);
+                                        if ui
+                                            .small_button(if is_expanded {
+                                                "Hide sources"
+                                            } else {
+                                                "Show sources"
+                                            })
+                                            .on_hover_text(
+                                                "First, middle and last source image of the date range",
+                                            )
+                                            .clicked()
+                                        {
+                                            toggled_preview = Some(path.clone());
+                                        }
+                                        if is_expanded {
+                                            ui.horizontal(|ui| {
+                                                for (name, texture) in textures {
+                                                    ui.add(
+                                                        egui::Image::new(texture)
+                                                            .max_height(18.0 * PREVIEW_LINES as f32),
+                                                    )
+                                                    .on_hover_text(name);
+                                                }
+                                            });
+                                        }
+                                    }
+                                    SourcePreview::Failed(e) => {
+                                        ui.label(
+                                            RichText::new(format!("No source preview: {}", e))
+                                                .color(Color32::GRAY),
+                                        );
+                                    }
+                                }
+                                if !file.log.is_empty() {
+                                    let is_expanded = self.expanded_logs.contains(path);
+                                    if ui
+                                        .small_button(if is_expanded {
+                                            "Hide log"
+                                        } else {
+                                            "Show log"
+                                        })
+                                        .clicked()
+                                    {
+                                        toggled_log = Some(path.clone());
+                                    }
+                                    if is_expanded {
+                                        ScrollArea::vertical()
+                                            .id_source(path)
+                                            .max_height(18.0 * LOG_LINES as f32)
+                                            .stick_to_bottom(true)
+                                            .show(ui, |ui| {
+                                                for line in file.log.lines() {
+                                                    ui.monospace(line);
+                                                }
+                                            });
+                                    }
+                                }
+                                if let Some(others) = duplicates.get(path) {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "Same config as {}",
+                                            others
+                                                .iter()
+                                                .map(|other| other.display().to_string())
+                                                .collect::<Vec<_>>()
+                                                .join(", ")
+                                        ))
+                                        .color(Color32::YELLOW),
+                                    );
+                                }
+                                for conflict in &file.conflicts {
+                                    ui.horizontal(|ui| {
+                                        let text = format!(
+                                            "{} {}",
+                                            conflict.reason,
+                                            conflict.path.display()
+                                        );
+                                        if conflict.is_ignored {
+                                            ui.label(
+                                                RichText::new(text + " (ignored)")
+                                                    .color(Color32::GRAY),
+                                            );
+                                        } else {
+                                            ui.label(RichText::new(text).color(Color32::RED));
+                                        }
+                                        if is_editable && !file.is_conflict_ignored {
+                                            if ui
+                                                .small_button("Ignore")
+                                                .on_hover_text(
+                                                    "Process this job although it may overwrite the outputs of the other",
+                                                )
+                                                .clicked()
+                                            {
+                                                conflict_ignored = Some((path.clone(), true));
+                                            }
+                                        } else if is_editable && ui.small_button("Undo").clicked() {
+                                            conflict_ignored = Some((path.clone(), false));
+                                        }
+                                    });
+                                }
+                                if let Some(validation) = &file.validation {
+                                    if validation.problems.is_empty() {
+                                        ui.label(
+                                            RichText::new("Ready to process").color(Color32::GREEN),
+                                        );
+                                    }
+                                    for problem in &validation.problems {
+                                        ui.label(RichText::new(problem).color(Color32::RED));
+                                    }
+                                    for note in &validation.notes {
+                                        ui.label(note);
+                                    }
+                                }
+                                if let Some(discrepancies) = verification {
+                                    if discrepancies.is_empty() {
+                                        ui.label(RichText::new("Verified").color(Color32::GREEN));
+                                    }
+                                    for discrepancy in discrepancies {
+                                        ui.label(RichText::new(discrepancy).color(Color32::RED));
+                                    }
+                                }
+                                if let (Ok(image_config), Some(_)) =
+                                    (&file.config, &file.default_output_path)
+                                {
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!(
+                                            "Output: {}",
+                                            image_config.output_path.display()
+                                        ));
+                                        if is_editable
+                                            && ui.small_button("Reset").clicked()
+                                        {
+                                            output_override = Some((path.clone(), None));
+                                        }
+                                    });
+                                }
+                                if let Some(reason) = file.video_skipped {
+                                    ui.label(
+                                        RichText::new(format!("Video skipped: {}", reason))
+                                            .color(Color32::YELLOW),
+                                    );
+                                }
+                                if let Some(ffmpeg_path) = &file.ffmpeg_path {
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!("ffmpeg: {}", ffmpeg_path.display()));
+                                        if is_editable
+                                            && ui.small_button("Reset").clicked()
+                                        {
+                                            ffmpeg_override = Some((path.clone(), None));
+                                        }
+                                    });
+                                }
+                                if !file.overrides.is_empty() {
+                                    ui.label(format!(
+                                        "Overrides: {}",
+                                        file.overrides.describe()
+                                    ));
+                                }
+                                if !file.date_ranges.is_empty() {
+                                    ui.label(format!(
+                                        "Date ranges: {}",
+                                        file.date_ranges
+                                            .iter()
+                                            .map(|(start_date, end_date)| format!(
+                                                "{} to {}",
+                                                start_date, end_date
+                                            ))
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                    ));
+                                }
+                                match &file.detected_dates {
+                                    Some(Ok((start_date, end_date))) => {
+                                        ui.horizontal(|ui| {
+                                            ui.label(format!(
+                                                "Images span {} to {}",
+                                                start_date, end_date
+                                            ));
+                                            if ui.small_button("Use these dates").clicked() {
+                                                accepted_dates = Some(path.clone());
+                                            }
+                                        });
+                                    }
+                                    Some(Err(message)) => {
+                                        ui.label(RichText::new(message).color(Color32::RED));
+                                    }
+                                    None => {}
                                 }
                             });
                         });
                     });
                 }
             });
+        if let Some(path) = pending_cleanup {
+            self.request_cleanup(path);
+        }
+        if let Some((path, draft)) = pending_publish {
+            self.publish_deposit(path, draft);
+        }
+        if let Some(path) = removed {
+            self.dropped_files.remove(&path);
+        }
+        if let Some(path) = edited_config {
+            self.open_config_editor(&path);
+        }
+        if let Some(path) = toggled_log {
+            if !self.expanded_logs.remove(&path) {
+                self.expanded_logs.insert(path);
+            }
+        }
+        if let Some(path) = toggled_preview {
+            if !self.expanded_previews.remove(&path) {
+                self.expanded_previews.insert(path);
+            }
+        }
+        if let Some(path) = accepted_dates {
+            self.apply_dates(&path);
+        }
+        if let Some(path) = edited_date_ranges {
+            self.open_date_ranges(&path);
+        }
+        if let Some(path) = edited_overrides {
+            self.open_overrides(&path);
+        }
+        if let Some((path, is_ignored)) = conflict_ignored {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.is_conflict_ignored = is_ignored;
+            }
+        }
+        if let Some((path, output_path)) = output_override {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                file.override_output_path(output_path);
+            }
+        }
+        if let Some((path, ffmpeg_path)) = ffmpeg_override {
+            if let Some(file) = self.dropped_files.get_mut(&path) {
+                match ffmpeg_path {
+                    Some(ffmpeg_path) => file.select_ffmpeg(&ffmpeg_path),
+                    None => file.ffmpeg_path = None,
+                }
+            }
+        }
+        if let Some(path) = rerun {
+            self.rerun_next_period(&path);
+        }
+        if let Some(path) = scrubbed {
+            self.open_scrubber(&path);
+        }
+        if let Some(path) = excluded {
+            self.exclude_flagged_frames(&path);
+        }
+        if let Some(path) = played {
+            self.open_player(&path);
+        }
+        match reviewed {
+            Some((path, true)) => self.approve(&path),
+            Some((path, false)) => {
+                if let Some(file) = self.dropped_files.get_mut(&path) {
+                    file.review = Review::Rejected;
+                }
+            }
+            None => {}
+        }
+        if let Some(column) = sorted_column {
+            if self.sort_column == column {
+                self.is_sort_descending = !self.is_sort_descending;
+            } else {
+                self.sort_column = column;
+                self.is_sort_descending = false;
+            }
+        }
+        if let Some(path) = cancelled {
+            self.request(LockedAction::Cancel(path));
+        }
     }
 }
 
 impl eframe::App for MigrationApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        if self.is_safe_mode && !self.is_settings_reset {
+            return;
+        }
+        match &self.data_dir {
+            Some(data_dir) => {
+                if let Err(e) = crate::storage::save_settings(data_dir, self) {
+                    log::error!("Error saving settings to {}: {}", data_dir.display(), e);
+                }
+            }
+            None => eframe::set_value(storage, eframe::APP_KEY, self),
+        }
+    }
+
+    /// Cancels the running jobs and gives them some time to stop their ffmpeg processes,
+    /// killing those still running afterwards.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.pending_jobs.clear();
+        for cancel in self
+            .dropped_files
+            .values()
+            .filter_map(|file| file.cancel.as_ref())
+        {
+            cancel.cancel();
+        }
+        self.tasks.shutdown();
+        children::kill_all();
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.poll();
 
+        self.start_pending_jobs();
+
         self.update_state();
 
+        self.start_scheduled_batch(ctx);
+
+        self.sync_queue();
+
+        self.sync_dashboard();
+
+        self.build_safe_mode_view(ctx);
+
+        self.build_health_view(ctx);
+
         self.build_settings_view(ctx);
 
+        self.build_map_view(ctx);
+
+        self.build_history_view(ctx);
+
         self.build_drag_and_drop_view(ctx);
 
         self.build_processing_view(ctx);
+
+        self.build_cleanup_view(ctx);
+
+        self.build_import_view(ctx);
+
+        self.build_date_ranges_view(ctx);
+
+        self.build_overrides_view(ctx);
+
+        self.build_scrubber_view(ctx);
+
+        self.build_player_view(ctx);
+
+        self.build_config_editor_view(ctx);
+
+        self.build_schedule_view(ctx);
+
+        self.build_previous_session_view(ctx);
+
+        self.build_session_lock_view(ctx);
+
+        self.build_drop_overlay(ctx);
     }
 }
@@ -0,0 +1,35 @@
+use crate::utils;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_NAME: &str = "manifest-sha256.txt";
+
+/// Builds a manifest in `sha256sum` format with one line per entry of `files`.
+pub fn manifest(files: &[(PathBuf, String)]) -> std::io::Result<String> {
+    let mut manifest = String::new();
+    for (path, name) in files {
+        manifest += format!("{}  {}\n", utils::sha256(path)?, name).as_str();
+    }
+    Ok(manifest)
+}
+
+/// Writes `files` into a ZIP archive at `archive_path`.
+/// Each entry is a source path and its name inside the archive. A `manifest-sha256.txt`
+/// listing the SHA-256 digest of every entry is added at the root of the archive.
+pub fn package(files: &[(PathBuf, String)], archive_path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = archive_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(archive_path)?);
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let manifest = manifest(files)?;
+    for (path, name) in files {
+        zip.start_file(name.as_str(), options)?;
+        std::io::copy(&mut std::fs::File::open(path)?, &mut zip)?;
+    }
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(manifest.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
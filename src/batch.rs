@@ -0,0 +1,124 @@
+use crate::dates::CameraClock;
+use crate::video::Codec;
+use std::path::{Path, PathBuf};
+
+/// Ending of the name of batch files, e.g. `nightly.batch.toml`.
+pub const SUFFIX: &str = ".batch.toml";
+
+/// Settings of a job that replace those of the app.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JobOverrides {
+    pub video_codec: Option<Codec>,
+    pub frame_rate: Option<u32>,
+    /// Clock of the camera, to order the frames of the job by capture time.
+    pub camera_clock: Option<CameraClock>,
+}
+
+impl JobOverrides {
+    /// Parses the `codec`, `frame_rate` and `clock` keys of batch and defaults files.
+    pub fn parse(
+        codec: Option<&str>,
+        frame_rate: Option<u32>,
+        clock: Option<&str>,
+    ) -> Result<Self, String> {
+        let video_codec = match codec {
+            Some(name) => Some(Codec::from_name(name).ok_or(format!("unknown codec {}", name))?),
+            None => None,
+        };
+        if frame_rate == Some(0) {
+            return Err("the frame rate must be positive".to_owned());
+        }
+        let camera_clock = match clock {
+            Some(clock) => Some(CameraClock::parse(clock).ok_or(format!(
+                "unknown camera clock {}, expected local or an offset like +01:00",
+                clock
+            ))?),
+            None => None,
+        };
+        Ok(Self {
+            video_codec,
+            frame_rate,
+            camera_clock,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Describes the overrides, e.g. "h.265 (HEVC, MP4), 8 fps".
+    pub fn describe(&self) -> String {
+        self.video_codec
+            .map(|codec| codec.label().to_owned())
+            .into_iter()
+            .chain(self.frame_rate.map(|rate| format!("{} fps", rate)))
+            .chain(
+                self.camera_clock
+                    .map(|clock| format!("camera clock {}", clock.name())),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A job of a batch file.
+pub struct BatchJob {
+    pub config: PathBuf,
+    pub overrides: JobOverrides,
+    /// Folder the images are written to instead of the one in the config.
+    pub output_path: Option<PathBuf>,
+}
+
+/// A job as written in a batch file, with paths relative to the batch file.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobEntry {
+    config: PathBuf,
+    codec: Option<String>,
+    frame_rate: Option<u32>,
+    clock: Option<String>,
+    output: Option<PathBuf>,
+}
+
+/// A batch file, e.g.
+/// ```toml
+/// [[jobs]]
+/// config = "sites/oak-north.toml"
+/// codec = "prores"
+/// frame_rate = 8
+/// clock = "+01:00"
+/// output = "/archive/oak-north"
+/// ```
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchFile {
+    #[serde(default)]
+    jobs: Vec<JobEntry>,
+}
+
+pub fn is_batch(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().to_lowercase().ends_with(SUFFIX))
+}
+
+/// Reads the jobs of a batch file, resolving their paths against the folder of the file.
+pub fn load(path: &Path) -> Result<Vec<BatchJob>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let batch: BatchFile = toml::from_str(&text).map_err(|e| e.to_string())?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    batch
+        .jobs
+        .into_iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let overrides =
+                JobOverrides::parse(job.codec.as_deref(), job.frame_rate, job.clock.as_deref())
+                    .map_err(|e| format!("Job {}: {}", i + 1, e))?;
+            Ok(BatchJob {
+                config: dir.join(job.config),
+                overrides,
+                output_path: job.output.map(|output| dir.join(output)),
+            })
+        })
+        .collect()
+}
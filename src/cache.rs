@@ -0,0 +1,62 @@
+use crate::dates::DateRange;
+use crate::utils;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// File in the image output folder recording what the migrated frames were made from.
+const STAMP_NAME: &str = ".migration-cache";
+
+/// Returns a key identifying the migrated frames of a job. It covers the config, the date
+/// ranges, the forest green flag and the content of every source image.
+pub fn key(
+    image_config: &tree_migration::Config,
+    date_ranges: &[DateRange],
+    is_forest_green_enabled: bool,
+) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(utils::config_hash(image_config).unwrap_or_default());
+    for (start_date, end_date) in date_ranges {
+        hasher.update(format!("{}..{}", start_date, end_date));
+    }
+    hasher.update([is_forest_green_enabled as u8]);
+    for image in utils::source_images(image_config)? {
+        hasher.update(image.to_string_lossy().as_bytes());
+        hasher.update(utils::sha256(&image)?);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Returns the path of the stamp of the frames in `output_path`.
+pub fn stamp_path(output_path: &Path) -> PathBuf {
+    output_path.join(STAMP_NAME)
+}
+
+/// Checks that `output_path` holds frames migrated with `key` that were not changed since.
+pub fn is_fresh(output_path: &Path, key: &str) -> bool {
+    let Ok(stamp) = std::fs::read_to_string(output_path.join(STAMP_NAME)) else {
+        return false;
+    };
+    let mut lines = stamp.lines();
+    if lines.next() != Some(key) {
+        return false;
+    }
+    lines.all(|line| {
+        let Some((size, name)) = line.split_once(' ') else {
+            return false;
+        };
+        std::fs::metadata(output_path.join(name))
+            .is_ok_and(|metadata| metadata.len().to_string() == size)
+    })
+}
+
+/// Records the frames in `output_path` as migrated with `key`.
+pub fn store(output_path: &Path, key: &str) -> std::io::Result<()> {
+    let mut stamp = format!("{}\n", key);
+    for frame in utils::image_files(output_path)? {
+        let size = std::fs::metadata(&frame)?.len();
+        if let Some(name) = frame.file_name() {
+            stamp += format!("{} {}\n", size, name.to_string_lossy()).as_str();
+        }
+    }
+    std::fs::write(output_path.join(STAMP_NAME), stamp)
+}
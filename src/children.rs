@@ -0,0 +1,192 @@
+use crate::cancel::CancelToken;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, PidExt, ProcessExt, Signal, System, SystemExt};
+
+/// An ffmpeg or hook process started by the app.
+#[derive(Clone)]
+pub struct ChildProcess {
+    pub command: String,
+    pub started: Instant,
+}
+
+/// Child processes of the app by PID, registered while they run.
+static CHILDREN: Mutex<BTreeMap<u32, ChildProcess>> = Mutex::new(BTreeMap::new());
+
+/// Set while processing is paused, so that new child processes are suspended as they start.
+static IS_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps a child process registered until it is dropped.
+pub struct Registration(u32);
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Ok(mut children) = CHILDREN.lock() {
+            children.remove(&self.0);
+        }
+    }
+}
+
+/// Registers the child process `pid` started with `command`, suspending it while processing
+/// is paused.
+pub fn register(pid: u32, command: &Command) -> Registration {
+    let command = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.insert(
+            pid,
+            ChildProcess {
+                command,
+                started: Instant::now(),
+            },
+        );
+    }
+    if IS_PAUSED.load(Ordering::Relaxed) {
+        signal(pid, Signal::Stop);
+    }
+    Registration(pid)
+}
+
+/// Returns the registered child processes by PID.
+pub fn list() -> Vec<(u32, ChildProcess)> {
+    CHILDREN.lock().map_or(Vec::new(), |children| {
+        children
+            .iter()
+            .map(|(pid, child)| (*pid, child.clone()))
+            .collect()
+    })
+}
+
+/// Sends `signal` to the process `pid`. Returns false if the signal is not supported on this
+/// platform, as suspending is not on Windows.
+fn signal(pid: u32, signal: Signal) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_process(pid);
+    system
+        .process(pid)
+        .and_then(|process| process.kill_with(signal))
+        .unwrap_or(false)
+}
+
+/// Suspends every registered child process and those registered until `resume`.
+pub fn pause() {
+    IS_PAUSED.store(true, Ordering::Relaxed);
+    for (pid, _) in list() {
+        signal(pid, Signal::Stop);
+    }
+}
+
+pub fn resume() {
+    IS_PAUSED.store(false, Ordering::Relaxed);
+    for (pid, _) in list() {
+        signal(pid, Signal::Continue);
+    }
+}
+
+/// Kills the child process `pid` if it is registered.
+pub fn kill(pid: u32) -> bool {
+    if !CHILDREN
+        .lock()
+        .is_ok_and(|children| children.contains_key(&pid))
+    {
+        return false;
+    }
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_process(pid);
+    system.process(pid).is_some_and(|process| process.kill())
+}
+
+/// Kills every registered child process, so that no encoder outlives the app.
+pub fn kill_all() {
+    for (pid, child) in list() {
+        if kill(pid) {
+            log::warn!("Killed leftover process {}: {}", pid, child.command);
+        }
+    }
+}
+
+/// Receives the lines of the standard output of a child process.
+pub type LineCallback = Box<dyn FnMut(&str) + Send>;
+
+/// A child process that ran to its end.
+pub struct Finished {
+    pub status: ExitStatus,
+    /// Empty when the lines were handed to `on_stdout_line`.
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub enum RunError {
+    Spawn(std::io::Error),
+    Wait(std::io::Error),
+    Cancelled,
+}
+
+/// Runs `command` without input, registered as a child process until it exits, and collects
+/// its output. The process is killed if `cancel` is triggered while it runs.
+/// With `on_stdout_line`, every line of the standard output is handed to it as it comes
+/// instead of being collected.
+pub fn run(
+    command: &mut Command,
+    cancel: &CancelToken,
+    on_stdout_line: Option<LineCallback>,
+) -> Result<Finished, RunError> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(RunError::Spawn)?;
+    let _registration = register(child.id(), command);
+    let mut stdout = child.stdout.take();
+    let stdout = std::thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stdout) = &mut stdout {
+            match on_stdout_line {
+                Some(mut on_line) => {
+                    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                        on_line(&line);
+                    }
+                }
+                None => {
+                    let _ = stdout.read_to_string(&mut output);
+                }
+            }
+        }
+        output
+    });
+    let mut stderr = child.stderr.take();
+    let stderr = std::thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(stderr) = &mut stderr {
+            let _ = stderr.read_to_string(&mut output);
+        }
+        output
+    });
+    let status = loop {
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(RunError::Cancelled);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(RunError::Wait(e)),
+        }
+    };
+    Ok(Finished {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
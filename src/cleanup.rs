@@ -0,0 +1,60 @@
+use crate::cache;
+use crate::runner;
+use crate::utils;
+use std::path::{Path, PathBuf};
+
+/// Lists the files the job of `image_config` wrote into its output folder: the frames dated
+/// within its date range with their XMP sidecars, its manifest and the frame cache stamp.
+/// The output folder may be shared with other jobs, so its other files and the folder
+/// itself are left out.
+pub fn job_files(image_config: &tree_migration::Config) -> Vec<PathBuf> {
+    let output_path = &image_config.output_path;
+    let mut files = utils::image_files(output_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|frame| {
+            utils::frame_date(frame).is_some_and(|date| {
+                date >= image_config.start_date && date <= image_config.end_date
+            })
+        })
+        .flat_map(|frame| {
+            let mut sidecar = frame.clone().into_os_string();
+            sidecar.push(".xmp");
+            [frame, PathBuf::from(sidecar)]
+        })
+        .collect::<Vec<_>>();
+    files.push(runner::manifest_path(image_config));
+    files.push(cache::stamp_path(output_path));
+    files.retain(|file| file.is_file());
+    files
+}
+
+/// Deletes the output files of a job. Files that do not exist are skipped. Folders are
+/// refused, as only files known to belong to the job may be deleted.
+pub fn delete(outputs: &[PathBuf]) -> std::io::Result<()> {
+    if let Some(folder) = outputs.iter().find(|output| output.is_dir()) {
+        return Err(std::io::Error::other(format!(
+            "{} is a folder, only files of the job are deleted",
+            folder.display()
+        )));
+    }
+    for output in outputs {
+        if output.exists() {
+            std::fs::remove_file(output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the outputs of a job into `quarantine_path`. Outputs that do not exist are skipped.
+pub fn quarantine(outputs: &[PathBuf], quarantine_path: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(quarantine_path)?;
+    for output in outputs {
+        if let Some(name) = output.file_name() {
+            if output.exists() {
+                std::fs::rename(output, quarantine_path.join(name))?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Seconds after which the status page reloads itself.
+const REFRESH_SECONDS: u32 = 5;
+
+/// A job as shown on the status page.
+pub struct JobStatus {
+    pub path: String,
+    pub status: String,
+    /// From 0 to 1, while the job runs.
+    pub progress: Option<f32>,
+    pub error: String,
+}
+
+/// A finished render as shown on the status page.
+pub struct RenderStatus {
+    pub site: String,
+    pub end_date: String,
+    pub deposit_id: Option<String>,
+}
+
+/// What the status page shows, published by the app.
+#[derive(Default)]
+pub struct Snapshot {
+    pub jobs: Vec<JobStatus>,
+    pub renders: Vec<RenderStatus>,
+}
+
+/// Read-only status page of the queue served over HTTP on all interfaces.
+pub struct Dashboard {
+    pub port: u16,
+    snapshot: Arc<Mutex<Snapshot>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Dashboard {
+    pub fn start(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let snapshot = snapshot.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let page = snapshot.lock().map(|s| render(&s)).unwrap_or_default();
+                            if let Err(e) = respond(stream, &page) {
+                                log::error!("Error serving status page {}", e);
+                            }
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(e) => log::error!("Error serving status page {}", e),
+                    }
+                }
+            });
+        }
+        Ok(Self {
+            port,
+            snapshot,
+            stop,
+        })
+    }
+
+    pub fn publish(&self, snapshot: Snapshot) {
+        if let Ok(mut current) = self.snapshot.lock() {
+            *current = snapshot;
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Answers any GET request with the status page and anything else with 405.
+fn respond(mut stream: TcpStream, page: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let response = if request_line.starts_with("GET ") {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            page.len(),
+            page
+        )
+    } else {
+        "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_owned()
+    };
+    stream.write_all(response.as_bytes())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render(snapshot: &Snapshot) -> String {
+    let mut jobs = String::new();
+    for job in &snapshot.jobs {
+        let status = match job.progress {
+            Some(progress) => format!("{} {:.0}%", job.status, progress * 100.0),
+            None => job.status.clone(),
+        };
+        jobs += format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"error\">{}</td></tr>\n",
+            escape_html(&status),
+            escape_html(&job.path),
+            escape_html(&job.error)
+        )
+        .as_str();
+    }
+    if snapshot.jobs.is_empty() {
+        jobs += "<tr><td colspan=\"3\">The queue is empty.</td></tr>\n";
+    }
+    let mut renders = String::new();
+    for render in &snapshot.renders {
+        renders += format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&render.site),
+            escape_html(&render.end_date),
+            escape_html(render.deposit_id.as_deref().unwrap_or(""))
+        )
+        .as_str();
+    }
+    if snapshot.renders.is_empty() {
+        renders += "<tr><td colspan=\"3\">No finished renders yet.</td></tr>\n";
+    }
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<meta http-equiv=\"refresh\" content=\"{refresh}\">
+<title>Tree Migration queue</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ text-align: left; padding: 0.3em 1em; border-bottom: 1px solid #ddd; }}
+.error {{ color: #c00; }}
+</style>
+</head>
+<body>
+<h1>Queue</h1>
+<table>
+<tr><th>Status</th><th>Config</th><th>Error</th></tr>
+{jobs}</table>
+<h1>Recent renders</h1>
+<table>
+<tr><th>Site</th><th>Rendered until</th><th>Deposit</th></tr>
+{renders}</table>
+</body>
+</html>
+",
+        refresh = REFRESH_SECONDS,
+        jobs = jobs,
+        renders = renders,
+    )
+}
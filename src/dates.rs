@@ -0,0 +1,221 @@
+use crate::runner;
+use crate::utils;
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc,
+};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+pub type DateRange = (NaiveDate, NaiveDate);
+
+/// Time zone of the clock of a camera, to put the EXIF capture times of its frames in order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CameraClock {
+    /// Local time of this computer, following its daylight saving changes. Frames taken in
+    /// the hour repeated when the clocks go back cannot be told apart.
+    #[default]
+    Local,
+    /// A fixed offset from UTC in minutes, for cameras that ignore daylight saving time.
+    Offset(i32),
+}
+
+impl CameraClock {
+    /// Parses `local` or an offset from UTC like `+01:00` or `-05:30`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("local") {
+            return Some(CameraClock::Local);
+        }
+        let sign = match text.chars().next()? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        let (hours, minutes) = text[1..].split_once(':')?;
+        let (hours, minutes) = (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?);
+        (hours <= 14 && minutes < 60).then_some(CameraClock::Offset(sign * (hours * 60 + minutes)))
+    }
+
+    /// Returns the clock as it is parsed, e.g. `local` or `+01:00`.
+    pub fn name(&self) -> String {
+        match self {
+            CameraClock::Local => "local".to_owned(),
+            CameraClock::Offset(minutes) => format!(
+                "{}{:02}:{:02}",
+                if *minutes < 0 { '-' } else { '+' },
+                minutes.abs() / 60,
+                minutes.abs() % 60
+            ),
+        }
+    }
+
+    /// Converts a time read from the camera clock to UTC. Of the two times of the repeated
+    /// hour when the clocks go back, the earlier one is taken.
+    pub fn to_utc(self, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            CameraClock::Local => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+            CameraClock::Offset(minutes) => FixedOffset::east_opt(minutes * 60)?
+                .from_local_datetime(&time)
+                .single()
+                .map(|time| time.with_timezone(&Utc)),
+        }
+    }
+}
+
+/// How dates are written into video file names, overlays and slates.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum DateFormat {
+    #[default]
+    Iso,
+    /// Day, month and year, e.g. 01-04-2023.
+    Dmy,
+    /// A strftime pattern such as `%Y%m%d`.
+    Custom(String),
+}
+
+impl DateFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "ISO (2023-04-01)",
+            DateFormat::Dmy => "Day first (01-04-2023)",
+            DateFormat::Custom(_) => "Custom",
+        }
+    }
+
+    fn pattern(&self) -> &str {
+        match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::Dmy => "%d-%m-%Y",
+            DateFormat::Custom(pattern) => pattern,
+        }
+    }
+
+    /// Formats `date` with the pattern, or returns `None` if the pattern is invalid or asks
+    /// for more than a date, e.g. the hour.
+    fn try_format(&self, date: NaiveDate) -> Option<String> {
+        let mut formatted = String::new();
+        write!(formatted, "{}", date.format(self.pattern())).ok()?;
+        Some(formatted)
+    }
+
+    /// Checks that the pattern formats dates into text that can be part of a file name.
+    pub fn validate(&self) -> Result<(), String> {
+        let sample = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap_or_default();
+        match self.try_format(sample) {
+            Some(formatted) if formatted.trim().is_empty() => {
+                Err("The date format is empty".to_owned())
+            }
+            Some(formatted) => match runner::invalid_file_name_char(&formatted) {
+                Some(c) => Err(format!("The date format yields {}", c)),
+                None => Ok(()),
+            },
+            None => Err(format!("Invalid date format {}", self.pattern())),
+        }
+    }
+
+    /// Formats `date`, in ISO format if the pattern is invalid.
+    pub fn format(&self, date: NaiveDate) -> String {
+        match self.validate() {
+            Ok(()) => self.try_format(date).unwrap_or_else(|| date.to_string()),
+            Err(_) => date.to_string(),
+        }
+    }
+}
+
+fn read_config(config_path: &Path) -> Result<toml::Value, String> {
+    std::fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())?
+        .parse::<toml::Value>()
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the dates of the first and last image in the input folder of a config file.
+/// The config file is read as plain TOML so that configs with invalid dates can be fixed too.
+pub fn detect(config_path: &Path) -> Result<DateRange, String> {
+    let config = read_config(config_path)?;
+    let input_path = config
+        .get("input_path")
+        .and_then(|value| value.as_str())
+        .map(PathBuf::from)
+        .ok_or("Config has no input path".to_owned())?;
+    let dates = utils::image_files(&input_path)
+        .map_err(|e| format!("Cannot scan {}: {}", input_path.display(), e))?
+        .iter()
+        .filter_map(|image| utils::image_date(image))
+        .collect::<Vec<_>>();
+    match (dates.iter().min(), dates.iter().max()) {
+        (Some(start), Some(end)) => Ok((*start, *end)),
+        _ => Err(format!("No dated images in {}", input_path.display())),
+    }
+}
+
+/// Writes `start_date` and `end_date` into a config file, leaving all other keys untouched.
+pub fn apply(config_path: &Path, (start_date, end_date): DateRange) -> Result<(), String> {
+    let mut config = read_config(config_path)?;
+    let table = config
+        .as_table_mut()
+        .ok_or("Config is not a table".to_owned())?;
+    table.insert(
+        "start_date".to_owned(),
+        toml::Value::String(start_date.to_string()),
+    );
+    table.insert(
+        "end_date".to_owned(),
+        toml::Value::String(end_date.to_string()),
+    );
+    let contents = toml::to_string(&config).map_err(|e| e.to_string())?;
+    std::fs::write(config_path, contents).map_err(|e| e.to_string())
+}
+
+/// Parses and sorts date ranges entered as `YYYY-MM-DD`, rejecting overlapping ranges.
+pub fn parse_ranges(ranges: &[(String, String)]) -> Result<Vec<DateRange>, String> {
+    let parse = |date: &String| {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))
+    };
+    let mut parsed = Vec::new();
+    for (start_date, end_date) in ranges {
+        let (start_date, end_date) = (parse(start_date)?, parse(end_date)?);
+        if start_date > end_date {
+            return Err(format!("{} is after {}", start_date, end_date));
+        }
+        parsed.push((start_date, end_date));
+    }
+    parsed.sort();
+    for pair in parsed.windows(2) {
+        if pair[1].0 <= pair[0].1 {
+            return Err(format!(
+                "{} to {} overlaps {} to {}",
+                pair[0].0, pair[0].1, pair[1].0, pair[1].1
+            ));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Returns the period following `range`. Ranges covering whole months advance by the same
+/// number of months, other ranges by the same number of days.
+pub fn next_period((start_date, end_date): DateRange) -> DateRange {
+    let next_start = end_date.succ_opt().unwrap_or(end_date);
+    if start_date.day() == 1 && next_start.day() == 1 {
+        let months = (end_date.year() - start_date.year()) * 12 + end_date.month() as i32
+            - start_date.month() as i32
+            + 1;
+        if let Some(next_end) = next_start
+            .checked_add_months(Months::new(months as u32))
+            .and_then(|date| date.pred_opt())
+        {
+            return (next_start, next_end);
+        }
+    }
+    (next_start, next_start + (end_date - start_date))
+}
+
+/// Copies a config file to `new_path` with its dates set to `range`.
+pub fn clone_config(config_path: &Path, new_path: &Path, range: DateRange) -> Result<(), String> {
+    std::fs::copy(config_path, new_path).map_err(|e| e.to_string())?;
+    apply(new_path, range)
+}
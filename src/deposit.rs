@@ -0,0 +1,292 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::NaiveDate;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Repository {
+    Zenodo,
+    /// A DSpace instance accepting SWORD v2 deposits.
+    DSpace,
+}
+
+impl Repository {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Repository::Zenodo => "Zenodo",
+            Repository::DSpace => "DSpace",
+        }
+    }
+}
+
+/// Where archives are deposited and with which credentials.
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DepositSettings {
+    pub repository: Repository,
+    /// Zenodo base URL, or the SWORD collection URL of DSpace.
+    pub url: String,
+    /// DSpace user name, unused by Zenodo.
+    pub username: String,
+    /// Zenodo access token or DSpace password. It is kept out of the settings file, which is
+    /// plain text, and has to be entered again in every session.
+    #[serde(skip)]
+    pub token: String,
+    /// Creator of the deposits, as `Family name, Given names` or an organisation name.
+    pub creator: String,
+}
+
+impl Default for DepositSettings {
+    fn default() -> Self {
+        Self {
+            repository: Repository::Zenodo,
+            url: "https://zenodo.org".to_owned(),
+            username: String::new(),
+            token: String::new(),
+            creator: String::new(),
+        }
+    }
+}
+
+impl DepositSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url.trim().is_empty() {
+            return Err("The repository URL is not set".to_owned());
+        }
+        if self.token.is_empty() {
+            return Err("The repository credentials are not set".to_owned());
+        }
+        if self.creator.trim().is_empty() {
+            return Err("The creator of the deposits is not set".to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// Metadata of a deposit, taken from the job.
+pub struct Metadata {
+    pub title: String,
+    pub location: String,
+    pub camera: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl Metadata {
+    fn description(&self) -> String {
+        format!(
+            "Tree migration images of {} taken by camera {} from {} to {}.",
+            self.location, self.camera, self.start_date, self.end_date
+        )
+    }
+}
+
+/// An archive deposited to a repository.
+#[derive(Clone)]
+pub struct Deposit {
+    /// DOI on Zenodo, reserved until the deposition is published, or handle on DSpace.
+    pub id: String,
+    /// Id of the Zenodo deposition while it is an unpublished draft.
+    pub draft: Option<u64>,
+}
+
+/// Deposits the archive at `archive_path`. Zenodo depositions are left as drafts, to be
+/// checked on Zenodo and published with [`publish`].
+pub fn deposit(
+    settings: &DepositSettings,
+    archive_path: &Path,
+    metadata: &Metadata,
+) -> Result<Deposit, String> {
+    settings.validate()?;
+    match settings.repository {
+        Repository::Zenodo => deposit_zenodo(settings, archive_path, metadata),
+        Repository::DSpace => deposit_dspace(settings, archive_path, metadata),
+    }
+}
+
+fn file_name(archive_path: &Path) -> String {
+    archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "archive.zip".to_owned())
+}
+
+fn read_json(response: ureq::Response) -> Result<serde_json::Value, String> {
+    let body = response.into_string().map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|e| format!("Invalid repository response: {}", e))
+}
+
+fn zenodo_depositions(settings: &DepositSettings) -> String {
+    format!(
+        "{}/api/deposit/depositions",
+        settings.url.trim().trim_end_matches('/')
+    )
+}
+
+fn deposit_zenodo(
+    settings: &DepositSettings,
+    archive_path: &Path,
+    metadata: &Metadata,
+) -> Result<Deposit, String> {
+    let depositions = zenodo_depositions(settings);
+    let authorization = format!("Bearer {}", settings.token);
+    let deposition = ureq::post(&depositions)
+        .set("Authorization", &authorization)
+        .set("Content-Type", "application/json")
+        .send_string("{}")
+        .map_err(|e| format!("Cannot create the deposition: {}", e))
+        .and_then(read_json)?;
+    let id = deposition["id"]
+        .as_u64()
+        .ok_or("The repository returned no deposition id".to_owned())?;
+    let bucket = deposition["links"]["bucket"]
+        .as_str()
+        .ok_or("The repository returned no file bucket".to_owned())?;
+
+    let archive = std::fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    ureq::put(&format!("{}/{}", bucket, file_name(archive_path)))
+        .set("Authorization", &authorization)
+        .set("Content-Type", "application/octet-stream")
+        .send(archive)
+        .map_err(|e| format!("Cannot upload the archive: {}", e))?;
+
+    let body = serde_json::json!({
+        "metadata": {
+            "title": metadata.title,
+            "upload_type": "dataset",
+            "description": metadata.description(),
+            "creators": [{ "name": settings.creator.trim() }],
+            "keywords": [metadata.location, metadata.camera],
+            "dates": [{
+                "start": metadata.start_date.to_string(),
+                "end": metadata.end_date.to_string(),
+                "type": "Collected",
+            }],
+            "prereserve_doi": true,
+        }
+    });
+    let updated = ureq::put(&format!("{}/{}", depositions, id))
+        .set("Authorization", &authorization)
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|e| format!("Cannot set the deposition metadata: {}", e))
+        .and_then(read_json)?;
+    let doi = updated["metadata"]["prereserve_doi"]["doi"]
+        .as_str()
+        .ok_or("The repository reserved no DOI".to_owned())?;
+    Ok(Deposit {
+        id: doi.to_owned(),
+        draft: Some(id),
+    })
+}
+
+/// Publishes the draft Zenodo deposition `draft` and returns its DOI. Published depositions
+/// cannot be deleted.
+pub fn publish(settings: &DepositSettings, draft: u64) -> Result<String, String> {
+    settings.validate()?;
+    if settings.repository != Repository::Zenodo {
+        return Err(format!(
+            "Deposits to {} are not published from here",
+            settings.repository.label()
+        ));
+    }
+    let published = ureq::post(&format!(
+        "{}/{}/actions/publish",
+        zenodo_depositions(settings),
+        draft
+    ))
+    .set("Authorization", &format!("Bearer {}", settings.token))
+    .call()
+    .map_err(|e| format!("Cannot publish the deposition: {}", e))
+    .and_then(read_json)?;
+    published["doi"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or("The repository returned no DOI".to_owned())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the `href` of the first link with relation `rel` in a SWORD deposit receipt.
+fn link(receipt: &str, rel: &str) -> Option<String> {
+    let pattern = format!("rel=\"{}\"", rel);
+    receipt.split('<').find_map(|element| {
+        if !element.starts_with("link") || !element.contains(&pattern) {
+            return None;
+        }
+        let (_, href) = element.split_once("href=\"")?;
+        let (href, _) = href.split_once('"')?;
+        Some(href.replace("&amp;", "&"))
+    })
+}
+
+fn deposit_dspace(
+    settings: &DepositSettings,
+    archive_path: &Path,
+    metadata: &Metadata,
+) -> Result<Deposit, String> {
+    let authorization = format!(
+        "Basic {}",
+        STANDARD.encode(format!("{}:{}", settings.username, settings.token))
+    );
+    let entry = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<entry xmlns=\"http://www.w3.org/2005/Atom\" xmlns:dcterms=\"http://purl.org/dc/terms/\">
+    <title>{title}</title>
+    <dcterms:title>{title}</dcterms:title>
+    <dcterms:creator>{creator}</dcterms:creator>
+    <dcterms:description>{description}</dcterms:description>
+    <dcterms:subject>{location}</dcterms:subject>
+    <dcterms:subject>{camera}</dcterms:subject>
+    <dcterms:temporal>{start_date}/{end_date}</dcterms:temporal>
+</entry>
+",
+        title = escape_xml(&metadata.title),
+        creator = escape_xml(settings.creator.trim()),
+        description = escape_xml(&metadata.description()),
+        location = escape_xml(&metadata.location),
+        camera = escape_xml(&metadata.camera),
+        start_date = metadata.start_date,
+        end_date = metadata.end_date,
+    );
+    let receipt = ureq::post(settings.url.trim())
+        .set("Authorization", &authorization)
+        .set("Content-Type", "application/atom+xml;type=entry")
+        .set("In-Progress", "true")
+        .send_string(&entry)
+        .map_err(|e| format!("Cannot create the item: {}", e))?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let edit_media =
+        link(&receipt, "edit-media").ok_or("The repository returned no media link".to_owned())?;
+    let edit = link(&receipt, "edit").ok_or("The repository returned no edit link".to_owned())?;
+    let handle =
+        link(&receipt, "alternate").ok_or("The repository returned no handle".to_owned())?;
+
+    let archive = std::fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    ureq::post(&edit_media)
+        .set("Authorization", &authorization)
+        .set("Content-Type", "application/zip")
+        .set(
+            "Content-Disposition",
+            format!("attachment; filename={}", file_name(archive_path)).as_str(),
+        )
+        .set("Packaging", "http://purl.org/net/sword/package/Binary")
+        .send(archive)
+        .map_err(|e| format!("Cannot upload the archive: {}", e))?;
+
+    ureq::post(&edit)
+        .set("Authorization", &authorization)
+        .set("In-Progress", "false")
+        .call()
+        .map_err(|e| format!("Cannot complete the item: {}", e))?;
+    Ok(Deposit {
+        id: handle,
+        draft: None,
+    })
+}
@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+
+pub fn is_config(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
+
+/// Dropped paths sorted by what happens to them.
+#[derive(Default)]
+pub struct DropSummary {
+    pub accepted: Vec<PathBuf>,
+    pub folders: Vec<PathBuf>,
+    pub unsupported: Vec<PathBuf>,
+}
+
+impl DropSummary {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>, accepts: fn(&Path) -> bool) -> Self {
+        let mut summary = Self::default();
+        for path in paths {
+            if path.is_dir() {
+                summary.folders.push(path);
+            } else if accepts(&path) {
+                summary.accepted.push(path);
+            } else {
+                summary.unsupported.push(path);
+            }
+        }
+        summary
+    }
+
+    /// Describes the drop, e.g. "Add 12 config files", for files of the given `kind`.
+    pub fn describe(&self, kind: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.accepted.is_empty() {
+            lines.push(format!("Add {} {}", self.accepted.len(), kind));
+        }
+        if !self.folders.is_empty() {
+            lines.push(format!(
+                "{} folders will be scanned for {}",
+                self.folders.len(),
+                kind
+            ));
+        }
+        if !self.unsupported.is_empty() {
+            lines.push(format!(
+                "{} unsupported files will be ignored",
+                self.unsupported.len()
+            ));
+        }
+        lines
+    }
+
+    /// Returns the accepted files together with those found in the dropped folders.
+    pub fn files(&self, accepts: fn(&Path) -> bool) -> Vec<PathBuf> {
+        let mut files = self.accepted.clone();
+        for folder in &self.folders {
+            find_files(folder, accepts, &mut files);
+        }
+        files
+    }
+}
+
+fn find_files(folder: &Path, accepts: fn(&Path) -> bool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.is_dir() {
+            find_files(&path, accepts, files);
+        } else if accepts(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// Returns the paths of dropped items together with the number of items that could not be
+/// read. Items that carry their contents instead of a path, as dropped from some browsers and
+/// archive tools, are written into `temp_dir` if their name is accepted.
+pub fn dropped_paths(
+    files: Vec<egui::DroppedFile>,
+    temp_dir: &Path,
+    accepts: fn(&Path) -> bool,
+) -> (Vec<PathBuf>, usize) {
+    let mut paths = Vec::new();
+    let mut unreadable = 0;
+    for file in files {
+        if let Some(path) = file.path {
+            paths.push(path);
+            continue;
+        }
+        let Some(name) = Path::new(&file.name).file_name().map(PathBuf::from) else {
+            unreadable += 1;
+            continue;
+        };
+        if !accepts(&name) {
+            paths.push(name);
+            continue;
+        }
+        match file.bytes.map(|bytes| save_bytes(&name, &bytes, temp_dir)) {
+            Some(Ok(path)) => paths.push(path),
+            Some(Err(e)) => {
+                log::warn!("Cannot save dropped {}: {}", name.display(), e);
+                unreadable += 1;
+            }
+            None => unreadable += 1,
+        }
+    }
+    (paths, unreadable)
+}
+
+/// Writes dropped contents into a new folder in `temp_dir`, so drops with the same name
+/// don't replace each other.
+fn save_bytes(name: &Path, bytes: &[u8], temp_dir: &Path) -> std::io::Result<PathBuf> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let folder = temp_dir.join("dropped").join(timestamp.to_string());
+    std::fs::create_dir_all(&folder)?;
+    let path = folder.join(name);
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+use std::time::Duration;
+
+/// Config keys shown in the editor with their labels.
+pub const FIELDS: [(&str, &str); 6] = [
+    ("location", "Location"),
+    ("camera", "Camera"),
+    ("start_date", "Start date"),
+    ("end_date", "End date"),
+    ("input_path", "Input folder"),
+    ("output_path", "Output folder"),
+];
+
+/// Time after the last edit before the config is validated.
+pub const VALIDATION_DELAY: Duration = Duration::from_millis(500);
+
+/// What the editor shows of a config file.
+pub enum Contents {
+    /// Values of `FIELDS`.
+    Fields(Vec<String>),
+    /// Text of a file that is not valid TOML, edited as is.
+    Raw(String),
+}
+
+impl Default for Contents {
+    fn default() -> Self {
+        Contents::Fields(vec![String::new(); FIELDS.len()])
+    }
+}
+
+/// Reads the values of `FIELDS` from a config file, or its text if it is not valid TOML.
+/// Missing keys are returned empty.
+pub fn read(config_path: &Path) -> Result<Contents, String> {
+    let contents = std::fs::read_to_string(config_path).map_err(|e| e.to_string())?;
+    let Ok(document) = contents.parse::<toml_edit::Document>() else {
+        return Ok(Contents::Raw(contents));
+    };
+    Ok(Contents::Fields(
+        FIELDS
+            .iter()
+            .map(|(key, _)| match document.get(key) {
+                Some(item) => item
+                    .as_str()
+                    .map_or_else(|| item.to_string().trim().to_owned(), str::to_owned),
+                None => String::new(),
+            })
+            .collect(),
+    ))
+}
+
+/// Checks that the config file with `contents` parses as a config, without changing the file.
+pub fn validate(config_path: &Path, contents: &Contents) -> Result<(), String> {
+    let text = with_contents(config_path, contents)?;
+    let check_path =
+        std::env::temp_dir().join(format!("tree-migration-check-{}.toml", std::process::id()));
+    std::fs::write(&check_path, text).map_err(|e| e.to_string())?;
+    let result = tree_migration::Config::from(&check_path)
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    let _ = std::fs::remove_file(&check_path);
+    result
+}
+
+/// Writes `contents` into a config file. Fields are written into the file as it is, keeping
+/// all other keys, comments and formatting.
+pub fn save(config_path: &Path, contents: &Contents) -> Result<(), String> {
+    let text = with_contents(config_path, contents)?;
+    std::fs::write(config_path, text).map_err(|e| e.to_string())
+}
+
+fn with_contents(config_path: &Path, contents: &Contents) -> Result<String, String> {
+    let values = match contents {
+        Contents::Fields(values) => values,
+        Contents::Raw(text) => {
+            return text
+                .parse::<toml_edit::Document>()
+                .map(|_| text.clone())
+                .map_err(|e| e.to_string())
+        }
+    };
+    let mut document = std::fs::read_to_string(config_path)
+        .map_err(|e| e.to_string())?
+        .parse::<toml_edit::Document>()
+        .map_err(|e| e.to_string())?;
+    for ((key, _), value) in FIELDS.iter().zip(values) {
+        let value = value.trim();
+        match document
+            .get_mut(key)
+            .and_then(toml_edit::Item::as_value_mut)
+        {
+            Some(old) => {
+                // Values that were not strings, such as TOML dates, keep their type if they
+                // still parse as one.
+                let mut new = match old {
+                    toml_edit::Value::String(_) => toml_edit::Value::from(value),
+                    _ => value
+                        .parse::<toml_edit::Value>()
+                        .unwrap_or_else(|_| toml_edit::Value::from(value)),
+                };
+                *new.decor_mut() = old.decor().clone();
+                *old = new;
+            }
+            None if value.is_empty() => {}
+            None => document[key] = toml_edit::value(value),
+        }
+    }
+    Ok(document.to_string())
+}
@@ -0,0 +1,43 @@
+use crate::utils;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Places the frames of `source` into `destination` as `frame_000001.jpg`, `frame_000002.jpg`, …
+/// Frames are hard linked where possible and copied otherwise. `frame_` files left in
+/// `destination` by an earlier export that are not part of the new sequence are removed, so
+/// that editors do not import them with it.
+/// Returns the number of exported frames.
+pub fn export_sequence(source: &Path, destination: &Path) -> std::io::Result<usize> {
+    std::fs::create_dir_all(destination)?;
+    let frames = utils::image_files(source)?;
+    let targets = frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let extension = frame
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            destination.join(format!("frame_{:06}.{}", index + 1, extension))
+        })
+        .collect::<Vec<_>>();
+    let sequence = targets.iter().collect::<HashSet<&PathBuf>>();
+    for entry in std::fs::read_dir(destination)? {
+        let path = entry?.path();
+        let is_frame = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("frame_"));
+        if is_frame && path.is_file() && !sequence.contains(&path) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    for (frame, target) in frames.iter().zip(&targets) {
+        if target.exists() {
+            std::fs::remove_file(target)?;
+        }
+        if std::fs::hard_link(frame, target).is_err() {
+            std::fs::copy(frame, target)?;
+        }
+    }
+    Ok(frames.len())
+}
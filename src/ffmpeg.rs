@@ -0,0 +1,185 @@
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const BINARY_NAME: &str = if cfg!(windows) {
+    "ffmpeg.exe"
+} else {
+    "ffmpeg"
+};
+
+/// Folders ffmpeg is commonly installed in besides those on `PATH`.
+const COMMON_DIRS: &[&str] = if cfg!(windows) {
+    &[
+        "C:\\ffmpeg\\bin",
+        "C:\\Program Files\\ffmpeg\\bin",
+        "C:\\ProgramData\\chocolatey\\bin",
+    ]
+} else {
+    &[
+        "/opt/homebrew/bin",
+        "/usr/local/bin",
+        "/usr/bin",
+        "/opt/local/bin",
+        "/snap/bin",
+    ]
+};
+
+/// How a static build is packed.
+#[derive(Clone, Copy, PartialEq)]
+enum Archive {
+    Zip,
+    /// Extracted with the `tar` of the system, which every Linux distribution ships.
+    TarXz,
+}
+
+/// A static ffmpeg build pinned to a version, checked against the SHA-256 digest of its
+/// archive before it is installed.
+pub struct Build {
+    pub version: &'static str,
+    pub url: &'static str,
+    /// Digest of the archive at `url`. Builds without one are not downloaded.
+    sha256: Option<&'static str>,
+    archive: Archive,
+}
+
+/// Static ffmpeg build for the current platform. The URLs point at one release, so the
+/// archive and its digest do not change when a newer build is published.
+pub const BUILD: Option<Build> = if cfg!(windows) {
+    Some(Build {
+        version: "7.0.2",
+        url: "https://github.com/GyanD/codexffmpeg/releases/download/7.0.2/ffmpeg-7.0.2-essentials_build.zip",
+        sha256: None,
+        archive: Archive::Zip,
+    })
+} else if cfg!(target_os = "macos") {
+    Some(Build {
+        version: "7.0.2",
+        url: "https://evermeet.cx/ffmpeg/ffmpeg-7.0.2.zip",
+        sha256: None,
+        archive: Archive::Zip,
+    })
+} else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+    Some(Build {
+        version: "7.0.2",
+        url: "https://johnvansickle.com/ffmpeg/releases/ffmpeg-7.0.2-amd64-static.tar.xz",
+        sha256: None,
+        archive: Archive::TarXz,
+    })
+} else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+    Some(Build {
+        version: "7.0.2",
+        url: "https://johnvansickle.com/ffmpeg/releases/ffmpeg-7.0.2-arm64-static.tar.xz",
+        sha256: None,
+        archive: Archive::TarXz,
+    })
+} else {
+    None
+};
+
+/// Looks for ffmpeg on `PATH`, in the common install folders and in `data_dir`, returning the
+/// first binary accepted by images-to-video.
+pub fn detect(data_dir: Option<&Path>) -> Option<PathBuf> {
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(COMMON_DIRS.iter().map(PathBuf::from))
+        .chain(data_dir.map(|dir| dir.join("ffmpeg")))
+        .map(|dir| dir.join(BINARY_NAME))
+        .filter(|candidate| candidate.is_file())
+        .find_map(|candidate| {
+            images_to_video::utils::ffmpeg_path(candidate.display().to_string().as_str()).ok()
+        })
+}
+
+/// Downloads the static ffmpeg build for the current platform into `data_dir` and returns
+/// the path of the binary. The archive is only extracted if it matches the digest of the build.
+pub fn download(data_dir: &Path) -> Result<PathBuf, String> {
+    let build = BUILD.ok_or("No ffmpeg download for this platform".to_owned())?;
+    let sha256 = build.sha256.ok_or(format!(
+        "No checksum is known for the ffmpeg {} download",
+        build.version
+    ))?;
+    let mut archive = Vec::new();
+    ureq::get(build.url)
+        .call()
+        .map_err(|e| format!("Cannot download {}: {}", build.url, e))?
+        .into_reader()
+        .read_to_end(&mut archive)
+        .map_err(|e| format!("Cannot download {}: {}", build.url, e))?;
+    let digest = format!("{:x}", Sha256::digest(&archive));
+    if !digest.eq_ignore_ascii_case(sha256) {
+        return Err(format!(
+            "The checksum of the downloaded archive is {}, not the expected {}",
+            digest, sha256
+        ));
+    }
+    let folder = data_dir.join("ffmpeg");
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let binary = folder.join(BINARY_NAME);
+    match build.archive {
+        Archive::Zip => extract_zip(archive, &binary)?,
+        Archive::TarXz => extract_tar_xz(&archive, &folder, &binary)?,
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| e.to_string())?;
+    }
+    images_to_video::utils::ffmpeg_path(binary.display().to_string().as_str())
+        .map_err(|e| e.to_string())
+}
+
+/// Writes the ffmpeg binary of a ZIP archive to `binary`.
+fn extract_zip(archive: Vec<u8>, binary: &Path) -> Result<(), String> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive))
+        .map_err(|e| format!("Invalid ffmpeg archive: {}", e))?;
+    let name = zip
+        .file_names()
+        .find(|name| Path::new(name).file_name() == Some(BINARY_NAME.as_ref()))
+        .map(str::to_owned)
+        .ok_or("The archive contains no ffmpeg binary".to_owned())?;
+    let mut entry = zip.by_name(&name).map_err(|e| e.to_string())?;
+    let mut file = std::fs::File::create(binary).map_err(|e| e.to_string())?;
+    std::io::copy(&mut entry, &mut file).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Moves the ffmpeg binary of a tar.xz archive to `binary`, unpacking the archive in a
+/// temporary folder inside `folder`.
+fn extract_tar_xz(archive: &[u8], folder: &Path, binary: &Path) -> Result<(), String> {
+    let unpacked = folder.join("unpacked");
+    let _ = std::fs::remove_dir_all(&unpacked);
+    std::fs::create_dir_all(&unpacked).map_err(|e| e.to_string())?;
+    let archive_path = folder.join("ffmpeg.tar.xz");
+    std::fs::write(&archive_path, archive).map_err(|e| e.to_string())?;
+    let result = Command::new("tar")
+        .arg("-xJf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(&unpacked)
+        .output()
+        .map_err(|e| format!("Cannot run tar: {}", e))
+        .and_then(|output| {
+            if !output.status.success() {
+                return Err(format!(
+                    "Cannot unpack the ffmpeg archive: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            let found = std::fs::read_dir(&unpacked)
+                .map_err(|e| e.to_string())?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().join(BINARY_NAME))
+                .find(|candidate| candidate.is_file())
+                .ok_or("The archive contains no ffmpeg binary".to_owned())?;
+            std::fs::rename(found, binary).map_err(|e| e.to_string())
+        });
+    let _ = std::fs::remove_file(&archive_path);
+    let _ = std::fs::remove_dir_all(&unpacked);
+    result
+}
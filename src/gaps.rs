@@ -0,0 +1,135 @@
+use crate::dates::{DateFormat, DateRange};
+use crate::utils;
+use chrono::NaiveDate;
+use image::{Rgb, RgbImage};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum GapFilling {
+    Skip,
+    HoldLastFrame,
+    Slate,
+}
+
+const DATE_FORMATS: [&str; 3] = ["%Y%m%d", "%Y-%m-%d", "%Y_%m_%d"];
+pub const SLATE_BACKGROUND: u8 = 32;
+
+/// Returns the file name of `frame` with its date replaced by `date`, so that the new frame
+/// sorts between its neighbours.
+fn renamed(frame: &Path, frame_date: NaiveDate, date: NaiveDate) -> Option<String> {
+    let name = frame.file_name()?.to_string_lossy();
+    DATE_FORMATS.iter().find_map(|format| {
+        let old = frame_date.format(format).to_string();
+        name.contains(&old)
+            .then(|| name.replacen(&old, &date.format(format).to_string(), 1))
+    })
+}
+
+/// Renders a "No data" slate for `date` with the dimensions of `frame`.
+fn slate(frame: &Path, date: NaiveDate, date_format: &DateFormat) -> image::ImageResult<RgbImage> {
+    let (width, height) = image::image_dimensions(frame)?;
+    let mut slate = RgbImage::from_pixel(width, height, Rgb([SLATE_BACKGROUND; 3]));
+    let Some(font) = utils::font() else {
+        return Ok(slate);
+    };
+
+    let scale = rusttype::Scale::uniform(height as f32 / 12.0);
+    let v_metrics = font.v_metrics(scale);
+    let text = format!("No data {}", date_format.format(date));
+    let glyphs = font
+        .layout(&text, scale, rusttype::point(0.0, v_metrics.ascent))
+        .collect::<Vec<_>>();
+    let text_width = glyphs
+        .iter()
+        .filter_map(|glyph| glyph.pixel_bounding_box())
+        .map(|bounding_box| bounding_box.max.x)
+        .max()
+        .unwrap_or(0);
+    let offset_x = (width as i32 - text_width) / 2;
+    let offset_y = ((height as f32 - v_metrics.ascent + v_metrics.descent) / 2.0) as i32;
+    for glyph in glyphs {
+        let Some(bounding_box) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|x, y, coverage| {
+            let x = x as i32 + bounding_box.min.x + offset_x;
+            let y = y as i32 + bounding_box.min.y + offset_y;
+            if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                let value = SLATE_BACKGROUND as f32 + coverage * (255 - SLATE_BACKGROUND) as f32;
+                slate.put_pixel(x as u32, y as u32, Rgb([value as u8; 3]));
+            }
+        });
+    }
+    Ok(slate)
+}
+
+/// Lists `frames` with an entry for every day missing between two dated frames, which holds
+/// the date and the file name of the frame filling it next to the frame before it. Days outside
+/// of `date_ranges` were left out on purpose and stay missing. Without date ranges, every day
+/// is filled.
+pub fn with_gaps<'a>(
+    frames: &'a [PathBuf],
+    date_ranges: &[DateRange],
+) -> Vec<(&'a Path, Option<(NaiveDate, String)>)> {
+    let is_included = |date: &NaiveDate| {
+        date_ranges.is_empty()
+            || date_ranges
+                .iter()
+                .any(|(start_date, end_date)| start_date <= date && date <= end_date)
+    };
+    let mut filled = Vec::with_capacity(frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        filled.push((frame.as_path(), None));
+        let (Some(frame_date), Some(next_date)) = (
+            utils::frame_date(frame),
+            frames.get(i + 1).and_then(|next| utils::frame_date(next)),
+        ) else {
+            continue;
+        };
+        for date in frame_date
+            .iter_days()
+            .skip(1)
+            .take_while(|date| date < &next_date)
+            .filter(is_included)
+        {
+            if let Some(name) = renamed(frame, frame_date, date) {
+                filled.push((frame.as_path(), Some((date, name))));
+            }
+        }
+    }
+    filled
+}
+
+/// Returns `frames` with the missing days filled as listed by `with_gaps`. The filling frames
+/// are written into `dir`, so that the output folder keeps only the real frames.
+pub fn fill_gaps(
+    frames: &[PathBuf],
+    date_ranges: &[DateRange],
+    gap_filling: GapFilling,
+    date_format: &DateFormat,
+    dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    if gap_filling == GapFilling::Skip {
+        return Ok(frames.to_vec());
+    }
+    std::fs::create_dir_all(dir)?;
+    let mut filled = Vec::with_capacity(frames.len());
+    for (frame, gap) in with_gaps(frames, date_ranges) {
+        let Some((date, name)) = gap else {
+            filled.push(frame.to_path_buf());
+            continue;
+        };
+        let target = dir.join(name);
+        match gap_filling {
+            GapFilling::HoldLastFrame => {
+                std::fs::copy(frame, &target)?;
+            }
+            GapFilling::Slate => slate(frame, date, date_format)
+                .and_then(|slate| slate.save(&target))
+                .map_err(std::io::Error::other)?,
+            GapFilling::Skip => {}
+        }
+        filled.push(target);
+    }
+    Ok(filled)
+}
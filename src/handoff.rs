@@ -0,0 +1,115 @@
+use crate::app::SettingsPreset;
+use crate::batch::JobOverrides;
+use crate::dates::CameraClock;
+use crate::pipeline::Review;
+use crate::queue::{self, JobRecord, RecordStatus};
+use crate::video::Codec;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// A job as written in a handoff file.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct HandoffJob {
+    path: PathBuf,
+    status: String,
+    error: Option<String>,
+    output_override: Option<PathBuf>,
+    /// Space-separated `start/end` dates, as in the queue database.
+    date_ranges: String,
+    video_codec: Option<Codec>,
+    frame_rate: Option<u32>,
+    #[serde(default)]
+    camera_clock: Option<String>,
+    #[serde(default)]
+    video: Option<PathBuf>,
+    #[serde(default)]
+    video_name_collision: Option<usize>,
+    #[serde(default)]
+    review: String,
+    #[serde(default)]
+    proxy: Option<PathBuf>,
+    #[serde(default)]
+    flagged_frames: BTreeSet<PathBuf>,
+    #[serde(default)]
+    excluded_frames: BTreeSet<PathBuf>,
+}
+
+/// Queue state and output settings of one machine, to continue the batch on another machine
+/// that mounts the same storage. It is only exported while no batch runs, so no job is
+/// claimed by both machines. Jobs that were not done start over, unless their output folder
+/// holds frames the frame cache records as completely migrated.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Handoff {
+    pub exported_at: String,
+    /// Output settings of the exporting machine, without its ffmpeg binary.
+    pub settings: SettingsPreset,
+    jobs: Vec<HandoffJob>,
+}
+
+impl Handoff {
+    pub fn new(settings: SettingsPreset, records: &[JobRecord]) -> Self {
+        Self {
+            exported_at: chrono::Local::now().to_rfc3339(),
+            settings: SettingsPreset {
+                ffmpeg_path: None,
+                ..settings
+            },
+            jobs: records
+                .iter()
+                .map(|record| HandoffJob {
+                    path: record.path.clone(),
+                    status: record.status.as_str().to_owned(),
+                    error: record.error.clone(),
+                    output_override: record.output_override.clone(),
+                    date_ranges: queue::format_date_ranges(&record.date_ranges),
+                    video_codec: record.overrides.video_codec,
+                    frame_rate: record.overrides.frame_rate,
+                    camera_clock: record.overrides.camera_clock.map(|clock| clock.name()),
+                    video: record.video.clone(),
+                    video_name_collision: record.video_name_collision,
+                    review: record.review.as_str().to_owned(),
+                    proxy: record.proxy.clone(),
+                    flagged_frames: record.flagged_frames.clone(),
+                    excluded_frames: record.excluded_frames.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the jobs as queue records. ffmpeg binaries differ between machines, so every
+    /// job uses the one in the settings of this machine.
+    pub fn records(&self) -> Vec<JobRecord> {
+        self.jobs
+            .iter()
+            .map(|job| JobRecord {
+                path: job.path.clone(),
+                status: RecordStatus::parse(&job.status),
+                error: job.error.clone(),
+                output_override: job.output_override.clone(),
+                date_ranges: queue::parse_date_ranges(&job.date_ranges),
+                ffmpeg_path: None,
+                overrides: JobOverrides {
+                    video_codec: job.video_codec,
+                    frame_rate: job.frame_rate,
+                    camera_clock: job.camera_clock.as_deref().and_then(CameraClock::parse),
+                },
+                video: job.video.clone(),
+                video_name_collision: job.video_name_collision,
+                review: Review::parse(&job.review),
+                proxy: job.proxy.clone(),
+                flagged_frames: job.flagged_frames.clone(),
+                excluded_frames: job.excluded_frames.clone(),
+            })
+            .collect()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+}
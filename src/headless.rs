@@ -0,0 +1,194 @@
+use crate::batch::{self, BatchJob, JobOverrides};
+use crate::cancel::CancelToken;
+use crate::gaps::GapFilling;
+use crate::hooks::Hooks;
+use crate::joblog::JobLog;
+use crate::runner::{self, Delivery, JobSettings, Outcome, DEFAULT_NAME_TEMPLATE};
+use crate::tasks::TaskRuntime;
+use crate::video::{Codec, CodecOptions, ProcessEnvironment};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub const FLAG: &str = "--headless";
+
+/// Options of a run without the UI, e.g.
+/// `tree-migration-app --headless config1.toml config2.toml --video --codec h264`, or
+/// `tree-migration-app --headless --batch nightly.batch.toml --video`.
+struct HeadlessOptions {
+    jobs: Vec<BatchJob>,
+    is_forest_green_enabled: bool,
+    is_video_enabled: bool,
+    video_codec: Codec,
+    ffmpeg_path: PathBuf,
+    frame_rate: u32,
+    is_profiling_enabled: bool,
+}
+
+impl HeadlessOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            jobs: Vec::new(),
+            is_forest_green_enabled: false,
+            is_video_enabled: false,
+            video_codec: Codec::H264,
+            ffmpeg_path: PathBuf::from("ffmpeg"),
+            frame_rate: 4,
+            is_profiling_enabled: false,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                FLAG => {}
+                "--forest-green" => options.is_forest_green_enabled = true,
+                "--video" => options.is_video_enabled = true,
+                "--profile" => options.is_profiling_enabled = true,
+                "--codec" => {
+                    let name = args.next().unwrap_or_default();
+                    options.video_codec = Codec::from_name(&name)
+                        .filter(|codec| codec != &Codec::None)
+                        .ok_or(format!("Unknown codec {}", name))?
+                }
+                "--batch" => {
+                    let path = args.next().map(PathBuf::from).ok_or("Missing batch file")?;
+                    let jobs = batch::load(&path)
+                        .map_err(|e| format!("Invalid batch file {}: {}", path.display(), e))?;
+                    options.jobs.extend(jobs);
+                }
+                "--ffmpeg" => {
+                    options.ffmpeg_path = args
+                        .next()
+                        .map(PathBuf::from)
+                        .ok_or("Missing ffmpeg path")?
+                }
+                "--frame-rate" => {
+                    options.frame_rate = args
+                        .next()
+                        .and_then(|rate| rate.parse().ok())
+                        .filter(|rate| *rate > 0)
+                        .ok_or("Invalid frame rate")?
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => options.jobs.push(BatchJob {
+                    config: PathBuf::from(arg),
+                    overrides: JobOverrides::default(),
+                    output_path: None,
+                }),
+            }
+        }
+        if options.jobs.is_empty() {
+            return Err("No config files given".to_owned());
+        }
+        Ok(options)
+    }
+
+    fn job_settings(&self) -> JobSettings {
+        JobSettings {
+            is_forest_green_enabled: self.is_forest_green_enabled,
+            is_source_quarantine_enabled: false,
+            is_date_overlay_enabled: false,
+            is_title_slate_enabled: false,
+            is_video_failure_fatal: true,
+            gap_filling: GapFilling::Skip,
+            is_xmp_enabled: false,
+            sequence_export_path: None,
+            ffmpeg_path: self.is_video_enabled.then(|| self.ffmpeg_path.clone()),
+            is_review_proxy: false,
+            excluded_frames: Default::default(),
+            capture_order_clock: None,
+            config_path: PathBuf::new(),
+            hooks: Hooks::default(),
+            video_codec: self.video_codec,
+            codec_options: CodecOptions::default(),
+            ffmpeg_environment: ProcessEnvironment::default(),
+            video_output_path: None,
+            video_name_template: DEFAULT_NAME_TEMPLATE.to_owned(),
+            date_format: Default::default(),
+            video_name_collision: 0,
+            frame_rate: self.frame_rate,
+            target_duration: None,
+            temp_path: None,
+            preview_format: None,
+            is_manifest_enabled: false,
+            is_profiling_enabled: self.is_profiling_enabled,
+            archive_output_path: None,
+            deposit: None,
+        }
+    }
+}
+
+/// Runs the given configs one after another without the UI, printing the status of each.
+/// Returns the process exit code, which is non-zero if any job failed.
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let options = match HeadlessOptions::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let tasks = TaskRuntime::default();
+    let mut failures = 0;
+    for job in &options.jobs {
+        let path = &job.config;
+        let mut settings = options.job_settings();
+        settings.config_path = path.clone();
+        settings.video_codec = job.overrides.video_codec.unwrap_or(settings.video_codec);
+        settings.frame_rate = job.overrides.frame_rate.unwrap_or(settings.frame_rate);
+        let image_config = match tree_migration::Config::from(path) {
+            Ok(image_config) => tree_migration::Config {
+                output_path: job
+                    .output_path
+                    .clone()
+                    .unwrap_or(image_config.output_path.clone()),
+                ..image_config
+            },
+            Err(e) => {
+                println!("{}: Invalid config: {}", path.display(), e);
+                failures += 1;
+                continue;
+            }
+        };
+        println!("{}: Processing", path.display());
+        let (outcome, timings) = tasks.block_on(runner::run_job(
+            &image_config,
+            &[],
+            &settings,
+            &CancelToken::default(),
+            &JobLog::default(),
+            Arc::new(|_| {}),
+        ));
+        match outcome {
+            Outcome::Done(Delivery { shortfalls, .. }) => {
+                println!(
+                    "{}: {} ({})",
+                    path.display(),
+                    shortfalls.label(),
+                    runner::describe_timings(&timings)
+                );
+                for error in shortfalls
+                    .video_error
+                    .iter()
+                    .chain(&shortfalls.deposit_error)
+                    .chain(&shortfalls.warnings)
+                {
+                    println!("{}: {}", path.display(), error);
+                }
+            }
+            Outcome::Cancelled => {
+                println!("{}: Cancelled", path.display());
+                failures += 1;
+            }
+            Outcome::Failed(e) => {
+                println!("{}: Error: {}", path.display(), e);
+                failures += 1;
+            }
+            Outcome::StageFailed(e) => {
+                println!("{}: Error: {}", path.display(), e);
+                failures += 1;
+            }
+            Outcome::ProxyReady(proxy) => {
+                println!("{}: Proxy ready at {}", path.display(), proxy.display());
+            }
+        }
+    }
+    (failures > 0) as i32
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// Time an upload target has to answer the startup check.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the version reported by `ffmpeg -version`.
+pub fn ffmpeg_version(ffmpeg_path: &Path) -> Result<String, String> {
+    let output = Command::new(ffmpeg_path)
+        .arg("-version")
+        .output()
+        .map_err(|e| format!("Cannot run ffmpeg at {}: {}", ffmpeg_path.display(), e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("ffmpeg version "))
+        .and_then(|line| line.split_whitespace().next())
+        .map(str::to_owned)
+        .ok_or(format!("{} is not an ffmpeg binary", ffmpeg_path.display()))
+}
+
+/// Checks that a file can be created in `folder`, which also catches unmounted network shares.
+pub fn check_writable(folder: &Path) -> Result<(), String> {
+    if !folder.is_dir() {
+        return Err(format!("{} is not reachable", folder.display()));
+    }
+    let probe = folder.join(".tree-migration-write-test");
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("Cannot write to {}: {}", folder.display(), e))
+}
+
+/// Checks that the server at `url` answers a HEAD request. Any answer counts, as upload
+/// targets often refuse HEAD or unauthenticated requests.
+pub fn check_reachable(url: &str) -> Result<(), String> {
+    match ureq::head(url.trim()).timeout(TIMEOUT).call() {
+        Ok(_) | Err(ureq::Error::Status(..)) => Ok(()),
+        Err(e) => Err(format!("{} is not reachable: {}", url.trim(), e)),
+    }
+}
+
+/// Runs the startup checks and returns the ffmpeg version, if any, and the problems found.
+pub fn check(
+    ffmpeg_path: Option<&Path>,
+    folders: &[(&str, PathBuf)],
+    urls: &[(&str, String)],
+) -> (Option<String>, Vec<String>) {
+    let mut problems = Vec::new();
+    let version = match ffmpeg_path {
+        Some(ffmpeg_path) => match ffmpeg_version(ffmpeg_path) {
+            Ok(version) => Some(version),
+            Err(e) => {
+                problems.push(e);
+                None
+            }
+        },
+        None => None,
+    };
+    for (name, folder) in folders {
+        if let Err(e) = check_writable(folder) {
+            problems.push(format!("{}: {}", name, e));
+        }
+    }
+    for (name, url) in urls {
+        if let Err(e) = check_reachable(url) {
+            problems.push(format!("{}: {}", name, e));
+        }
+    }
+    (version, problems)
+}
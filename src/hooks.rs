@@ -0,0 +1,88 @@
+use crate::cancel::CancelToken;
+use crate::children::{self, RunError};
+use crate::joblog::JobLog;
+use crate::video::ProcessEnvironment;
+use std::path::Path;
+use std::process::Command;
+
+/// Environment variables a hook command gets with the paths of its job.
+pub const VARIABLES: [(&str, &str); 4] = [
+    ("TREE_MIGRATION_CONFIG", "config file"),
+    ("TREE_MIGRATION_OUTPUT", "output folder"),
+    ("TREE_MIGRATION_VIDEO", "video, empty without one"),
+    ("TREE_MIGRATION_STATUS", "\"success\" or \"failure\""),
+];
+
+/// Shell commands run after every job of a batch, e.g. to upload its outputs.
+/// They are saved in plain text with the other settings, so they should not contain secrets.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub on_success: String,
+    pub on_failure: String,
+}
+
+/// Paths of a finished job handed to its hook.
+pub struct HookJob<'a> {
+    pub config_path: &'a Path,
+    pub output_path: &'a Path,
+    pub video_path: Option<&'a Path>,
+    pub is_success: bool,
+}
+
+impl Hooks {
+    /// Runs the command for the outcome of `job` through the shell, if one is set, and adds its
+    /// output to `log`. The command runs in `environment`, the one of ffmpeg, and is killed if
+    /// the job is cancelled.
+    pub fn run(
+        &self,
+        job: &HookJob,
+        environment: &ProcessEnvironment,
+        cancel: &CancelToken,
+        log: &JobLog,
+    ) -> Result<(), String> {
+        let (name, script) = if job.is_success {
+            ("post-success", &self.on_success)
+        } else {
+            ("post-failure", &self.on_failure)
+        };
+        if script.trim().is_empty() {
+            return Ok(());
+        }
+        let mut command = if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        environment.apply(&mut command);
+        command
+            .arg(script)
+            .env("TREE_MIGRATION_CONFIG", job.config_path)
+            .env("TREE_MIGRATION_OUTPUT", job.output_path)
+            .env(
+                "TREE_MIGRATION_VIDEO",
+                job.video_path.unwrap_or(Path::new("")),
+            )
+            .env(
+                "TREE_MIGRATION_STATUS",
+                if job.is_success { "success" } else { "failure" },
+            );
+        log.line(format!("Running {} hook", name));
+        let finished = children::run(&mut command, cancel, None).map_err(|e| match e {
+            RunError::Spawn(e) => format!("Cannot run the {} hook: {}", name, e),
+            RunError::Wait(e) => format!("Cannot wait for the {} hook: {}", name, e),
+            RunError::Cancelled => format!("The {} hook was cancelled", name),
+        })?;
+        log.output(name, &finished.stdout);
+        log.output(name, &finished.stderr);
+        if finished.status.success() {
+            Ok(())
+        } else {
+            Err(format!("The {} hook failed with {}", name, finished.status))
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::utils;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct ImportSummary {
+    pub configs: Vec<PathBuf>,
+    pub imported: usize,
+    pub skipped: Vec<PathBuf>,
+}
+
+struct Capture {
+    camera: String,
+    time: NaiveDateTime,
+}
+
+fn capture(path: &Path) -> Option<Capture> {
+    let exif = utils::read_exif(path)?;
+    let camera = String::from_utf8_lossy(&utils::exif_ascii(&exif, exif::Tag::Model)?)
+        .trim()
+        .to_owned();
+    let time = utils::exif_time(&exif)?;
+    Some(Capture { camera, time })
+}
+
+/// Makes a camera or location name safe to use as a folder name.
+fn folder_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn images_recursive(dir: &Path, images: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            images_recursive(&path, images)?;
+        } else if utils::is_image(&path) {
+            images.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Copies the images of a camera card into `<archive_path>/<location>/<camera>/`, named
+/// `<camera>_<YYYYMMDD>_<HHMMSS>.<ext>` after their EXIF capture time, and writes a config
+/// covering the imported date range for every camera found on the card.
+/// Images without camera model or capture time are skipped.
+pub fn import(
+    card_path: &Path,
+    archive_path: &Path,
+    location: &str,
+) -> std::io::Result<ImportSummary> {
+    let mut images = Vec::new();
+    images_recursive(card_path, &mut images)?;
+    images.sort();
+
+    let location_path = archive_path.join(folder_name(location));
+    let mut ranges: HashMap<String, (NaiveDate, NaiveDate)> = HashMap::new();
+    let mut summary = ImportSummary {
+        configs: Vec::new(),
+        imported: 0,
+        skipped: Vec::new(),
+    };
+    for image in images {
+        let Some(capture) = capture(&image) else {
+            summary.skipped.push(image);
+            continue;
+        };
+        let camera = folder_name(&capture.camera);
+        let camera_path = location_path.join(&camera);
+        std::fs::create_dir_all(&camera_path)?;
+        let extension = image
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mut target = camera_path.join(format!(
+            "{}_{}.{}",
+            camera,
+            capture.time.format("%Y%m%d_%H%M%S"),
+            extension
+        ));
+        let mut index = 1;
+        while target.exists() {
+            target = camera_path.join(format!(
+                "{}_{}_{}.{}",
+                camera,
+                capture.time.format("%Y%m%d_%H%M%S"),
+                index,
+                extension
+            ));
+            index += 1;
+        }
+        std::fs::copy(&image, &target)?;
+        summary.imported += 1;
+
+        let date = capture.time.date();
+        ranges
+            .entry(camera)
+            .and_modify(|(start, end)| {
+                *start = date.min(*start);
+                *end = date.max(*end);
+            })
+            .or_insert((date, date));
+    }
+
+    for (camera, (start_date, end_date)) in ranges {
+        let config = tree_migration::Config {
+            input_path: location_path.join(&camera),
+            output_path: location_path.join(format!("{}-output", camera)),
+            location: location.to_owned(),
+            camera: camera.clone(),
+            start_date,
+            end_date,
+            ..Default::default()
+        };
+        let config_path = location_path.join(format!("{}.toml", camera));
+        confy::store_path(&config_path, config).map_err(std::io::Error::other)?;
+        summary.configs.push(config_path);
+    }
+    Ok(summary)
+}
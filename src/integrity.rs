@@ -0,0 +1,58 @@
+use crate::cleanup;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Folder inside the input folder that corrupt source images are moved to.
+pub const QUARANTINE_NAME: &str = "quarantine";
+const LOG_NAME: &str = "quarantine.log";
+
+/// Checks that an image is not empty, has a readable header and, for JPEG, is not truncated.
+/// This is much faster than decoding the image.
+pub fn check(path: &Path) -> Result<(), String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    if file.metadata().map_err(|e| e.to_string())?.len() == 0 {
+        return Err("Empty file".to_owned());
+    }
+    image::image_dimensions(path).map_err(|e| e.to_string())?;
+    let is_jpeg = path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg")
+    });
+    if is_jpeg {
+        let mut end = [0; 2];
+        file.seek(SeekFrom::End(-2))
+            .and_then(|_| file.read_exact(&mut end))
+            .map_err(|e| e.to_string())?;
+        if end != [0xFF, 0xD9] {
+            return Err("Truncated JPEG".to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the images that fail `check` with the reason.
+pub fn scan(images: &[PathBuf]) -> Vec<(PathBuf, String)> {
+    images
+        .iter()
+        .filter_map(|image| check(image).err().map(|e| (image.clone(), e)))
+        .collect()
+}
+
+/// Moves corrupt source images into the quarantine folder of `input_path` and appends the
+/// reasons to its log, so that they do not break later runs of the site.
+pub fn quarantine(input_path: &Path, problems: &[(PathBuf, String)]) -> std::io::Result<()> {
+    let quarantine_path = input_path.join(QUARANTINE_NAME);
+    let images = problems
+        .iter()
+        .map(|(image, _)| image.clone())
+        .collect::<Vec<_>>();
+    cleanup::quarantine(&images, &quarantine_path)?;
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(quarantine_path.join(LOG_NAME))?;
+    let now = chrono::Local::now().to_rfc3339();
+    for (image, reason) in problems {
+        writeln!(log, "{} {}: {}", now, image.display(), reason)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use std::sync::{Arc, Mutex};
+
+/// Log of a job run, shared between the job and the UI.
+#[derive(Clone, Default)]
+pub struct JobLog(Arc<Mutex<Vec<String>>>);
+
+impl JobLog {
+    /// Adds a line to the log and to the log of the app.
+    pub fn line(&self, line: String) {
+        log::info!("{}", line);
+        if let Ok(mut lines) = self.0.lock() {
+            lines.push(line);
+        }
+    }
+
+    /// Adds the output of a process, prefixing every line with `source`.
+    pub fn output(&self, source: &str, output: &str) {
+        log::debug!("{}: {}", source, output);
+        if let Ok(mut lines) = self.0.lock() {
+            lines.extend(
+                output
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| format!("{}: {}", source, line)),
+            );
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0.lock().map_or(Vec::new(), |lines| lines.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().map_or(true, |lines| lines.is_empty())
+    }
+}
@@ -0,0 +1,61 @@
+//! Keeps the memory of jobs with very large frames, e.g. stitched panoramas, in check.
+//! The migration decodes every frame whole, which this app does not control, so frames are
+//! not streamed. Instead, only one job with large frames runs at a time, and the previews
+//! and thumbnails of the app decode JPEGs at a reduced scale.
+
+use image::codecs::jpeg::JpegDecoder;
+use image::DynamicImage;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Frames with more pixels than this take hundreds of megabytes each to decode.
+pub const LARGE_FRAME_PIXELS: u64 = 40_000_000;
+
+/// Set while a job with large frames holds the slot.
+static IS_SLOT_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether `image` has more than `LARGE_FRAME_PIXELS`, reading only its header.
+pub fn is_large(image: &Path) -> bool {
+    image::image_dimensions(image)
+        .is_ok_and(|(width, height)| width as u64 * height as u64 > LARGE_FRAME_PIXELS)
+}
+
+/// Slot of the job with large frames that is running, freed when dropped.
+pub struct Slot(());
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        IS_SLOT_TAKEN.store(false, Ordering::Release);
+    }
+}
+
+/// Returns whether another job with large frames is running.
+pub fn is_slot_taken() -> bool {
+    IS_SLOT_TAKEN.load(Ordering::Acquire)
+}
+
+/// Takes the slot unless another job with large frames is running.
+pub fn try_acquire() -> Option<Slot> {
+    IS_SLOT_TAKEN
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_ok()
+        .then_some(Slot(()))
+}
+
+/// Loads `path` scaled down to fit into `size` × `size`. JPEGs are decoded at a reduced scale
+/// right away, so that large frames are never held in memory at full size.
+pub fn open_scaled(path: &Path, size: u32) -> image::ImageResult<DynamicImage> {
+    let is_jpeg = path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg")
+    });
+    let image = if is_jpeg {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut decoder = JpegDecoder::new(file)?;
+        let requested = size.min(u16::MAX as u32) as u16;
+        decoder.scale(requested, requested)?;
+        DynamicImage::from_decoder(decoder)?
+    } else {
+        image::open(path)?
+    };
+    Ok(image.thumbnail(size, size))
+}
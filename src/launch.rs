@@ -0,0 +1,58 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Opens `target` in the application at `application_path`.
+/// On macOS applications are bundles and are started through `open -a`.
+pub fn open_with(application_path: &Path, target: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-a").arg(application_path);
+        command
+    } else {
+        Command::new(application_path)
+    };
+    command.arg(target).spawn().map(|_| ())
+}
+
+/// Opens `target` in the default application for its type.
+pub fn open(target: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(target).spawn().map(|_| ())
+}
+
+/// Shows `target` in the platform file manager. Folders are opened, files are selected in
+/// their folder where the file manager supports it.
+pub fn show_in_file_manager(target: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        if target.is_file() {
+            command.arg("-R");
+        }
+        command.arg(target);
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        if target.is_file() {
+            command.arg(format!("/select,{}", target.display()));
+        } else {
+            command.arg(target);
+        }
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        match target.parent().filter(|_| target.is_file()) {
+            Some(folder) => command.arg(folder),
+            None => command.arg(target),
+        };
+        command
+    };
+    command.spawn().map(|_| ())
+}
@@ -0,0 +1,39 @@
+use chrono::Datelike;
+use std::path::{Component, PathBuf};
+
+pub const DEFAULT_TEMPLATE: &str = "{year}/{location}/{camera}";
+
+/// Expands an output layout template such as `{year}/{location}/{camera}` for a job.
+/// Supported placeholders are `{year}`, `{month}`, `{location}` and `{camera}`.
+pub fn expand(template: &str, image_config: &tree_migration::Config) -> Result<PathBuf, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded += &rest[..start];
+        let Some(end) = rest[start..].find('}') else {
+            return Err(format!("Unclosed placeholder in {}", template));
+        };
+        expanded += match &rest[start + 1..start + end] {
+            "year" => image_config.start_date.year().to_string(),
+            "month" => format!("{:02}", image_config.start_date.month()),
+            "location" => image_config.location.clone(),
+            "camera" => image_config.camera.clone(),
+            placeholder => return Err(format!("Unknown placeholder {{{}}}", placeholder)),
+        }
+        .as_str();
+        rest = &rest[start + end + 1..];
+    }
+    expanded += rest;
+
+    let path = PathBuf::from(expanded);
+    if path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "Layout {} must be a relative path without \"..\"",
+            path.display()
+        ));
+    }
+    Ok(path)
+}
@@ -0,0 +1,68 @@
+use crate::batch::JobOverrides;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Settings applied to every job of a location when its config is added.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocationDefaults {
+    pub overrides: JobOverrides,
+    /// Delivery folder the images are written to instead of the one in the config.
+    pub output_path: Option<PathBuf>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DefaultsEntry {
+    codec: Option<String>,
+    frame_rate: Option<u32>,
+    clock: Option<String>,
+    output: Option<PathBuf>,
+}
+
+/// Loads a `location,latitude,longitude` mapping file. Lines that cannot be parsed,
+/// such as a header, are skipped.
+pub fn load(path: &Path) -> Result<HashMap<String, [f64; 2]>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut locations = HashMap::new();
+    for line in contents.lines() {
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        if let [location, latitude, longitude] = fields[..] {
+            if let (Ok(latitude), Ok(longitude)) = (latitude.parse(), longitude.parse()) {
+                locations.insert(location.to_owned(), [latitude, longitude]);
+            }
+        }
+    }
+    Ok(locations)
+}
+
+/// Loads a defaults file with a table per location, e.g.
+/// ```toml
+/// [Ridge-North]
+/// codec = "prores"
+/// frame_rate = 8
+/// clock = "+01:00"
+/// output = "/delivery/ridge-north"
+/// ```
+/// Relative output folders are resolved against the folder of the file.
+pub fn load_defaults(path: &Path) -> Result<HashMap<String, LocationDefaults>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let entries: HashMap<String, DefaultsEntry> =
+        toml::from_str(&text).map_err(|e| e.to_string())?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    entries
+        .into_iter()
+        .map(|(location, entry)| {
+            let overrides = JobOverrides::parse(
+                entry.codec.as_deref(),
+                entry.frame_rate,
+                entry.clock.as_deref(),
+            )
+            .map_err(|e| format!("{}: {}", location, e))?;
+            let defaults = LocationDefaults {
+                overrides,
+                output_path: entry.output.map(|output| dir.join(output)),
+            };
+            Ok((location, defaults))
+        })
+        .collect()
+}
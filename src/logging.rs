@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Folder inside the app data folder that the log files are written to.
+const LOGS_NAME: &str = "logs";
+/// Number of log files of past runs that are kept.
+const KEPT_LOGS: usize = 10;
+
+/// Most detailed messages written to the log.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "Errors",
+            LogLevel::Warn => "Warnings",
+            LogLevel::Info => "Job events",
+            LogLevel::Debug => "Debug",
+        }
+    }
+
+    fn filter(&self) -> log::LevelFilter {
+        match self {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+        }
+    }
+}
+
+/// Writes log records both to stderr and to the log file of the run.
+struct Tee(std::fs::File);
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let _ = std::io::stderr().write_all(buf);
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Creates the log file of this run in the logs folder of `data_dir` and deletes the oldest
+/// ones beyond `KEPT_LOGS`.
+fn create_log_file(data_dir: &Path) -> std::io::Result<(PathBuf, std::fs::File)> {
+    let dir = data_dir.join(LOGS_NAME);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{}.log",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    let mut logs = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|log| log.extension().is_some_and(|extension| extension == "log"))
+        .collect::<Vec<_>>();
+    logs.sort();
+    for log in logs.iter().rev().skip(KEPT_LOGS) {
+        let _ = std::fs::remove_file(log);
+    }
+    Ok((path, file))
+}
+
+/// Routes the log records of the app at `level` and warnings of its libraries to stderr and,
+/// with a `data_dir`, to a new log file there. `RUST_LOG` overrides the filters. Returns the
+/// path of the log file.
+pub fn init(data_dir: Option<&Path>, level: LogLevel) -> Option<PathBuf> {
+    let mut builder = env_logger::Builder::new();
+    builder
+        .filter_level(log::LevelFilter::Warn)
+        .filter_module(env!("CARGO_CRATE_NAME"), log::LevelFilter::Debug)
+        .parse_default_env()
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "{} {:<5} {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                record.level(),
+                record.args()
+            )
+        });
+    let log_file = data_dir.map(create_log_file);
+    let path = match log_file {
+        Some(Ok((path, file))) => {
+            builder.target(env_logger::Target::Pipe(Box::new(Tee(file))));
+            Some(path)
+        }
+        Some(Err(e)) => {
+            eprintln!("Error creating log file {}", e);
+            None
+        }
+        None => None,
+    };
+    if builder.try_init().is_ok() {
+        set_level(level);
+    }
+    path
+}
+
+/// Changes the most detailed messages logged from now on, unless `RUST_LOG` sets the filters,
+/// which then stay as given.
+pub fn set_level(level: LogLevel) {
+    if std::env::var_os("RUST_LOG").is_none() {
+        log::set_max_level(level.filter());
+    }
+}
@@ -2,11 +2,92 @@ extern crate images_to_video;
 extern crate tree_migration;
 
 mod app;
+mod archive;
+mod batch;
+mod cache;
+mod cancel;
+mod children;
+mod cleanup;
+mod dashboard;
+mod dates;
+mod deposit;
+mod dropping;
+mod editor;
+mod export;
+mod ffmpeg;
+mod gaps;
+mod handoff;
+mod headless;
+mod health;
+mod hooks;
+mod import;
+mod integrity;
+mod joblog;
+mod largeframe;
+mod launch;
+mod layout;
+mod locations;
+mod logging;
+mod notification;
+mod overlay;
+mod pipeline;
+mod preflight;
+mod queue;
+mod report;
+mod runner;
+mod schedule;
+mod script;
+mod storage;
+mod tasks;
+mod throughput;
+mod transcode;
+mod usage;
+mod utils;
+mod verify;
+mod video;
+mod webhook;
+mod xmp;
 
 use app::MigrationApp;
+use std::path::PathBuf;
+
+pub const APP_NAME: &str = "Tree Migration";
+
+/// Options given on the command line.
+#[derive(Default)]
+pub struct LaunchOptions {
+    /// Skips restoring persisted settings and the queue so that corrupted state can be reset.
+    pub is_safe_mode: bool,
+    pub data_dir: Option<PathBuf>,
+    pub temp_dir: Option<PathBuf>,
+    /// Batch file whose jobs are added on start.
+    pub batch_path: Option<PathBuf>,
+}
+
+impl LaunchOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--safe-mode" => options.is_safe_mode = true,
+                "--data-dir" => options.data_dir = args.next().map(PathBuf::from),
+                "--temp-dir" => options.temp_dir = args.next().map(PathBuf::from),
+                "--batch" => options.batch_path = args.next().map(PathBuf::from),
+                _ => {}
+            }
+        }
+        options
+    }
+}
 
 fn main() -> eframe::Result<()> {
-    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    if std::env::args().any(|arg| arg == headless::FLAG) {
+        // Job events go to stderr, `RUST_LOG=debug` shows more.
+        logging::init(None, logging::LogLevel::Info);
+        std::process::exit(headless::run(std::env::args().skip(1)));
+    }
+
+    let options = LaunchOptions::parse(std::env::args().skip(1));
 
     let native_options = eframe::NativeOptions {
         initial_window_size: Some([700.0, 500.0].into()),
@@ -14,8 +95,8 @@ fn main() -> eframe::Result<()> {
         ..Default::default()
     };
     eframe::run_native(
-        "Tree Migration",
+        APP_NAME,
         native_options,
-        Box::new(|cc| Box::new(MigrationApp::new(cc))),
+        Box::new(move |cc| Box::new(MigrationApp::new(cc, options))),
     )
 }
@@ -0,0 +1,46 @@
+use notify_rust::Notification;
+
+/// Sound of the notification, from the freedesktop sound theme on Linux.
+#[cfg(target_os = "linux")]
+const SOUND_NAME: &str = "complete";
+#[cfg(not(target_os = "linux"))]
+const SOUND_NAME: &str = "Default";
+
+/// Shows a desktop notification with the number of jobs of a finished batch that succeeded
+/// and failed.
+pub fn batch_finished(done: usize, failed: usize, is_sound_enabled: bool) -> Result<(), String> {
+    let mut notification = Notification::new();
+    notification
+        .appname(crate::APP_NAME)
+        .summary(if failed == 0 {
+            "Batch finished"
+        } else {
+            "Batch finished with errors"
+        })
+        .body(&format!("{} done, {} failed", done, failed));
+    if is_sound_enabled {
+        notification.sound_name(SOUND_NAME);
+    }
+    notification.show().map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Shows a desktop notification that the batch stopped after `failures` jobs failed in a row,
+/// cancelling `cancelled` queued jobs.
+pub fn batch_stopped(
+    failures: u32,
+    cancelled: usize,
+    is_sound_enabled: bool,
+) -> Result<(), String> {
+    let mut notification = Notification::new();
+    notification
+        .appname(crate::APP_NAME)
+        .summary("Batch stopped")
+        .body(&format!(
+            "{} jobs failed in a row, {} queued jobs cancelled",
+            failures, cancelled
+        ));
+    if is_sound_enabled {
+        notification.sound_name(SOUND_NAME);
+    }
+    notification.show().map(|_| ()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,139 @@
+use crate::dates::{DateFormat, DateRange};
+use crate::gaps::SLATE_BACKGROUND;
+use crate::utils;
+use chrono::NaiveDate;
+use image::{Rgb, RgbImage};
+use std::path::{Path, PathBuf};
+
+/// Darkening of the box behind the date, from 0 (none) to 1 (black).
+const BOX_SHADE: f32 = 0.6;
+/// How long the title slate is shown at the start of the video, in seconds.
+pub const TITLE_SECONDS: f64 = 2.0;
+
+/// Returns the capture date of every frame from its file name or EXIF data. Frames without
+/// one are spread evenly over `date_range` by their position.
+pub fn frame_dates(frames: &[PathBuf], (start_date, end_date): DateRange) -> Vec<NaiveDate> {
+    let days = (end_date - start_date).num_days().max(0);
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            utils::image_date(frame).unwrap_or_else(|| {
+                let offset = days * i as i64 / frames.len().saturating_sub(1).max(1) as i64;
+                start_date + chrono::Duration::days(offset)
+            })
+        })
+        .collect()
+}
+
+/// Draws `text` in white on a dark box into the bottom left corner of `image`.
+fn stamp(image: &mut RgbImage, text: &str, font: &rusttype::Font) {
+    let (width, height) = image.dimensions();
+    let scale = rusttype::Scale::uniform(height as f32 / 20.0);
+    let v_metrics = font.v_metrics(scale);
+    let margin = (height / 40) as i32;
+    let text_height = (v_metrics.ascent - v_metrics.descent).ceil() as i32;
+    let glyphs = font
+        .layout(text, scale, rusttype::point(0.0, v_metrics.ascent))
+        .collect::<Vec<_>>();
+    let text_width = glyphs
+        .iter()
+        .filter_map(|glyph| glyph.pixel_bounding_box())
+        .map(|bounding_box| bounding_box.max.x)
+        .max()
+        .unwrap_or(0);
+    let offset_x = 2 * margin;
+    let offset_y = height as i32 - 2 * margin - text_height;
+
+    let box_right = (offset_x + text_width + margin).clamp(0, width as i32) as u32;
+    let box_top = (offset_y - margin).clamp(0, height as i32) as u32;
+    let box_bottom = (offset_y + text_height + margin).clamp(0, height as i32) as u32;
+    for y in box_top..box_bottom {
+        for x in (margin as u32).min(box_right)..box_right {
+            let pixel = image.get_pixel_mut(x, y);
+            pixel.0 = pixel
+                .0
+                .map(|value| (value as f32 * (1.0 - BOX_SHADE)) as u8);
+        }
+    }
+
+    for glyph in glyphs {
+        let Some(bounding_box) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|x, y, coverage| {
+            let x = x as i32 + bounding_box.min.x + offset_x;
+            let y = y as i32 + bounding_box.min.y + offset_y;
+            if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                let pixel = image.get_pixel_mut(x as u32, y as u32);
+                *pixel = Rgb(pixel
+                    .0
+                    .map(|value| (value as f32 + coverage * (255.0 - value as f32)) as u8));
+            }
+        });
+    }
+}
+
+/// Writes a copy of every frame stamped with its date into `dir` and returns the copies in the
+/// same order.
+pub fn stamp_frames(
+    frames: &[PathBuf],
+    dates: &[NaiveDate],
+    date_format: &DateFormat,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    let font = utils::font().ok_or("The overlay font is not available".to_owned())?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    frames
+        .iter()
+        .zip(dates)
+        .enumerate()
+        .map(|(i, (frame, date))| {
+            let mut image = image::open(frame).map_err(|e| e.to_string())?.to_rgb8();
+            stamp(&mut image, &date_format.format(*date), &font);
+            let stamped = dir.join(format!("{:06}.png", i));
+            image.save(&stamped).map_err(|e| e.to_string())?;
+            Ok(stamped)
+        })
+        .collect()
+}
+
+/// Renders a title slate with `lines` centered one below the other, with the dimensions of
+/// `frame`, into `output`.
+pub fn title_slate(frame: &Path, lines: &[String], output: &Path) -> Result<(), String> {
+    let font = utils::font().ok_or("The overlay font is not available".to_owned())?;
+    let (width, height) = image::image_dimensions(frame).map_err(|e| e.to_string())?;
+    let mut slate = RgbImage::from_pixel(width, height, Rgb([SLATE_BACKGROUND; 3]));
+    let scale = rusttype::Scale::uniform(height as f32 / 16.0);
+    let v_metrics = font.v_metrics(scale);
+    let line_height = (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap).ceil() as i32;
+    let top = (height as i32 - line_height * lines.len() as i32) / 2;
+    for (i, line) in lines.iter().enumerate() {
+        let glyphs = font
+            .layout(line, scale, rusttype::point(0.0, v_metrics.ascent))
+            .collect::<Vec<_>>();
+        let text_width = glyphs
+            .iter()
+            .filter_map(|glyph| glyph.pixel_bounding_box())
+            .map(|bounding_box| bounding_box.max.x)
+            .max()
+            .unwrap_or(0);
+        let offset_x = (width as i32 - text_width) / 2;
+        let offset_y = top + line_height * i as i32;
+        for glyph in glyphs {
+            let Some(bounding_box) = glyph.pixel_bounding_box() else {
+                continue;
+            };
+            glyph.draw(|x, y, coverage| {
+                let x = x as i32 + bounding_box.min.x + offset_x;
+                let y = y as i32 + bounding_box.min.y + offset_y;
+                if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                    let value =
+                        SLATE_BACKGROUND as f32 + coverage * (255 - SLATE_BACKGROUND) as f32;
+                    slate.put_pixel(x as u32, y as u32, Rgb([value as u8; 3]));
+                }
+            });
+        }
+    }
+    slate.save(output).map_err(|e| e.to_string())
+}
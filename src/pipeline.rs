@@ -0,0 +1,688 @@
+use crate::cancel::CancelToken;
+use crate::dates::DateRange;
+use crate::joblog::JobLog;
+use crate::largeframe;
+use crate::runner::{self, JobSettings, Outcome, Shortfalls, StageTimings};
+use crate::utils;
+use crate::video::AdaptiveEncode;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// State of the batch as a whole.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QueueState {
+    Init,
+    InvalidConfigs,
+    ValidConfigs,
+    /// Valid and waiting for the time the batch was scheduled to start at.
+    Scheduled,
+    Processing,
+    ProcessingDone,
+    ProcessingErrors,
+    /// Processing with no new jobs started and the running encodes suspended.
+    Paused,
+}
+
+impl QueueState {
+    /// Whether a batch is running, including while it is paused.
+    pub fn is_processing(&self) -> bool {
+        *self == QueueState::Processing || *self == QueueState::Paused
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobStatus {
+    InvalidConfig,
+    ValidConfig,
+    /// Valid, but another job would write to the same outputs.
+    Conflict,
+    Processing,
+    ProcessingDone,
+    /// The images were migrated but the video could not be encoded.
+    VideoFailed,
+    /// Everything was delivered except the deposit of the archive.
+    UploadPending,
+    /// Everything was delivered but some stages logged errors.
+    DoneWithWarnings,
+    /// Only the proxy was rendered and waits to be approved or rejected.
+    AwaitingReview,
+    /// The proxy was rejected, so the job was not rendered in full.
+    Rejected,
+    ProcessingError,
+    Cancelled,
+    Queued,
+    Paused,
+    Unkown,
+}
+
+impl JobStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::InvalidConfig => "Invalid Config",
+            JobStatus::ValidConfig => "Valid Config",
+            JobStatus::Conflict => "Conflict",
+            JobStatus::Processing => "Processing",
+            JobStatus::ProcessingDone => "Done",
+            JobStatus::VideoFailed => "Images done, video failed",
+            JobStatus::UploadPending => "Done, upload pending",
+            JobStatus::DoneWithWarnings => "Done with warnings",
+            JobStatus::AwaitingReview => "Awaiting review",
+            JobStatus::Rejected => "Rejected",
+            JobStatus::ProcessingError => "Error",
+            JobStatus::Cancelled => "Cancelled",
+            JobStatus::Queued => "Queued",
+            JobStatus::Paused => "Paused",
+            JobStatus::Unkown => "Unkown",
+        }
+    }
+
+    /// Returns whether the job finished, possibly without delivering everything.
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::ProcessingDone
+                | JobStatus::VideoFailed
+                | JobStatus::UploadPending
+                | JobStatus::DoneWithWarnings
+        )
+    }
+
+    /// Returns whether the job still runs or waits to run.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Processing | JobStatus::Queued | JobStatus::Paused
+        )
+    }
+}
+
+/// Decision on the proxy of a job rendered in review mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Review {
+    /// No proxy was rendered, the job renders in full.
+    #[default]
+    None,
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl Review {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Review::None => "none",
+            Review::Pending => "pending",
+            Review::Approved => "approved",
+            Review::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse(review: &str) -> Self {
+        match review {
+            "pending" => Review::Pending,
+            "approved" => Review::Approved,
+            "rejected" => Review::Rejected,
+            _ => Review::None,
+        }
+    }
+}
+
+/// What the status of a job is derived from.
+pub struct Job<'a> {
+    pub is_config_valid: bool,
+    /// Whether the last run succeeded, once it finished.
+    pub result: Option<bool>,
+    /// Whether the job crashed or a stage after the migration failed it.
+    pub has_failed_stage: bool,
+    pub is_cancelled: bool,
+    pub is_queued: bool,
+    /// Whether the job conflicts with another one and that was not ignored.
+    pub has_conflicts: bool,
+    pub review: Review,
+    pub shortfalls: &'a Shortfalls,
+}
+
+impl Job<'_> {
+    pub fn status(&self, queue_state: &QueueState) -> JobStatus {
+        if self.result == Some(true) {
+            let shortfalls = self.shortfalls;
+            return if self.review == Review::Pending {
+                JobStatus::AwaitingReview
+            } else if self.review == Review::Rejected {
+                JobStatus::Rejected
+            } else if shortfalls.video_error.is_some() {
+                JobStatus::VideoFailed
+            } else if shortfalls.deposit_error.is_some() {
+                JobStatus::UploadPending
+            } else if !shortfalls.warnings.is_empty() {
+                JobStatus::DoneWithWarnings
+            } else {
+                JobStatus::ProcessingDone
+            };
+        } else if self.result == Some(false) || self.has_failed_stage {
+            return JobStatus::ProcessingError;
+        } else if self.is_cancelled {
+            return JobStatus::Cancelled;
+        } else if self.is_queued {
+            return JobStatus::Queued;
+        } else if self.is_config_valid && queue_state == &QueueState::Processing {
+            return JobStatus::Processing;
+        } else if self.is_config_valid && queue_state == &QueueState::Paused {
+            return JobStatus::Paused;
+        } else if self.is_config_valid && self.has_conflicts {
+            return JobStatus::Conflict;
+        } else if self.is_config_valid {
+            return JobStatus::ValidConfig;
+        } else if !self.is_config_valid {
+            return JobStatus::InvalidConfig;
+        }
+        JobStatus::Unkown
+    }
+}
+
+/// Change of the batch state that the user is notified of.
+#[derive(Debug, PartialEq)]
+pub enum QueueEvent {
+    /// No job is left to run.
+    Finished,
+}
+
+/// Returns the state of the batch after its jobs changed to `statuses`, with the event of the
+/// change. A running batch keeps going when jobs fail and only ends once no job is pending,
+/// with errors if any job failed.
+pub fn next_state(
+    queue_state: &QueueState,
+    statuses: &[JobStatus],
+) -> (QueueState, Option<QueueEvent>) {
+    if statuses.is_empty() {
+        (QueueState::Init, None)
+    } else if queue_state.is_processing() {
+        if statuses.iter().any(JobStatus::is_pending) {
+            (*queue_state, None)
+        } else if statuses.contains(&JobStatus::ProcessingError) {
+            (QueueState::ProcessingErrors, Some(QueueEvent::Finished))
+        } else {
+            (QueueState::ProcessingDone, Some(QueueEvent::Finished))
+        }
+    } else if statuses
+        .iter()
+        .any(|status| status == &JobStatus::InvalidConfig || status == &JobStatus::Conflict)
+    {
+        (QueueState::InvalidConfigs, None)
+    } else if queue_state == &QueueState::Scheduled {
+        (QueueState::Scheduled, None)
+    } else {
+        (QueueState::ValidConfigs, None)
+    }
+}
+
+/// Job waiting in a `JobQueue`.
+pub struct QueuedJob<J> {
+    pub path: PathBuf,
+    pub cancel: CancelToken,
+    pub job: J,
+}
+
+/// Jobs waiting for a free slot, started in the order they were queued.
+pub struct JobQueue<J> {
+    pending: VecDeque<QueuedJob<J>>,
+}
+
+impl<J> Default for JobQueue<J> {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<J> JobQueue<J> {
+    pub fn push(&mut self, path: PathBuf, cancel: CancelToken, job: J) {
+        self.pending.push_back(QueuedJob { path, cancel, job });
+    }
+
+    /// Puts a job that was started but could not run yet back at the front of the queue.
+    pub fn defer(&mut self, path: PathBuf, cancel: CancelToken, job: J) {
+        self.pending.push_front(QueuedJob { path, cancel, job });
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Returns the paths of the waiting jobs, the next one to start first.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.pending.iter().map(|job| job.path.as_path())
+    }
+
+    /// Removes the jobs that were cancelled while waiting and returns their paths.
+    pub fn remove_cancelled(&mut self) -> Vec<PathBuf> {
+        let (cancelled, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<VecDeque<_>, _>(|job| job.cancel.is_cancelled());
+        self.pending = pending;
+        cancelled.into_iter().map(|job| job.path).collect()
+    }
+
+    /// Takes the jobs to start while `running` jobs run and at most `max_jobs` may, none while
+    /// the batch is paused. Jobs that `can_start` rejects keep their place and let the jobs
+    /// after them start.
+    pub fn start(
+        &mut self,
+        queue_state: &QueueState,
+        running: usize,
+        max_jobs: usize,
+        can_start: impl Fn(&J) -> bool,
+    ) -> Vec<QueuedJob<J>> {
+        if queue_state == &QueueState::Paused {
+            return Vec::new();
+        }
+        let mut count = max_jobs.saturating_sub(running);
+        let mut started = Vec::new();
+        let mut waiting = VecDeque::new();
+        for job in std::mem::take(&mut self.pending) {
+            if count > 0 && can_start(&job.job) {
+                count -= 1;
+                started.push(job);
+            } else {
+                waiting.push_back(job);
+            }
+        }
+        self.pending = waiting;
+        started
+    }
+}
+
+/// What a running job reports back, in the order it happens.
+pub enum JobEvent {
+    SourcesCounted(usize),
+    EncodeAdapted(AdaptiveEncode),
+    Progress(f32),
+    StagesTimed(StageTimings),
+    Finished(Outcome),
+    /// The job has large frames and another job with large frames runs, so it goes back into
+    /// the queue instead of taking a slot while it waits.
+    Deferred(Box<JobRun>),
+}
+
+/// A job with the settings taken when it was queued.
+pub struct JobRun {
+    pub image_config: tree_migration::Config,
+    pub date_ranges: Vec<DateRange>,
+    pub settings: JobSettings,
+    /// Whether the frames are over `largeframe::LARGE_FRAME_PIXELS`, known once the job was
+    /// deferred.
+    pub is_large: bool,
+}
+
+impl JobRun {
+    /// Whether the job may start now. Only one job with large frames runs at a time.
+    pub fn can_start(&self) -> bool {
+        !(self.is_large && largeframe::is_slot_taken())
+    }
+
+    /// Runs the job and reports what happens through `on_event`. With `is_adaptive`, the
+    /// encode settings are first adapted to the resolution of the sources.
+    pub async fn run(
+        self,
+        is_adaptive: bool,
+        cancel: CancelToken,
+        log: JobLog,
+        on_event: Arc<dyn Fn(JobEvent) + Send + Sync>,
+    ) {
+        let sources = utils::source_images(&self.image_config).unwrap_or_default();
+        on_event(JobEvent::SourcesCounted(sources.len()));
+        let is_large = sources
+            .first()
+            .is_some_and(|source| largeframe::is_large(source));
+        let slot = is_large.then(largeframe::try_acquire).flatten();
+        if is_large && slot.is_none() {
+            on_event(JobEvent::Deferred(Box::new(JobRun { is_large, ..self })));
+            return;
+        }
+        let JobRun {
+            image_config,
+            date_ranges,
+            mut settings,
+            ..
+        } = self;
+        let encode = sources
+            .first()
+            .filter(|_| is_adaptive)
+            .and_then(|source| image::image_dimensions(source).ok())
+            .and_then(|resolution| {
+                AdaptiveEncode::new(settings.video_codec, &settings.codec_options, resolution)
+            });
+        if let Some(encode) = encode {
+            log.line(format!("Adaptive encode settings {}", encode.describe()));
+            settings.codec_options = encode.apply(&settings.codec_options);
+            on_event(JobEvent::EncodeAdapted(encode));
+        }
+        let progress = {
+            let on_event = on_event.clone();
+            Arc::new(move |fraction| on_event(JobEvent::Progress(fraction)))
+        };
+        let (outcome, timings) = runner::run_job(
+            &image_config,
+            &date_ranges,
+            &settings,
+            &cancel,
+            &log,
+            progress,
+        )
+        .await;
+        on_event(JobEvent::StagesTimed(timings));
+        on_event(JobEvent::Finished(outcome));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(shortfalls: &Shortfalls) -> Job<'_> {
+        Job {
+            is_config_valid: true,
+            result: None,
+            has_failed_stage: false,
+            is_cancelled: false,
+            is_queued: false,
+            has_conflicts: false,
+            review: Review::None,
+            shortfalls,
+        }
+    }
+
+    #[test]
+    fn valid_job_follows_the_queue() {
+        let shortfalls = Shortfalls::default();
+        let job = job(&shortfalls);
+        assert_eq!(
+            job.status(&QueueState::ValidConfigs),
+            JobStatus::ValidConfig
+        );
+        assert_eq!(job.status(&QueueState::Processing), JobStatus::Processing);
+        assert_eq!(job.status(&QueueState::Paused), JobStatus::Paused);
+        assert_eq!(
+            job.status(&QueueState::ProcessingDone),
+            JobStatus::ValidConfig
+        );
+    }
+
+    #[test]
+    fn invalid_and_conflicting_jobs() {
+        let shortfalls = Shortfalls::default();
+        let invalid = Job {
+            is_config_valid: false,
+            ..job(&shortfalls)
+        };
+        assert_eq!(
+            invalid.status(&QueueState::Processing),
+            JobStatus::InvalidConfig
+        );
+        let conflict = Job {
+            has_conflicts: true,
+            ..job(&shortfalls)
+        };
+        assert_eq!(
+            conflict.status(&QueueState::InvalidConfigs),
+            JobStatus::Conflict
+        );
+        assert_eq!(
+            conflict.status(&QueueState::Processing),
+            JobStatus::Processing
+        );
+    }
+
+    #[test]
+    fn finished_jobs_report_their_shortfalls() {
+        fn done(shortfalls: &Shortfalls) -> JobStatus {
+            Job {
+                result: Some(true),
+                ..job(shortfalls)
+            }
+            .status(&QueueState::Processing)
+        }
+        let mut shortfalls = Shortfalls::default();
+        assert_eq!(done(&shortfalls), JobStatus::ProcessingDone);
+        shortfalls.warnings.push("XMP sidecars failed".to_owned());
+        assert_eq!(done(&shortfalls), JobStatus::DoneWithWarnings);
+        shortfalls.deposit_error = Some("Timed out".to_owned());
+        assert_eq!(done(&shortfalls), JobStatus::UploadPending);
+        shortfalls.video_error = Some("ffmpeg exited".to_owned());
+        assert_eq!(done(&shortfalls), JobStatus::VideoFailed);
+        assert!(done(&shortfalls).is_done());
+    }
+
+    #[test]
+    fn reviewed_jobs() {
+        let shortfalls = Shortfalls::default();
+        let proxy = |review| {
+            Job {
+                result: Some(true),
+                review,
+                ..job(&shortfalls)
+            }
+            .status(&QueueState::ProcessingDone)
+        };
+        assert_eq!(proxy(Review::Pending), JobStatus::AwaitingReview);
+        assert_eq!(proxy(Review::Rejected), JobStatus::Rejected);
+        assert_eq!(proxy(Review::Approved), JobStatus::ProcessingDone);
+        assert!(!proxy(Review::Pending).is_done());
+        assert_eq!(
+            next_state(
+                &QueueState::Processing,
+                &[JobStatus::AwaitingReview, JobStatus::ProcessingDone]
+            ),
+            (QueueState::ProcessingDone, Some(QueueEvent::Finished))
+        );
+    }
+
+    #[test]
+    fn failures_take_precedence_over_waiting() {
+        let shortfalls = Shortfalls::default();
+        let failed = Job {
+            result: Some(false),
+            is_queued: true,
+            ..job(&shortfalls)
+        };
+        assert_eq!(
+            failed.status(&QueueState::Processing),
+            JobStatus::ProcessingError
+        );
+        let crashed = Job {
+            has_failed_stage: true,
+            is_cancelled: true,
+            ..job(&shortfalls)
+        };
+        assert_eq!(
+            crashed.status(&QueueState::Processing),
+            JobStatus::ProcessingError
+        );
+        let cancelled = Job {
+            is_cancelled: true,
+            is_queued: true,
+            ..job(&shortfalls)
+        };
+        assert_eq!(
+            cancelled.status(&QueueState::Processing),
+            JobStatus::Cancelled
+        );
+        let queued = Job {
+            is_queued: true,
+            ..job(&shortfalls)
+        };
+        assert_eq!(queued.status(&QueueState::Paused), JobStatus::Queued);
+    }
+
+    #[test]
+    fn empty_queue_is_init() {
+        assert_eq!(
+            next_state(&QueueState::Processing, &[]),
+            (QueueState::Init, None)
+        );
+        assert_eq!(
+            next_state(&QueueState::ValidConfigs, &[]),
+            (QueueState::Init, None)
+        );
+    }
+
+    #[test]
+    fn idle_queue_is_blocked_by_invalid_and_conflicting_jobs() {
+        let valid = [JobStatus::ValidConfig, JobStatus::ProcessingDone];
+        assert_eq!(
+            next_state(&QueueState::Init, &valid),
+            (QueueState::ValidConfigs, None)
+        );
+        for blocking in [JobStatus::InvalidConfig, JobStatus::Conflict] {
+            assert_eq!(
+                next_state(
+                    &QueueState::ValidConfigs,
+                    &[JobStatus::ValidConfig, blocking]
+                ),
+                (QueueState::InvalidConfigs, None)
+            );
+        }
+    }
+
+    #[test]
+    fn scheduled_queue_waits_unless_blocked() {
+        assert_eq!(
+            next_state(
+                &QueueState::Scheduled,
+                &[JobStatus::ValidConfig, JobStatus::ProcessingDone]
+            ),
+            (QueueState::Scheduled, None)
+        );
+        assert_eq!(
+            next_state(
+                &QueueState::Scheduled,
+                &[JobStatus::ValidConfig, JobStatus::InvalidConfig]
+            ),
+            (QueueState::InvalidConfigs, None)
+        );
+    }
+
+    #[test]
+    fn running_queue_waits_for_pending_jobs() {
+        for pending in [JobStatus::Processing, JobStatus::Queued, JobStatus::Paused] {
+            assert_eq!(
+                next_state(
+                    &QueueState::Processing,
+                    &[JobStatus::ProcessingDone, pending]
+                ),
+                (QueueState::Processing, None)
+            );
+        }
+        assert_eq!(
+            next_state(&QueueState::Paused, &[JobStatus::Queued]),
+            (QueueState::Paused, None)
+        );
+    }
+
+    #[test]
+    fn running_queue_finishes_when_nothing_is_pending() {
+        let finished = [JobStatus::ProcessingDone, JobStatus::Cancelled];
+        let failed = [JobStatus::ProcessingDone, JobStatus::ProcessingError];
+        for queue_state in [QueueState::Processing, QueueState::Paused] {
+            assert_eq!(
+                next_state(&queue_state, &finished),
+                (QueueState::ProcessingDone, Some(QueueEvent::Finished))
+            );
+            assert_eq!(
+                next_state(&queue_state, &failed),
+                (QueueState::ProcessingErrors, Some(QueueEvent::Finished))
+            );
+        }
+    }
+
+    #[test]
+    fn failed_job_does_not_stop_the_running_queue() {
+        let statuses = [JobStatus::ProcessingError, JobStatus::Queued];
+        for queue_state in [QueueState::Processing, QueueState::Paused] {
+            assert_eq!(next_state(&queue_state, &statuses), (queue_state, None));
+        }
+    }
+
+    #[test]
+    fn queue_starts_jobs_in_order_up_to_the_limit() {
+        let mut queue = JobQueue::default();
+        for name in ["a", "b", "c"] {
+            queue.push(PathBuf::from(name), CancelToken::default(), ());
+        }
+        let started = queue.start(&QueueState::Processing, 1, 3, |_| true);
+        assert_eq!(
+            started
+                .iter()
+                .map(|job| job.path.clone())
+                .collect::<Vec<_>>(),
+            [PathBuf::from("a"), PathBuf::from("b")]
+        );
+        assert!(queue
+            .start(&QueueState::Processing, 3, 3, |_| true)
+            .is_empty());
+        assert!(queue
+            .start(&QueueState::Processing, 4, 3, |_| true)
+            .is_empty());
+        assert_eq!(queue.paths().collect::<Vec<_>>(), [Path::new("c")]);
+    }
+
+    #[test]
+    fn paused_queue_starts_nothing() {
+        let mut queue = JobQueue::default();
+        queue.push(PathBuf::from("a"), CancelToken::default(), ());
+        assert!(queue.start(&QueueState::Paused, 0, 2, |_| true).is_empty());
+        assert_eq!(
+            queue.start(&QueueState::Processing, 0, 2, |_| true).len(),
+            1
+        );
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn cancelled_jobs_leave_the_queue() {
+        let mut queue = JobQueue::default();
+        let cancel = CancelToken::default();
+        queue.push(PathBuf::from("a"), CancelToken::default(), ());
+        queue.push(PathBuf::from("b"), cancel.clone(), ());
+        queue.push(PathBuf::from("c"), CancelToken::default(), ());
+        assert!(queue.remove_cancelled().is_empty());
+        cancel.cancel();
+        assert_eq!(queue.remove_cancelled(), [PathBuf::from("b")]);
+        assert_eq!(
+            queue.paths().collect::<Vec<_>>(),
+            [Path::new("a"), Path::new("c")]
+        );
+    }
+
+    #[test]
+    fn jobs_that_cannot_start_keep_their_place() {
+        let mut queue = JobQueue::default();
+        for (name, is_large) in [("a", true), ("b", false), ("c", true), ("d", false)] {
+            queue.push(PathBuf::from(name), CancelToken::default(), is_large);
+        }
+        let started = queue.start(&QueueState::Processing, 0, 1, |is_large| !is_large);
+        assert_eq!(
+            started
+                .iter()
+                .map(|job| job.path.clone())
+                .collect::<Vec<_>>(),
+            [PathBuf::from("b")]
+        );
+        queue.defer(PathBuf::from("b"), CancelToken::default(), false);
+        assert_eq!(
+            queue.paths().collect::<Vec<_>>(),
+            [
+                Path::new("b"),
+                Path::new("a"),
+                Path::new("c"),
+                Path::new("d")
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::throughput;
+use crate::utils;
+use crate::video::{self, Codec};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{DiskExt, System, SystemExt};
+
+/// Videos shorter than this, in seconds, are almost always a frame rate mistake.
+const MIN_VIDEO_SECONDS: f64 = 2.0;
+/// Videos longer than this, in seconds, are almost always a frame rate mistake.
+const MAX_VIDEO_SECONDS: f64 = 3600.0;
+/// Length of the video that suggested frame rates aim for, in seconds.
+const SUGGESTED_VIDEO_SECONDS: f64 = 20.0;
+
+/// Findings of checking a job without running it.
+pub struct Validation {
+    /// What the job would process and write.
+    pub notes: Vec<String>,
+    /// What would make the job fail.
+    pub problems: Vec<String>,
+}
+
+impl Validation {
+    pub fn failed(problem: String) -> Self {
+        Self {
+            notes: Vec::new(),
+            problems: vec![problem],
+        }
+    }
+}
+
+/// Returns the closest folder of `path` that exists, as the output folder may not exist yet.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|ancestor| ancestor.is_dir())
+}
+
+/// Returns the mount point of the disk holding `folder` and the space available on it, in
+/// bytes.
+fn disk(system: &System, folder: &Path) -> Option<(PathBuf, u64)> {
+    let folder = folder.canonicalize().ok()?;
+    system
+        .disks()
+        .iter()
+        .filter(|disk| folder.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| (disk.mount_point().to_path_buf(), disk.available_space()))
+}
+
+/// Returns the space available on the disk holding `folder`, in bytes.
+fn available_space(folder: &Path) -> Option<u64> {
+    let mut system = System::new();
+    system.refresh_disks_list();
+    disk(&system, folder).map(|(_, available)| available)
+}
+
+/// Rough size of a video relative to the frames it is encoded from.
+fn video_ratio(codec: Codec) -> f64 {
+    match codec {
+        Codec::H264 => 0.05,
+        Codec::H265 | Codec::Vp9 => 0.03,
+        Codec::ProRes => 0.6,
+        Codec::Gif => 0.3,
+        Codec::None => 0.0,
+    }
+}
+
+/// Estimates what the job of `config` writes into which folder: frames of about the average
+/// size of its source images into its output folder and, if `video` is given, a video of that
+/// codec into that folder.
+pub fn estimate_writes(
+    config: &tree_migration::Config,
+    video: Option<(Codec, PathBuf)>,
+) -> Result<Vec<(PathBuf, u64)>, String> {
+    let images = utils::source_images(config).map_err(|e| e.to_string())?;
+    let sizes = images
+        .iter()
+        .filter_map(|image| std::fs::metadata(image).ok())
+        .map(|metadata| metadata.len())
+        .collect::<Vec<_>>();
+    let average = sizes.iter().sum::<u64>() / sizes.len().max(1) as u64;
+    let frames = average * images.len() as u64;
+    let mut writes = vec![(config.output_path.clone(), frames)];
+    if let Some((codec, folder)) = video {
+        writes.push((folder, (frames as f64 * video_ratio(codec)) as u64));
+    }
+    Ok(writes)
+}
+
+/// Adds up `writes` per disk and describes the disks without enough free space for them.
+/// Folders on disks that cannot be determined are left out.
+pub fn check_space(writes: &[(PathBuf, u64)]) -> Vec<String> {
+    let mut system = System::new();
+    system.refresh_disks_list();
+    let mut disks: BTreeMap<PathBuf, (u64, u64)> = BTreeMap::new();
+    for (folder, bytes) in writes {
+        let Some((mount_point, available)) =
+            existing_ancestor(folder).and_then(|folder| disk(&system, folder))
+        else {
+            continue;
+        };
+        disks.entry(mount_point).or_insert((0, available)).0 += bytes;
+    }
+    disks
+        .into_iter()
+        .filter(|(_, (required, available))| required > available)
+        .map(|(mount_point, (required, available))| {
+            format!(
+                "About {:.0} MB needed on {} but only {:.0} MB free",
+                required as f64 / 1_000_000.0,
+                mount_point.display(),
+                available as f64 / 1_000_000.0
+            )
+        })
+        .collect()
+}
+
+/// Describes how long a video lasts, with tenths of a second for short ones.
+fn describe_length(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{:.1} s", seconds)
+    } else {
+        throughput::describe(Duration::from_secs_f64(seconds))
+    }
+}
+
+/// Checks how long a video of `frames` frames at `frame_rate` lasts. If it is shorter than
+/// `MIN_VIDEO_SECONDS` or longer than `MAX_VIDEO_SECONDS`, returns a frame rate that gives a
+/// sensible length and a description of both.
+pub fn check_duration(frames: usize, frame_rate: u32) -> Option<(u32, String)> {
+    if frames == 0 || frame_rate == 0 {
+        return None;
+    }
+    let seconds = frames as f64 / frame_rate as f64;
+    if (MIN_VIDEO_SECONDS..=MAX_VIDEO_SECONDS).contains(&seconds) {
+        return None;
+    }
+    let max_rate = video::MAX_FRAME_RATE as u32;
+    let suggested = ((frames as f64 / SUGGESTED_VIDEO_SECONDS).round() as u32).clamp(1, max_rate);
+    if suggested == frame_rate {
+        return None;
+    }
+    Some((
+        suggested,
+        format!(
+            "{} frames at {} fps last {}, {} fps gives {}",
+            frames,
+            frame_rate,
+            describe_length(seconds),
+            suggested,
+            describe_length(frames as f64 / suggested as f64)
+        ),
+    ))
+}
+
+/// Checks that the job of `config` can run without writing anything: that the input folder
+/// is readable and holds images for the date range, and that the output folder is writable
+/// and its disk has room for about as much as the source images take.
+pub fn validate(config: &tree_migration::Config) -> Validation {
+    let mut notes = Vec::new();
+    let mut problems = Vec::new();
+
+    let images = match utils::source_images(config) {
+        Ok(images) => images,
+        Err(e) => {
+            return Validation::failed(format!(
+                "Cannot read {}: {}",
+                config.input_path.display(),
+                e
+            ))
+        }
+    };
+    let days = (config.end_date - config.start_date).num_days().max(-1) as usize + 1;
+    let covered = images
+        .iter()
+        .filter_map(|image| utils::frame_date(image))
+        .collect::<HashSet<_>>()
+        .len();
+    if images.is_empty() {
+        problems.push(format!(
+            "No source images from {} to {}",
+            config.start_date, config.end_date
+        ));
+    } else {
+        notes.push(format!(
+            "{} source images, {} of {} days without images",
+            images.len(),
+            days.saturating_sub(covered),
+            days
+        ));
+    }
+
+    let required = images
+        .iter()
+        .filter_map(|image| std::fs::metadata(image).ok())
+        .map(|metadata| metadata.len())
+        .sum::<u64>();
+    match existing_ancestor(&config.output_path) {
+        Some(folder) => {
+            match std::fs::metadata(folder) {
+                Ok(metadata) if metadata.permissions().readonly() => {
+                    problems.push(format!("{} is read-only", folder.display()))
+                }
+                Ok(_) => {}
+                Err(e) => problems.push(format!("Cannot access {}: {}", folder.display(), e)),
+            }
+            match available_space(folder) {
+                Some(available) if available < required => problems.push(format!(
+                    "About {:.0} MB needed but only {:.0} MB free for {}",
+                    required as f64 / 1_000_000.0,
+                    available as f64 / 1_000_000.0,
+                    config.output_path.display()
+                )),
+                Some(available) => notes.push(format!(
+                    "About {:.0} MB needed, {:.0} MB free",
+                    required as f64 / 1_000_000.0,
+                    available as f64 / 1_000_000.0
+                )),
+                None => notes.push(format!(
+                    "About {:.0} MB needed, free space unknown",
+                    required as f64 / 1_000_000.0
+                )),
+            }
+        }
+        None => problems.push(format!("{} is not reachable", config.output_path.display())),
+    }
+
+    Validation { notes, problems }
+}
@@ -0,0 +1,553 @@
+use crate::batch::JobOverrides;
+use crate::dates::{CameraClock, DateRange};
+use crate::pipeline::Review;
+use crate::video::Codec;
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use rusqlite::{params, Connection};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub const DATABASE_NAME: &str = "queue.sqlite";
+
+/// Status of a job as recorded in the queue database, see `pipeline::JobStatus` for the one
+/// shown.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordStatus {
+    Queued,
+    Processing,
+    Done,
+    Error,
+}
+
+impl RecordStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordStatus::Queued => "queued",
+            RecordStatus::Processing => "processing",
+            RecordStatus::Done => "done",
+            RecordStatus::Error => "error",
+        }
+    }
+
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "processing" => RecordStatus::Processing,
+            "done" => RecordStatus::Done,
+            "error" => RecordStatus::Error,
+            _ => RecordStatus::Queued,
+        }
+    }
+}
+
+/// The persisted state of a queued config file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobRecord {
+    pub path: PathBuf,
+    pub status: RecordStatus,
+    pub error: Option<String>,
+    pub output_override: Option<PathBuf>,
+    pub date_ranges: Vec<DateRange>,
+    /// ffmpeg binary the job uses instead of the one in the settings.
+    pub ffmpeg_path: Option<PathBuf>,
+    /// Settings from the batch file the job was loaded from or from the job script.
+    pub overrides: JobOverrides,
+    /// Video written by the last run.
+    pub video: Option<PathBuf>,
+    /// Number appended to the name of the job's video, once the job started. See
+    /// `VideoName::collision`.
+    pub video_name_collision: Option<usize>,
+    pub review: Review,
+    /// Proxy rendered by the last run in review mode.
+    pub proxy: Option<PathBuf>,
+    /// Output frames flagged in the QC scrubber.
+    pub flagged_frames: BTreeSet<PathBuf>,
+    /// Output frames left out of the video.
+    pub excluded_frames: BTreeSet<PathBuf>,
+}
+
+/// A finished render of a site, kept after the job left the queue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Render {
+    pub config_path: PathBuf,
+    pub location: String,
+    pub camera: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// DOI or handle of the archive deposited to a repository.
+    pub deposit_id: Option<String>,
+    pub metrics: Option<RenderMetrics>,
+}
+
+/// A job that finished, successfully or not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub finished_at: DateTime<FixedOffset>,
+    pub config_path: PathBuf,
+    pub location: String,
+    pub camera: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// In seconds.
+    pub duration: f64,
+    pub status: String,
+    /// Output folders and files that exist after the job.
+    pub outputs: Vec<PathBuf>,
+}
+
+/// Narrows the history down to jobs whose location and camera contain the given texts and
+/// whose date range contains `date`.
+#[derive(Default)]
+pub struct HistoryQuery {
+    pub location: String,
+    pub camera: String,
+    pub date: Option<NaiveDate>,
+}
+
+/// Number of jobs returned by a history search, most recent first.
+const HISTORY_LIMIT: usize = 500;
+
+/// How long a render took and how much it produced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderMetrics {
+    /// In seconds.
+    pub duration: f64,
+    pub frames: usize,
+    /// Size of the output frames.
+    pub bytes: u64,
+}
+
+/// Metrics of a render at the time it finished.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderSample {
+    pub location: String,
+    pub camera: String,
+    pub finished_at: DateTime<FixedOffset>,
+    pub metrics: RenderMetrics,
+}
+
+pub fn format_date_ranges(date_ranges: &[DateRange]) -> String {
+    date_ranges
+        .iter()
+        .map(|(start_date, end_date)| format!("{}/{}", start_date, end_date))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn parse_date_ranges(date_ranges: &str) -> Vec<DateRange> {
+    date_ranges
+        .split_whitespace()
+        .filter_map(|range| {
+            let (start_date, end_date) = range.split_once('/')?;
+            Some((
+                NaiveDate::parse_from_str(start_date, "%Y-%m-%d").ok()?,
+                NaiveDate::parse_from_str(end_date, "%Y-%m-%d").ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Lists paths one per line, as outputs and frames are stored.
+fn format_paths<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) -> String {
+    paths
+        .into_iter()
+        .map(|path| path.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_paths(paths: &str) -> impl Iterator<Item = PathBuf> + '_ {
+    paths.lines().map(PathBuf::from)
+}
+
+/// The job queue, stored in an SQLite database so that it survives crashes and can be
+/// inspected with standard tools.
+pub struct Queue {
+    connection: Connection,
+    records: Vec<JobRecord>,
+}
+
+impl Queue {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let connection = Connection::open(path)?;
+        connection.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = FULL;
+             CREATE TABLE IF NOT EXISTS jobs (
+                 path TEXT PRIMARY KEY,
+                 status TEXT NOT NULL,
+                 error TEXT,
+                 output_override TEXT,
+                 date_ranges TEXT NOT NULL DEFAULT ''
+             );
+             CREATE TABLE IF NOT EXISTS renders (
+                 config_path TEXT NOT NULL,
+                 location TEXT NOT NULL,
+                 camera TEXT NOT NULL,
+                 start_date TEXT NOT NULL,
+                 end_date TEXT NOT NULL,
+                 finished_at TEXT NOT NULL
+             );",
+        )?;
+        for (table, column, column_type) in [
+            ("jobs", "ffmpeg_path", "TEXT"),
+            ("jobs", "video_codec", "TEXT"),
+            ("jobs", "frame_rate", "INTEGER"),
+            ("jobs", "camera_clock", "TEXT"),
+            ("jobs", "video_path", "TEXT"),
+            ("jobs", "video_name_collision", "INTEGER"),
+            ("jobs", "review", "TEXT"),
+            ("jobs", "proxy_path", "TEXT"),
+            ("jobs", "flagged_frames", "TEXT"),
+            ("jobs", "excluded_frames", "TEXT"),
+            ("renders", "deposit_id", "TEXT"),
+            ("renders", "duration", "REAL"),
+            ("renders", "frames", "INTEGER"),
+            ("renders", "bytes", "INTEGER"),
+            ("renders", "status", "TEXT"),
+            ("renders", "outputs", "TEXT"),
+            ("renders", "is_success", "INTEGER NOT NULL DEFAULT 1"),
+        ] {
+            let has_column = connection
+                .prepare(format!("SELECT {} FROM {} LIMIT 0", column, table).as_str())
+                .is_ok();
+            if !has_column {
+                connection.execute(
+                    format!(
+                        "ALTER TABLE {} ADD COLUMN {} {}",
+                        table, column, column_type
+                    )
+                    .as_str(),
+                    [],
+                )?;
+            }
+        }
+        migrate_history(&connection)?;
+        let mut queue = Self {
+            connection,
+            records: Vec::new(),
+        };
+        queue.records = queue.load()?;
+        Ok(queue)
+    }
+
+    fn load(&self) -> rusqlite::Result<Vec<JobRecord>> {
+        let mut statement = self.connection.prepare(
+            "SELECT path, status, error, output_override, date_ranges, ffmpeg_path, video_codec,
+                 frame_rate, camera_clock, video_path, video_name_collision,
+                 review, proxy_path, flagged_frames, excluded_frames
+             FROM jobs ORDER BY path",
+        )?;
+        let records = statement
+            .query_map([], |row| {
+                Ok(JobRecord {
+                    path: PathBuf::from(row.get::<_, String>(0)?),
+                    status: RecordStatus::parse(&row.get::<_, String>(1)?),
+                    error: row.get(2)?,
+                    output_override: row.get::<_, Option<String>>(3)?.map(PathBuf::from),
+                    date_ranges: parse_date_ranges(&row.get::<_, String>(4)?),
+                    ffmpeg_path: row.get::<_, Option<String>>(5)?.map(PathBuf::from),
+                    overrides: JobOverrides {
+                        video_codec: row
+                            .get::<_, Option<String>>(6)?
+                            .and_then(|name| Codec::from_name(&name)),
+                        frame_rate: row.get(7)?,
+                        camera_clock: row
+                            .get::<_, Option<String>>(8)?
+                            .and_then(|clock| CameraClock::parse(&clock)),
+                    },
+                    video: row.get::<_, Option<String>>(9)?.map(PathBuf::from),
+                    video_name_collision: row
+                        .get::<_, Option<i64>>(10)?
+                        .map(|collision| collision as usize),
+                    review: row
+                        .get::<_, Option<String>>(11)?
+                        .map_or(Review::None, |review| Review::parse(&review)),
+                    proxy: row.get::<_, Option<String>>(12)?.map(PathBuf::from),
+                    flagged_frames: parse_paths(
+                        &row.get::<_, Option<String>>(13)?.unwrap_or_default(),
+                    )
+                    .collect(),
+                    excluded_frames: parse_paths(
+                        &row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+                    )
+                    .collect(),
+                })
+            })?
+            .collect();
+        records
+    }
+
+    pub fn records(&self) -> &[JobRecord] {
+        &self.records
+    }
+
+    /// Replaces the stored queue with `records` in a single transaction. Nothing is written
+    /// if the queue did not change.
+    pub fn sync(&mut self, mut records: Vec<JobRecord>) -> rusqlite::Result<()> {
+        records.sort_by(|a, b| a.path.cmp(&b.path));
+        if records == self.records {
+            return Ok(());
+        }
+        let transaction = self.connection.transaction()?;
+        for record in &self.records {
+            if !records.iter().any(|r| r.path == record.path) {
+                transaction.execute(
+                    "DELETE FROM jobs WHERE path = ?1",
+                    params![record.path.to_string_lossy()],
+                )?;
+            }
+        }
+        for record in records.iter().filter(|r| !self.records.contains(r)) {
+            transaction.execute(
+                "INSERT OR REPLACE INTO jobs (path, status, error, output_override, date_ranges,
+                     ffmpeg_path, video_codec, frame_rate, camera_clock, video_path,
+                     video_name_collision, review, proxy_path, flagged_frames, excluded_frames)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    record.path.to_string_lossy(),
+                    record.status.as_str(),
+                    record.error,
+                    record
+                        .output_override
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    format_date_ranges(&record.date_ranges),
+                    record
+                        .ffmpeg_path
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    record.overrides.video_codec.map(|codec| codec.name()),
+                    record.overrides.frame_rate,
+                    record.overrides.camera_clock.map(|clock| clock.name()),
+                    record
+                        .video
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    record
+                        .video_name_collision
+                        .map(|collision| collision as i64),
+                    record.review.as_str(),
+                    record
+                        .proxy
+                        .as_ref()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    format_paths(&record.flagged_frames),
+                    format_paths(&record.excluded_frames),
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        self.records = records;
+        Ok(())
+    }
+
+    /// Records a job that finished, successfully or not, with its render if it delivered one.
+    /// Only jobs with a render count as renders of their site.
+    pub fn record_history(
+        &self,
+        entry: &HistoryEntry,
+        render: Option<&Render>,
+    ) -> rusqlite::Result<()> {
+        let metrics = render.and_then(|render| render.metrics.as_ref());
+        self.connection.execute(
+            "INSERT INTO renders (config_path, location, camera, start_date, end_date,
+                 finished_at, deposit_id, duration, frames, bytes, status, outputs, is_success)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                entry.config_path.to_string_lossy(),
+                entry.location,
+                entry.camera,
+                entry.start_date.to_string(),
+                entry.end_date.to_string(),
+                entry.finished_at.to_rfc3339(),
+                render.and_then(|render| render.deposit_id.clone()),
+                metrics.map_or(entry.duration, |metrics| metrics.duration),
+                metrics.map(|metrics| metrics.frames as i64),
+                metrics.map(|metrics| metrics.bytes as i64),
+                entry.status,
+                format_paths(&entry.outputs),
+                render.is_some(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the finished jobs matching `query`, most recent first.
+    pub fn search_history(&self, query: &HistoryQuery) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT finished_at, config_path, location, camera, start_date, end_date,
+                 COALESCE(duration, 0), COALESCE(status, 'Done'), COALESCE(outputs, '')
+             FROM renders
+             WHERE location LIKE ?1 ESCAPE '\\' AND camera LIKE ?2 ESCAPE '\\'
+                 AND (?3 IS NULL OR (start_date <= ?3 AND end_date >= ?3))
+             ORDER BY finished_at DESC LIMIT ?4",
+        )?;
+        let entries = statement
+            .query_map(
+                params![
+                    format!("%{}%", escape_like(&query.location)),
+                    format!("%{}%", escape_like(&query.camera)),
+                    query.date.map(|date| date.to_string()),
+                    HISTORY_LIMIT as i64,
+                ],
+                |row| {
+                    let date = |index| {
+                        row.get::<_, String>(index).map(|date| {
+                            NaiveDate::parse_from_str(&date, "%Y-%m-%d").unwrap_or_default()
+                        })
+                    };
+                    Ok(HistoryEntry {
+                        finished_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                            .unwrap_or_default(),
+                        config_path: PathBuf::from(row.get::<_, String>(1)?),
+                        location: row.get(2)?,
+                        camera: row.get(3)?,
+                        start_date: date(4)?,
+                        end_date: date(5)?,
+                        duration: row.get(6)?,
+                        status: row.get(7)?,
+                        outputs: parse_paths(&row.get::<_, String>(8)?).collect(),
+                    })
+                },
+            )?
+            .collect();
+        entries
+    }
+
+    /// Returns the render with the latest end date of every site.
+    pub fn last_renders(&self) -> rusqlite::Result<Vec<Render>> {
+        let mut statement = self.connection.prepare(
+            "SELECT config_path, location, camera, start_date, MAX(end_date), deposit_id,
+                 duration, frames, bytes
+             FROM renders WHERE is_success = 1
+             GROUP BY location, camera ORDER BY location, camera",
+        )?;
+        let renders = statement
+            .query_map([], |row| {
+                let date = |index| {
+                    row.get::<_, String>(index).map(|date| {
+                        NaiveDate::parse_from_str(&date, "%Y-%m-%d").unwrap_or_default()
+                    })
+                };
+                Ok(Render {
+                    config_path: PathBuf::from(row.get::<_, String>(0)?),
+                    location: row.get(1)?,
+                    camera: row.get(2)?,
+                    start_date: date(3)?,
+                    end_date: date(4)?,
+                    deposit_id: row.get(5)?,
+                    metrics: metrics(row, 6)?,
+                })
+            })?
+            .collect();
+        renders
+    }
+
+    /// Returns the metrics of every render that recorded them, oldest first.
+    pub fn render_history(&self) -> rusqlite::Result<Vec<RenderSample>> {
+        let mut statement = self.connection.prepare(
+            "SELECT location, camera, finished_at, duration, frames, bytes FROM renders
+             WHERE is_success = 1 AND duration IS NOT NULL ORDER BY finished_at",
+        )?;
+        let samples = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    metrics(row, 3)?,
+                ))
+            })?
+            .filter_map(|sample| match sample {
+                Ok((location, camera, finished_at, Some(metrics))) => {
+                    let finished_at = DateTime::parse_from_rfc3339(&finished_at).ok()?;
+                    Some(Ok(RenderSample {
+                        location,
+                        camera,
+                        finished_at,
+                        metrics,
+                    }))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect();
+        samples
+    }
+}
+
+/// Moves the jobs of the former history table into the renders table. Failed jobs become
+/// renders that do not count, finished ones complete the render recorded with them.
+fn migrate_history(connection: &Connection) -> rusqlite::Result<()> {
+    let has_history = connection
+        .prepare("SELECT status FROM history LIMIT 0")
+        .is_ok();
+    if !has_history {
+        return Ok(());
+    }
+    let transaction = connection.unchecked_transaction()?;
+    transaction.execute_batch(
+        "UPDATE renders SET
+             status = (SELECT status FROM history
+                 WHERE history.config_path = renders.config_path
+                     AND history.start_date = renders.start_date
+                     AND history.end_date = renders.end_date
+                 ORDER BY history.finished_at DESC LIMIT 1),
+             outputs = (SELECT outputs FROM history
+                 WHERE history.config_path = renders.config_path
+                     AND history.start_date = renders.start_date
+                     AND history.end_date = renders.end_date
+                 ORDER BY history.finished_at DESC LIMIT 1),
+             duration = COALESCE(duration, (SELECT duration FROM history
+                 WHERE history.config_path = renders.config_path
+                     AND history.start_date = renders.start_date
+                     AND history.end_date = renders.end_date
+                 ORDER BY history.finished_at DESC LIMIT 1))
+         WHERE status IS NULL;
+         INSERT INTO renders (config_path, location, camera, start_date, end_date, finished_at,
+             duration, status, outputs, is_success)
+         SELECT config_path, location, camera, start_date, end_date, finished_at, duration,
+             status, outputs, 0
+         FROM history
+         WHERE status NOT IN ('Done', 'Done with warnings', 'Done, upload pending',
+             'Images done, video failed');
+         DROP TABLE history;",
+    )?;
+    transaction.commit()
+}
+
+/// Escapes the wildcards of a LIKE pattern, for `ESCAPE '\'`.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Reads the duration, frames and bytes columns starting at `index`.
+fn metrics(row: &rusqlite::Row, index: usize) -> rusqlite::Result<Option<RenderMetrics>> {
+    let duration = row.get::<_, Option<f64>>(index)?;
+    let frames = row.get::<_, Option<i64>>(index + 1)?;
+    let bytes = row.get::<_, Option<i64>>(index + 2)?;
+    Ok(match (duration, frames, bytes) {
+        (Some(duration), Some(frames), Some(bytes)) => Some(RenderMetrics {
+            duration,
+            frames: frames as usize,
+            bytes: bytes as u64,
+        }),
+        _ => None,
+    })
+}
+
+/// Deletes a queue database that cannot be opened, including its write-ahead log.
+pub fn remove_database(path: &Path) -> std::io::Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let file = PathBuf::from(format!("{}{}", path.display(), suffix));
+        if file.exists() {
+            std::fs::remove_file(file)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use crate::dates::DateRange;
+use crate::largeframe;
+use crate::utils;
+use printpdf::{
+    BuiltinFont, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const THUMBNAIL_WIDTH: f32 = 50.0;
+/// Size in pixels thumbnails are scaled down to before they are embedded.
+const THUMBNAIL_PIXELS: u32 = 400;
+
+/// A job of the batch as summarized in the report.
+pub struct SiteSummary {
+    pub site: String,
+    pub date_range: DateRange,
+    pub status: String,
+    pub source_path: PathBuf,
+    pub output_path: PathBuf,
+    /// Checksum manifest of the job's outputs, if one was written.
+    pub manifest_path: Option<PathBuf>,
+    /// File names of the frames flagged in QC.
+    pub flagged_frames: Vec<String>,
+}
+
+/// Everything shown in the PDF report of a batch.
+pub struct BatchReport {
+    pub title: String,
+    pub settings: Vec<(String, String)>,
+    pub sites: Vec<SiteSummary>,
+    /// Config paths with the error of their job.
+    pub failures: Vec<(String, String)>,
+}
+
+/// Number of days of `date_range` with at least one source image, and number of days in it.
+fn coverage(source_path: &Path, (start_date, end_date): DateRange) -> (usize, usize) {
+    let days = (end_date - start_date).num_days().max(-1) as usize + 1;
+    let covered = utils::image_files(source_path)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|image| utils::frame_date(image))
+        .filter(|date| (start_date..=end_date).contains(date))
+        .collect::<HashSet<_>>()
+        .len();
+    (covered, days)
+}
+
+/// Writes pages top to bottom, starting a new page when the current one is full.
+struct Writer {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Distance of the next line from the bottom of the page.
+    y: f32,
+}
+
+impl Writer {
+    fn new(title: &str) -> Result<Self, String> {
+        let (document, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Content");
+        let font = document
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| e.to_string())?;
+        let bold = document
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| e.to_string())?;
+        let layer = document.get_page(page).get_layer(layer);
+        Ok(Self {
+            document,
+            layer,
+            font,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+
+    /// Starts a new page unless `height` millimetres are left on the current one.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+        let (page, layer) = self
+            .document
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Content");
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn text(&mut self, text: &str, size: f32, x: f32, is_bold: bool) {
+        let height = size * 0.5;
+        self.reserve(height);
+        self.y -= height;
+        let font = if is_bold { &self.bold } else { &self.font };
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), font);
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.y -= 4.0;
+        self.text(text, 14.0, MARGIN, true);
+        self.y -= 2.0;
+    }
+
+    fn line(&mut self, text: &str) {
+        self.text(text, 10.0, MARGIN, false);
+    }
+
+    /// Writes a site with its thumbnail on the left and its details on the right.
+    fn site(&mut self, thumbnail: Option<image::DynamicImage>, lines: &[String]) {
+        let thumbnail = thumbnail.map(|thumbnail| {
+            let dpi = thumbnail.width() as f32 * 25.4 / THUMBNAIL_WIDTH;
+            let height = thumbnail.height() as f32 * 25.4 / dpi;
+            (thumbnail, dpi, height)
+        });
+        let text_height = lines.len() as f32 * 5.0;
+        let height = thumbnail
+            .as_ref()
+            .map_or(text_height, |(_, _, height)| height.max(text_height));
+        self.reserve(height + 6.0);
+        let top = self.y;
+        if let Some((thumbnail, dpi, thumbnail_height)) = thumbnail {
+            Image::from_dynamic_image(&thumbnail).add_to_layer(
+                self.layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(MARGIN)),
+                    translate_y: Some(Mm(top - thumbnail_height)),
+                    dpi: Some(dpi),
+                    ..Default::default()
+                },
+            );
+        }
+        for (i, line) in lines.iter().enumerate() {
+            self.y -= 5.0;
+            self.layer.use_text(
+                line.as_str(),
+                10.0,
+                Mm(MARGIN + THUMBNAIL_WIDTH + 5.0),
+                Mm(self.y),
+                if i == 0 { &self.bold } else { &self.font },
+            );
+        }
+        self.y = top - height - 6.0;
+    }
+}
+
+/// Loads the middle output frame of a site, scaled down for embedding.
+fn thumbnail(output_path: &Path) -> Option<image::DynamicImage> {
+    let frames = utils::image_files(output_path).ok()?;
+    let frame = frames.get(frames.len() / 2)?;
+    let image = largeframe::open_scaled(frame, THUMBNAIL_PIXELS).ok()?;
+    Some(image::DynamicImage::ImageRgb8(image.to_rgb8()))
+}
+
+/// Writes `report` as a PDF at `path`, with a thumbnail and the coverage of every site.
+pub fn write_pdf(report: &BatchReport, path: &Path) -> Result<(), String> {
+    let mut writer = Writer::new(&report.title)?;
+    writer.text(&report.title, 18.0, MARGIN, true);
+    writer.line(&format!(
+        "Generated {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    ));
+
+    writer.heading("Settings");
+    for (name, value) in &report.settings {
+        writer.line(&format!("{}: {}", name, value));
+    }
+
+    writer.heading("Sites");
+    if report.sites.is_empty() {
+        writer.line("No sites in this batch.");
+    }
+    for site in &report.sites {
+        let (covered, days) = coverage(&site.source_path, site.date_range);
+        let frames = utils::image_files(&site.output_path).map_or(0, |frames| frames.len());
+        let mut lines = vec![
+            site.site.clone(),
+            format!("{} to {}", site.date_range.0, site.date_range.1),
+            format!("Status: {}", site.status),
+            format!("Frames: {}", frames),
+            format!(
+                "Coverage: {} of {} days ({:.0}%)",
+                covered,
+                days,
+                100.0 * covered as f64 / days.max(1) as f64
+            ),
+            format!("Missing days: {}", days.saturating_sub(covered)),
+            format!("Flagged frames: {}", site.flagged_frames.len()),
+        ];
+        if let Some(manifest) = &site.manifest_path {
+            lines.push(format!(
+                "Manifest: {}",
+                manifest.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            // Split in two, as a whole digest is wider than the column.
+            match utils::sha256(manifest) {
+                Ok(digest) => {
+                    let (first, second) = digest.split_at(digest.len() / 2);
+                    lines.push(format!("SHA-256: {}", first));
+                    lines.push(second.to_owned());
+                }
+                Err(e) => lines.push(format!("SHA-256: {}", e)),
+            }
+        }
+        writer.site(thumbnail(&site.output_path), &lines);
+        for frame in &site.flagged_frames {
+            writer.text(frame, 10.0, MARGIN + THUMBNAIL_WIDTH + 5.0, false);
+        }
+    }
+
+    writer.heading("Failures");
+    if report.failures.is_empty() {
+        writer.line("None.");
+    }
+    for (path, error) in &report.failures {
+        writer.text(path, 10.0, MARGIN, true);
+        writer.text(error, 10.0, MARGIN + 5.0, false);
+    }
+
+    let bytes = writer.document.save_to_bytes().map_err(|e| e.to_string())?;
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
+}
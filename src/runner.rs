@@ -0,0 +1,1036 @@
+use crate::archive;
+use crate::cache;
+use crate::cancel::{CancelToken, Cancelled};
+use crate::dates::{self, CameraClock, DateFormat};
+use crate::deposit::{self, Deposit, DepositSettings};
+use crate::export;
+use crate::gaps::{self, GapFilling};
+use crate::hooks::{HookJob, Hooks};
+use crate::integrity;
+use crate::joblog::JobLog;
+use crate::overlay;
+use crate::utils;
+use crate::video::{
+    self, Codec, CodecOptions, PreviewFormat, ProcessEnvironment, ProgressCallback,
+};
+use crate::xmp;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Letters spelled out in ASCII in file names.
+const TRANSLITERATIONS: [(&str, &str); 21] = [
+    ("ä", "ae"),
+    ("ö", "oe"),
+    ("ü", "ue"),
+    ("Ä", "Ae"),
+    ("Ö", "Oe"),
+    ("Ü", "Ue"),
+    ("ß", "ss"),
+    ("àáâãåā", "a"),
+    ("ÀÁÂÃÅĀ", "A"),
+    ("çćč", "c"),
+    ("ÇĆČ", "C"),
+    ("èéêëē", "e"),
+    ("ÈÉÊËĒ", "E"),
+    ("ìíîï", "i"),
+    ("ÌÍÎÏ", "I"),
+    ("ñ", "n"),
+    ("Ñ", "N"),
+    ("òóôõøō", "o"),
+    ("ÒÓÔÕØŌ", "O"),
+    ("ùúûū", "u"),
+    ("ÙÚÛŪ", "U"),
+];
+
+/// Turns a location or camera into a part of a file name that ffmpeg and every file system
+/// accept. Umlauts and accented letters are spelled out in ASCII, and spaces, slashes and any
+/// other characters besides letters, digits, `-`, `_` and `.` become a single `_`.
+pub fn sanitize(part: &str) -> String {
+    let mut sanitized = String::new();
+    for c in part.trim().trim_start_matches('.').chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+            sanitized.push(c);
+        } else if let Some((_, ascii)) = TRANSLITERATIONS
+            .iter()
+            .find(|(letters, _)| letters.contains(c))
+        {
+            sanitized.push_str(ascii);
+        } else if !sanitized.ends_with('_') {
+            sanitized.push('_');
+        }
+    }
+    sanitized
+}
+
+pub fn output_name(image_config: &tree_migration::Config) -> String {
+    sanitize(&image_config.location)
+        + "-"
+        + sanitize(&image_config.camera).as_str()
+        + "-"
+        + image_config.start_date.to_string().as_str()
+        + "-"
+        + image_config.end_date.to_string().as_str()
+}
+
+/// Returns `parent` joined with the output name of the job followed by `suffix`. Earlier
+/// versions did not sanitize locations and cameras in output names, so the path with the
+/// unsanitized name is returned instead if only that one exists.
+pub fn output_in(parent: &Path, image_config: &tree_migration::Config, suffix: &str) -> PathBuf {
+    let path = parent.join(output_name(image_config) + suffix);
+    let unsanitized = parent.join(format!(
+        "{}-{}-{}-{}{}",
+        image_config.location,
+        image_config.camera,
+        image_config.start_date,
+        image_config.end_date,
+        suffix
+    ));
+    if !path.exists() && unsanitized.exists() {
+        unsanitized
+    } else {
+        path
+    }
+}
+
+/// Template of `output_name`, the default name of videos.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{location}-{camera}-{start}-{end}";
+pub const NAME_PLACEHOLDERS: [&str; 5] = ["location", "camera", "start", "end", "fps"];
+
+/// Returns the first character of `name` that is not allowed in file names, if any.
+pub fn invalid_file_name_char(name: &str) -> Option<char> {
+    name.chars()
+        .find(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+}
+
+/// Checks that `template` only uses known placeholders and yields a valid file name.
+pub fn validate_name_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("The file name template is empty".to_owned());
+    }
+    if let Some(c) = invalid_file_name_char(template) {
+        return Err(format!("The file name template contains {}", c));
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            return Err("The file name template has an unclosed {".to_owned());
+        };
+        let placeholder = &rest[start + 1..start + end];
+        if !NAME_PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!("Unknown placeholder {{{}}}", placeholder));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    if rest.contains('}') {
+        return Err("The file name template has an unopened }".to_owned());
+    }
+    Ok(())
+}
+
+/// How the video of a job is named.
+#[derive(Clone)]
+pub struct VideoName {
+    pub template: String,
+    pub frame_rate: u32,
+    pub codec: Codec,
+    pub date_format: DateFormat,
+    /// Number of other jobs of the batch that come first with the same name, appended to the
+    /// name so that the videos do not overwrite each other.
+    pub collision: usize,
+}
+
+impl VideoName {
+    /// Returns the file name of the video of `image_config`, with the extension of the
+    /// container of the codec.
+    pub fn file_name(&self, image_config: &tree_migration::Config) -> String {
+        self.fill(
+            &image_config.location,
+            &image_config.camera,
+            &self.date_format.format(image_config.start_date),
+            &self.date_format.format(image_config.end_date),
+        )
+    }
+
+    /// Returns the file name of the video for the given placeholder values.
+    pub fn fill(&self, location: &str, camera: &str, start: &str, end: &str) -> String {
+        let mut name = self
+            .template
+            .replace("{location}", &sanitize(location))
+            .replace("{camera}", &sanitize(camera))
+            .replace("{start}", start)
+            .replace("{end}", end)
+            .replace("{fps}", &self.frame_rate.to_string());
+        if self.collision > 0 {
+            name += &format!("-{}", self.collision + 1);
+        }
+        name + "." + self.codec.extension()
+    }
+}
+
+/// Returns the path of the job's video.
+pub fn video_path(
+    image_config: &tree_migration::Config,
+    video_output_path: &Option<PathBuf>,
+    name: &VideoName,
+) -> PathBuf {
+    video_output_path
+        .as_ref()
+        .unwrap_or(&image_config.output_path)
+        .join(name.file_name(image_config))
+}
+
+/// Returns the low-resolution proxy rendered next to the job's video for review.
+pub fn proxy_path(
+    image_config: &tree_migration::Config,
+    video_output_path: &Option<PathBuf>,
+    name: &VideoName,
+) -> PathBuf {
+    let video = video_path(image_config, video_output_path, name);
+    let stem = video
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    video.with_file_name(format!("{}-proxy.mp4", stem))
+}
+
+/// Returns the preview animations that may sit next to `video`. A GIF video has no GIF
+/// preview.
+pub fn preview_paths(video: &Path) -> Vec<PathBuf> {
+    [PreviewFormat::Gif, PreviewFormat::Webp]
+        .iter()
+        .map(|format| video.with_extension(format.extension()))
+        .filter(|preview| preview != video)
+        .collect()
+}
+
+/// Lists the files produced by a job together with their path relative to the job's package.
+/// XMP sidecars are listed next to their images.
+fn job_outputs(
+    image_config: &tree_migration::Config,
+    video_output_path: &Option<PathBuf>,
+    name: &VideoName,
+) -> std::io::Result<Vec<(PathBuf, String)>> {
+    let mut outputs = Vec::new();
+    for image in utils::image_files(&image_config.output_path)? {
+        let name = format!("images/{}", image.file_name().unwrap().to_string_lossy());
+        let mut sidecar = image.clone().into_os_string();
+        sidecar.push(".xmp");
+        let sidecar = PathBuf::from(sidecar);
+        outputs.push((image, name.clone()));
+        if sidecar.exists() {
+            outputs.push((sidecar, name + ".xmp"));
+        }
+    }
+    let video = video_path(image_config, video_output_path, name);
+    let previews = preview_paths(&video);
+    for file in std::iter::once(video).chain(previews) {
+        if file.exists() {
+            let name = file.file_name().unwrap().to_string_lossy().to_string();
+            outputs.push((file, name));
+        }
+    }
+    Ok(outputs)
+}
+
+/// Describes a finished job in its archive: the site, the dates, the video settings, how long
+/// the stages took and what the job did not deliver.
+fn job_report(
+    image_config: &tree_migration::Config,
+    date_ranges: &[dates::DateRange],
+    settings: &JobSettings,
+    timings: &StageTimings,
+    shortfalls: &Shortfalls,
+    video: Option<&Path>,
+) -> String {
+    let report = serde_json::json!({
+        "location": image_config.location,
+        "camera": image_config.camera,
+        "start_date": image_config.start_date.to_string(),
+        "end_date": image_config.end_date.to_string(),
+        "date_ranges": date_ranges
+            .iter()
+            .map(|(start_date, end_date)| format!("{}/{}", start_date, end_date))
+            .collect::<Vec<_>>(),
+        "frames": utils::image_files(&image_config.output_path).map_or(0, |frames| frames.len()),
+        "excluded_frames": settings.excluded_frames.len(),
+        "video": video
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned()),
+        "video_codec": settings.video_codec.name(),
+        "frame_rate": settings.frame_rate,
+        "stage_seconds": timings
+            .iter()
+            .map(|(stage, duration)| (stage.to_string(), duration.as_secs_f64().into()))
+            .collect::<serde_json::Map<String, serde_json::Value>>(),
+        "video_error": shortfalls.video_error,
+        "warnings": shortfalls.warnings,
+        "created_at": chrono::Local::now().to_rfc3339(),
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// Returns the path of the checksum manifest of the job in its image output folder.
+pub fn manifest_path(image_config: &tree_migration::Config) -> PathBuf {
+    output_in(
+        &image_config.output_path,
+        image_config,
+        &format!("-{}", archive::MANIFEST_NAME),
+    )
+}
+
+/// Writes a SHA-256 manifest of the job outputs into the image output folder.
+/// Files outside of that folder are listed with their absolute path.
+fn write_manifest(
+    image_config: &tree_migration::Config,
+    video_output_path: &Option<PathBuf>,
+    name: &VideoName,
+) -> std::io::Result<PathBuf> {
+    let outputs = job_outputs(image_config, video_output_path, name)?
+        .into_iter()
+        .map(|(path, _)| {
+            let name = path
+                .strip_prefix(&image_config.output_path)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            (path, name)
+        })
+        .collect::<Vec<_>>();
+    let manifest_path = manifest_path(image_config);
+    std::fs::write(&manifest_path, archive::manifest(&outputs)?)?;
+    Ok(manifest_path)
+}
+
+/// Migrates every date range of a job into the job's output folder.
+/// Without date ranges the range of the config is used. Remaining ranges are
+/// skipped once the job is cancelled.
+async fn migrate(
+    image_config: &tree_migration::Config,
+    date_ranges: &[dates::DateRange],
+    is_forest_green_enabled: bool,
+    cancel: &CancelToken,
+) -> Result<(), tree_migration::Error> {
+    if date_ranges.is_empty() {
+        return tree_migration::run(image_config.clone(), is_forest_green_enabled).await;
+    }
+    for (start_date, end_date) in date_ranges {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let range_config = tree_migration::Config {
+            start_date: *start_date,
+            end_date: *end_date,
+            ..image_config.clone()
+        };
+        tree_migration::run(range_config, is_forest_green_enabled).await?;
+    }
+    Ok(())
+}
+
+/// Name of the job report in archives.
+const REPORT_NAME: &str = "report.json";
+
+/// Share of a job's progress taken by the migration, the rest is post-processing.
+const MIGRATION_SHARE: f32 = 0.7;
+/// Share of a job's progress taken by the video encode.
+const VIDEO_SHARE: f32 = 0.2;
+
+/// Reports the migration progress of a job by comparing the images in its output folder
+/// with its source images, until `is_migrated` is set.
+async fn watch_migration(
+    image_config: tree_migration::Config,
+    is_migrated: Arc<AtomicBool>,
+    progress: ProgressCallback,
+) {
+    let sources = utils::source_images(&image_config).map_or(0, |sources| sources.len());
+    while !is_migrated.load(Ordering::Relaxed) {
+        if let Ok(outputs) = utils::image_files(&image_config.output_path) {
+            let fraction = (outputs.len() as f32 / sources.max(1) as f32).min(1.0);
+            progress(MIGRATION_SHARE * fraction);
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Time taken by each stage of a job that ran, in order.
+pub type StageTimings = Vec<(&'static str, Duration)>;
+
+/// Describes the stage timings, e.g. "Migrate 12.3 s, Encode 4.1 s".
+pub fn describe_timings(timings: &StageTimings) -> String {
+    timings
+        .iter()
+        .map(|(stage, duration)| format!("{} {:.1} s", stage, duration.as_secs_f64()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes a per-stage timing breakdown of a job into the image output folder, together with
+/// the frame count and the machine it ran on, for attaching to performance reports.
+fn write_profile(
+    image_config: &tree_migration::Config,
+    timings: &StageTimings,
+) -> std::io::Result<PathBuf> {
+    let frames = utils::image_files(&image_config.output_path)?.len();
+    let total = timings
+        .iter()
+        .map(|(_, duration)| duration.as_secs_f64())
+        .sum::<f64>();
+    let mut profile = format!(
+        "Job: {}\nFrames: {}\nCPU cores: {}\nSystem: {} {}\n\n",
+        output_name(image_config),
+        frames,
+        std::thread::available_parallelism().map_or(1, |cores| cores.get()),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    profile += format!("{:<12}{:>10}{:>8}\n", "Stage", "Seconds", "Share").as_str();
+    for (stage, duration) in timings {
+        let seconds = duration.as_secs_f64();
+        profile += format!(
+            "{:<12}{:>10.2}{:>7.0}%\n",
+            stage,
+            seconds,
+            100.0 * seconds / total.max(f64::EPSILON)
+        )
+        .as_str();
+    }
+    profile += format!("{:<12}{:>10.2}\n", "Total", total).as_str();
+    let profile_path = image_config
+        .output_path
+        .join(output_name(image_config) + "-profile.txt");
+    std::fs::write(&profile_path, profile)?;
+    Ok(profile_path)
+}
+
+/// Number of jobs started by this process, for telling their intermediate files apart.
+static JOB_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a new folder for the intermediate files of a job, in the temporary folder of the
+/// settings or the image output folder. It is named after the job and an ID of its own, so
+/// that any jobs, also of the same site and dates, can run at the same time.
+fn job_temp_dir(image_config: &tree_migration::Config, settings: &JobSettings) -> PathBuf {
+    let job_id = format!(
+        "{}-{}",
+        std::process::id(),
+        JOB_COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    settings
+        .temp_path
+        .as_ref()
+        .unwrap_or(&image_config.output_path)
+        .join(format!(".{}-{}", output_name(image_config), job_id))
+}
+
+/// Snapshot of the settings a job runs with, taken when processing starts.
+pub struct JobSettings {
+    pub is_forest_green_enabled: bool,
+    /// Stamps the capture date onto the frames of the video.
+    pub is_date_overlay_enabled: bool,
+    /// Opens the video with a slate showing the job name, its dates and time-lapse speed.
+    pub is_title_slate_enabled: bool,
+    /// Fails the job when its video cannot be encoded, instead of only logging the error.
+    pub is_video_failure_fatal: bool,
+    /// Scans the source images before migrating and moves corrupt ones into quarantine.
+    pub is_source_quarantine_enabled: bool,
+    pub gap_filling: GapFilling,
+    pub is_xmp_enabled: bool,
+    pub sequence_export_path: Option<PathBuf>,
+    /// Set only if a video is encoded.
+    pub ffmpeg_path: Option<PathBuf>,
+    /// Renders only a proxy video after the migration, to be approved before the full video
+    /// is encoded and the later stages run.
+    pub is_review_proxy: bool,
+    /// Output frames left out of the video, the proxy and the preview.
+    pub excluded_frames: BTreeSet<PathBuf>,
+    /// Clock of the camera when the frames are ordered by their EXIF capture time instead of
+    /// their names.
+    pub capture_order_clock: Option<CameraClock>,
+    /// Config file of the job, handed to the hooks.
+    pub config_path: PathBuf,
+    pub hooks: Hooks,
+    pub video_codec: Codec,
+    pub codec_options: CodecOptions,
+    pub ffmpeg_environment: ProcessEnvironment,
+    pub video_output_path: Option<PathBuf>,
+    /// File name of the video, see `NAME_PLACEHOLDERS`.
+    pub video_name_template: String,
+    /// Format of the dates in the video name, the overlay and the slates.
+    pub date_format: DateFormat,
+    /// See `VideoName::collision`.
+    pub video_name_collision: usize,
+    pub frame_rate: u32,
+    pub target_duration: Option<u32>,
+    pub temp_path: Option<PathBuf>,
+    pub preview_format: Option<PreviewFormat>,
+    pub is_manifest_enabled: bool,
+    /// Writes a per-stage timing breakdown next to the images.
+    pub is_profiling_enabled: bool,
+    pub archive_output_path: Option<PathBuf>,
+    /// Deposits the archive to a repository, set only if an archive is packaged.
+    pub deposit: Option<DepositSettings>,
+}
+
+impl JobSettings {
+    pub fn video_name(&self) -> VideoName {
+        VideoName {
+            template: self.video_name_template.clone(),
+            frame_rate: self.frame_rate,
+            codec: self.video_codec,
+            date_format: self.date_format.clone(),
+            collision: self.video_name_collision,
+        }
+    }
+}
+
+/// What a finished job did not deliver because a stage failed without failing the job.
+#[derive(Clone, Default)]
+pub struct Shortfalls {
+    /// Error of the video encode, only the images were delivered.
+    pub video_error: Option<String>,
+    /// Error of the deposit, the archive is packaged but not uploaded.
+    pub deposit_error: Option<String>,
+    /// Errors of the other stages.
+    pub warnings: Vec<String>,
+}
+
+impl Shortfalls {
+    /// Logs the error of a stage and keeps it as a warning.
+    fn warn(&mut self, log: &JobLog, message: String) {
+        log.line(message.clone());
+        self.warnings.push(message);
+    }
+
+    /// Returns the result of a finished job, the most severe shortfall first.
+    pub fn label(&self) -> &'static str {
+        if self.video_error.is_some() {
+            "Images done, video failed"
+        } else if self.deposit_error.is_some() {
+            "Done, upload pending"
+        } else if !self.warnings.is_empty() {
+            "Done with warnings"
+        } else {
+            "Done"
+        }
+    }
+}
+
+/// Why post-processing stopped before its last stage.
+enum Stopped {
+    Cancelled,
+    /// A stage failed that fails the job.
+    Failed(String),
+}
+
+impl From<Cancelled> for Stopped {
+    fn from(_: Cancelled) -> Self {
+        Stopped::Cancelled
+    }
+}
+
+/// Runs the stages following the migration of a job, stopping between stages once the job
+/// is cancelled. Errors of individual stages are logged and do not fail the job, except for
+/// the video encode if `is_video_failure_fatal` is set.
+/// Returns the deposit of the archive and what the job did not deliver.
+fn post_process(
+    (image_config, date_ranges): (&tree_migration::Config, &[dates::DateRange]),
+    settings: &JobSettings,
+    temp_dir: &Path,
+    cancel: &CancelToken,
+    log: &JobLog,
+    progress: &ProgressCallback,
+    timings: &mut StageTimings,
+) -> Result<Delivery, Stopped> {
+    let mut shortfalls = Shortfalls::default();
+    let mut encoded_video = None;
+    progress(MIGRATION_SHARE);
+    cancel.check()?;
+    if settings.is_xmp_enabled {
+        let started = Instant::now();
+        let mut keywords = vec![image_config.location.clone(), image_config.camera.clone()];
+        if settings.is_forest_green_enabled {
+            keywords.push("Forest Green".to_owned());
+        }
+        if let Err(e) = xmp::write_sidecars(&image_config.output_path, &keywords) {
+            shortfalls.warn(log, format!("Error writing XMP sidecars {}", e));
+        }
+        timings.push(("XMP", started.elapsed()));
+    }
+
+    cancel.check()?;
+    if let Some(export_path) = &settings.sequence_export_path {
+        let started = Instant::now();
+        if let Err(e) = export::export_sequence(
+            &image_config.output_path,
+            &output_in(export_path, image_config, ""),
+        ) {
+            shortfalls.warn(log, format!("Error exporting image sequence {}", e));
+        }
+        timings.push(("Export", started.elapsed()));
+    }
+
+    let video_start = 1.0 - VIDEO_SHARE - (1.0 - MIGRATION_SHARE - VIDEO_SHARE) / 2.0;
+    progress(video_start);
+    cancel.check()?;
+    if let Some(ffmpeg_path) = &settings.ffmpeg_path {
+        let started = Instant::now();
+        let video = video_path(
+            image_config,
+            &settings.video_output_path,
+            &settings.video_name(),
+        );
+        let video_progress = progress.clone();
+        let context = video::EncodeContext {
+            temp_dir: Some(temp_dir.to_path_buf()),
+            cancel: cancel.clone(),
+            on_progress: Some(Arc::new(move |fraction| {
+                video_progress(video_start + VIDEO_SHARE * fraction)
+            })),
+            environment: settings.ffmpeg_environment.clone(),
+            log: log.clone(),
+        };
+        if !settings.ffmpeg_environment.is_empty() {
+            log.line(format!(
+                "Encoding {} with {}",
+                output_name(image_config),
+                settings.ffmpeg_environment.describe()
+            ));
+        }
+        let mut video_error = None;
+        match video_frames(image_config, settings) {
+            Ok(frames) => {
+                let gaps_dir =
+                    (settings.gap_filling != GapFilling::Skip).then(|| temp_dir.join("gaps"));
+                let mut filled = frames.clone();
+                if let Some(gaps_dir) = &gaps_dir {
+                    let started = Instant::now();
+                    match gaps::fill_gaps(
+                        &frames,
+                        date_ranges,
+                        settings.gap_filling,
+                        &settings.date_format,
+                        gaps_dir,
+                    ) {
+                        Ok(with_gaps) => filled = with_gaps,
+                        Err(e) => shortfalls.warn(log, format!("Error filling missing days {}", e)),
+                    }
+                    timings.push(("Fill gaps", started.elapsed()));
+                }
+                let (mut encoded, frame_rate) = match settings.target_duration {
+                    Some(seconds) => video::fit_to_duration(&filled, seconds),
+                    None => (filled, settings.frame_rate as f64),
+                };
+                let overlay_dir = settings
+                    .is_date_overlay_enabled
+                    .then(|| temp_dir.join("overlay"));
+                if let Some(overlay_dir) = &overlay_dir {
+                    let dates = overlay::frame_dates(
+                        &encoded,
+                        (image_config.start_date, image_config.end_date),
+                    );
+                    match overlay::stamp_frames(
+                        &encoded,
+                        &dates,
+                        &settings.date_format,
+                        overlay_dir,
+                    ) {
+                        Ok(stamped) => encoded = stamped,
+                        Err(e) => {
+                            shortfalls.warn(log, format!("Error overlaying capture dates {}", e))
+                        }
+                    }
+                }
+                let days = (image_config.end_date - image_config.start_date).num_days() + 1;
+                let speed = video::describe_speed(days, encoded.len(), frame_rate);
+                if let Some(speed) = &speed {
+                    log.line(format!(
+                        "Time-lapse speed of {}: {}",
+                        output_name(image_config),
+                        speed
+                    ));
+                }
+                if let (true, Some(first)) = (settings.is_title_slate_enabled, encoded.first()) {
+                    // Saved in the format of the frames, which ffmpeg expects not to change.
+                    let extension = first.extension().unwrap_or("png".as_ref());
+                    let slate = temp_dir.join("title").with_extension(extension);
+                    let lines = [
+                        Some(output_name(image_config)),
+                        Some(format!(
+                            "{} – {}",
+                            settings.date_format.format(image_config.start_date),
+                            settings.date_format.format(image_config.end_date)
+                        )),
+                        speed,
+                    ];
+                    let lines = lines.into_iter().flatten().collect::<Vec<_>>();
+                    match overlay::title_slate(first, &lines, &slate) {
+                        Ok(()) => {
+                            let count = (overlay::TITLE_SECONDS * frame_rate).ceil().max(1.0);
+                            encoded.splice(0..0, std::iter::repeat_n(slate, count as usize));
+                        }
+                        Err(e) => {
+                            shortfalls.warn(log, format!("Error rendering title slate {}", e))
+                        }
+                    }
+                }
+                let result = video::encode_frames(
+                    ffmpeg_path,
+                    &encoded,
+                    frame_rate,
+                    settings.video_codec,
+                    &settings.codec_options,
+                    &video,
+                    &context,
+                );
+                for dir in overlay_dir.iter().chain(&gaps_dir) {
+                    let _ = std::fs::remove_dir_all(dir);
+                }
+                match result {
+                    Ok(()) => {
+                        encoded_video = Some(video.clone());
+                        let preview = settings
+                            .preview_format
+                            .map(|format| (format, video.with_extension(format.extension())))
+                            .filter(|(_, preview)| preview != &video);
+                        if let Some((format, preview)) = preview {
+                            if let Err(e) = video::encode_preview(
+                                ffmpeg_path,
+                                &frames,
+                                format,
+                                &preview,
+                                &context,
+                            ) {
+                                shortfalls.warn(log, format!("Error encoding preview {}", e));
+                            }
+                        }
+                    }
+                    Err(e) => video_error = Some(e),
+                }
+            }
+            Err(e) => video_error = Some(e.to_string()),
+        }
+        timings.push(("Encode", started.elapsed()));
+        // A cancelled job's encode fails as ffmpeg is stopped, which is no failure of the video.
+        cancel.check()?;
+        if let Some(e) = video_error {
+            log.line(format!("Error encoding video {}", e));
+            if settings.is_video_failure_fatal {
+                return Err(Stopped::Failed(format!("Video encode failed: {}", e)));
+            }
+            shortfalls.video_error = Some(e);
+        }
+    }
+
+    progress(video_start + VIDEO_SHARE);
+    cancel.check()?;
+    if settings.is_manifest_enabled {
+        let started = Instant::now();
+        if let Err(e) = write_manifest(
+            image_config,
+            &settings.video_output_path,
+            &settings.video_name(),
+        ) {
+            shortfalls.warn(log, format!("Error writing manifest {}", e));
+        }
+        timings.push(("Manifest", started.elapsed()));
+    }
+
+    cancel.check()?;
+    let mut deposit = None;
+    if let Some(archive_path) = &settings.archive_output_path {
+        let started = Instant::now();
+        let archive = archive_path.join(output_name(image_config) + ".zip");
+        let report = temp_dir.join(REPORT_NAME);
+        let result = job_outputs(
+            image_config,
+            &settings.video_output_path,
+            &settings.video_name(),
+        )
+        .and_then(|mut outputs| {
+            let manifest = manifest_path(image_config);
+            if settings.is_manifest_enabled && manifest.exists() {
+                let name = manifest.file_name().unwrap().to_string_lossy().to_string();
+                outputs.push((manifest, name));
+            }
+            std::fs::create_dir_all(temp_dir)?;
+            std::fs::write(
+                &report,
+                job_report(
+                    image_config,
+                    date_ranges,
+                    settings,
+                    timings,
+                    &shortfalls,
+                    encoded_video.as_deref(),
+                ),
+            )?;
+            outputs.push((report.clone(), REPORT_NAME.to_owned()));
+            archive::package(&outputs, &archive)
+        });
+        let _ = std::fs::remove_file(&report);
+        timings.push(("Archive", started.elapsed()));
+        match (result, &settings.deposit) {
+            (Ok(_), Some(deposit_settings)) => {
+                cancel.check()?;
+                let started = Instant::now();
+                let metadata = deposit::Metadata {
+                    title: output_name(image_config),
+                    location: image_config.location.clone(),
+                    camera: image_config.camera.clone(),
+                    start_date: image_config.start_date,
+                    end_date: image_config.end_date,
+                };
+                match deposit::deposit(deposit_settings, &archive, &metadata) {
+                    Ok(archive_deposit) => deposit = Some(archive_deposit),
+                    Err(e) => {
+                        log.line(format!("Error depositing archive {}", e));
+                        shortfalls.deposit_error = Some(e.to_string());
+                    }
+                }
+                timings.push(("Deposit", started.elapsed()));
+            }
+            (Ok(_), None) => {}
+            (Err(e), _) => shortfalls.warn(log, format!("Error packaging archive {}", e)),
+        }
+    }
+    Ok(Delivery {
+        video: encoded_video,
+        deposit,
+        shortfalls,
+    })
+}
+
+/// Returns the output frames of a job that go into its videos.
+fn video_frames(
+    image_config: &tree_migration::Config,
+    settings: &JobSettings,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut frames = utils::image_files(&image_config.output_path)?;
+    frames.retain(|frame| !settings.excluded_frames.contains(frame));
+    if let Some(clock) = settings.capture_order_clock {
+        utils::sort_by_capture_time(&mut frames, clock);
+    }
+    Ok(frames)
+}
+
+/// Renders the proxy of a migrated job for review and returns its path.
+fn render_proxy(
+    image_config: &tree_migration::Config,
+    settings: &JobSettings,
+    temp_dir: &Path,
+    cancel: &CancelToken,
+    log: &JobLog,
+    progress: &ProgressCallback,
+) -> Result<PathBuf, Stopped> {
+    progress(MIGRATION_SHARE);
+    cancel.check()?;
+    let ffmpeg_path = settings
+        .ffmpeg_path
+        .as_ref()
+        .ok_or(Stopped::Failed("Review proxies need ffmpeg".to_owned()))?;
+    let proxy = proxy_path(
+        image_config,
+        &settings.video_output_path,
+        &settings.video_name(),
+    );
+    let frames = video_frames(image_config, settings)
+        .map_err(|e| Stopped::Failed(format!("Proxy render failed: {}", e)))?;
+    let proxy_progress = progress.clone();
+    let context = video::EncodeContext {
+        temp_dir: Some(temp_dir.to_path_buf()),
+        cancel: cancel.clone(),
+        on_progress: Some(Arc::new(move |fraction| {
+            proxy_progress(MIGRATION_SHARE + (1.0 - MIGRATION_SHARE) * fraction)
+        })),
+        environment: settings.ffmpeg_environment.clone(),
+        log: log.clone(),
+    };
+    match video::encode_proxy(
+        ffmpeg_path,
+        &frames,
+        settings.frame_rate as f64,
+        &proxy,
+        &context,
+    ) {
+        Ok(()) => Ok(proxy),
+        Err(_) if cancel.is_cancelled() => Err(Stopped::Cancelled),
+        Err(e) => {
+            log.line(format!("Error rendering proxy {}", e));
+            Err(Stopped::Failed(format!("Proxy render failed: {}", e)))
+        }
+    }
+}
+
+/// Moves the source images of a job that fail the integrity check into quarantine.
+fn quarantine_sources(image_config: &tree_migration::Config, log: &JobLog) {
+    let problems = match utils::source_images(image_config) {
+        Ok(images) => integrity::scan(&images),
+        Err(e) => {
+            log.line(format!("Error scanning source images {}", e));
+            return;
+        }
+    };
+    if problems.is_empty() {
+        return;
+    }
+    match integrity::quarantine(&image_config.input_path, &problems) {
+        Ok(_) => {
+            for (image, reason) in &problems {
+                log.line(format!("Quarantined {}: {}", image.display(), reason));
+            }
+        }
+        Err(e) => log.line(format!("Error quarantining source images {}", e)),
+    }
+}
+
+/// What a job that finished delivered.
+pub struct Delivery {
+    /// The video as it was written, which later changes of the settings do not move.
+    pub video: Option<PathBuf>,
+    /// The deposit of the archive if it was deposited.
+    pub deposit: Option<Deposit>,
+    /// What was not delivered.
+    pub shortfalls: Shortfalls,
+}
+
+/// How a job ended.
+pub enum Outcome {
+    Done(Delivery),
+    Cancelled,
+    Failed(tree_migration::Error),
+    /// A stage after the migration failed the job.
+    StageFailed(String),
+    /// The proxy at the path awaits review before the full render.
+    ProxyReady(PathBuf),
+}
+
+/// Migrates a job and runs its post-processing stages, reporting progress from 0 to 1 and
+/// errors to `log`. Returns how the job ended together with the time taken by the stages
+/// that ran.
+pub async fn run_job(
+    image_config: &tree_migration::Config,
+    date_ranges: &[dates::DateRange],
+    settings: &JobSettings,
+    cancel: &CancelToken,
+    log: &JobLog,
+    progress: ProgressCallback,
+) -> (Outcome, StageTimings) {
+    let mut timings = StageTimings::new();
+    let is_migrated = Arc::new(AtomicBool::new(false));
+    tokio::spawn(watch_migration(
+        image_config.clone(),
+        is_migrated.clone(),
+        progress.clone(),
+    ));
+    if settings.is_source_quarantine_enabled {
+        let started = Instant::now();
+        quarantine_sources(image_config, log);
+        timings.push(("Scan", started.elapsed()));
+    }
+    let started = Instant::now();
+    let cache_key = match cache::key(image_config, date_ranges, settings.is_forest_green_enabled) {
+        Ok(key) => Some(key),
+        Err(e) => {
+            log.line(format!("Error computing the frame cache key {}", e));
+            None
+        }
+    };
+    let result = match &cache_key {
+        Some(key) if cache::is_fresh(&image_config.output_path, key) => {
+            timings.push(("Cached frames", started.elapsed()));
+            Ok(())
+        }
+        _ => {
+            let result = tokio::select! {
+                result = migrate(
+                    image_config,
+                    date_ranges,
+                    settings.is_forest_green_enabled,
+                    cancel,
+                ) => result,
+                _ = cancel.cancelled() => {
+                    is_migrated.store(true, Ordering::Relaxed);
+                    timings.push(("Migrate", started.elapsed()));
+                    return (Outcome::Cancelled, timings);
+                }
+            };
+            if let (Ok(_), Some(key)) = (&result, &cache_key) {
+                if let Err(e) = cache::store(&image_config.output_path, key) {
+                    log.line(format!("Error storing the frame cache {}", e));
+                }
+            }
+            timings.push(("Migrate", started.elapsed()));
+            result
+        }
+    };
+    is_migrated.store(true, Ordering::Relaxed);
+    let temp_dir = job_temp_dir(image_config, settings);
+    let mut outcome = match result {
+        Ok(_) if settings.is_review_proxy => match tokio::task::block_in_place(|| {
+            render_proxy(image_config, settings, &temp_dir, cancel, log, &progress)
+        }) {
+            Ok(proxy) => Outcome::ProxyReady(proxy),
+            Err(Stopped::Cancelled) => Outcome::Cancelled,
+            Err(Stopped::Failed(e)) => Outcome::StageFailed(e),
+        },
+        Ok(_) => match tokio::task::block_in_place(|| {
+            post_process(
+                (image_config, date_ranges),
+                settings,
+                &temp_dir,
+                cancel,
+                log,
+                &progress,
+                &mut timings,
+            )
+        }) {
+            Ok(delivery) => Outcome::Done(delivery),
+            Err(Stopped::Cancelled) => Outcome::Cancelled,
+            Err(Stopped::Failed(e)) => Outcome::StageFailed(e),
+        },
+        Err(e) => {
+            log.line(format!("Error migrating {}", e));
+            Outcome::Failed(e)
+        }
+    };
+    let is_success = match &outcome {
+        Outcome::Done(..) => Some(true),
+        Outcome::Failed(_) | Outcome::StageFailed(_) => Some(false),
+        Outcome::Cancelled | Outcome::ProxyReady(_) => None,
+    };
+    if let Some(is_success) = is_success {
+        let video = match &outcome {
+            Outcome::Done(delivery) => delivery.video.clone(),
+            _ => None,
+        };
+        let job = HookJob {
+            config_path: &settings.config_path,
+            output_path: &image_config.output_path,
+            video_path: video.as_deref(),
+            is_success,
+        };
+        if let Err(e) = tokio::task::block_in_place(|| {
+            settings
+                .hooks
+                .run(&job, &settings.ffmpeg_environment, cancel, log)
+        }) {
+            match &mut outcome {
+                Outcome::Done(delivery) => delivery.shortfalls.warn(log, e),
+                _ => log.line(e),
+            }
+        }
+    }
+    if temp_dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_dir) {
+            log.line(format!("Error removing {} {}", temp_dir.display(), e));
+        }
+    }
+    if settings.is_profiling_enabled && !matches!(outcome, Outcome::Failed(_)) {
+        if let Err(e) = write_profile(image_config, &timings) {
+            log.line(format!("Error writing profile {}", e));
+        }
+    }
+    (outcome, timings)
+}
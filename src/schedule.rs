@@ -0,0 +1,57 @@
+use crate::dates::DateRange;
+use chrono::{Datelike, Months, NaiveDate};
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Cadence {
+    Monthly,
+    Quarterly,
+}
+
+impl Cadence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Cadence::Monthly => "Monthly",
+            Cadence::Quarterly => "Quarterly",
+        }
+    }
+
+    fn months(&self) -> u32 {
+        match self {
+            Cadence::Monthly => 1,
+            Cadence::Quarterly => 3,
+        }
+    }
+
+    /// Returns the calendar period containing `date`.
+    fn period(&self, date: NaiveDate) -> Option<DateRange> {
+        let month = date.month0() / self.months() * self.months() + 1;
+        let start_date = NaiveDate::from_ymd_opt(date.year(), month, 1)?;
+        let end_date = start_date
+            .checked_add_months(Months::new(self.months()))?
+            .pred_opt()?;
+        Some((start_date, end_date))
+    }
+}
+
+/// Returns the periods that completed after `last_rendered` and before `today`, oldest first.
+pub fn catch_up_periods(
+    cadence: Cadence,
+    last_rendered: NaiveDate,
+    today: NaiveDate,
+) -> Vec<DateRange> {
+    let mut periods = Vec::new();
+    let Some(mut date) = last_rendered.succ_opt() else {
+        return periods;
+    };
+    while let Some((start_date, end_date)) = cadence.period(date) {
+        if end_date >= today {
+            break;
+        }
+        periods.push((start_date.max(date), end_date));
+        match end_date.succ_opt() {
+            Some(next) => date = next,
+            None => break,
+        }
+    }
+    periods
+}
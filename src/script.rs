@@ -0,0 +1,106 @@
+use crate::batch::JobOverrides;
+use crate::utils;
+use crate::video::Codec;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::path::{Path, PathBuf};
+
+/// Operations after which a script is stopped, so that an endless loop does not hang the app.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// What a script changed about a job.
+pub struct ScriptResult {
+    pub output_path: PathBuf,
+    pub overrides: JobOverrides,
+}
+
+/// Rhai script run for every job when the batch starts. It gets a `job` map with the fields
+/// `location`, `camera`, `start_date`, `end_date`, `input_path` and `image_count`, which are
+/// read-only, and `output_path`, `codec` and `frame_rate`, which it may change, e.g.
+/// ```rhai
+/// if job.location.starts_with("N-") {
+///     job.output_path = "/archive/north/" + job.location;
+/// }
+/// if job.image_count > 2000 {
+///     job.frame_rate = 12;
+/// }
+/// ```
+pub struct JobScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl JobScript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| e.to_string())?;
+        Ok(Self { engine, ast })
+    }
+
+    /// Runs the script for a job whose codec and frame rate are `video_codec` and
+    /// `frame_rate` unless overridden.
+    pub fn apply(
+        &self,
+        image_config: &tree_migration::Config,
+        overrides: &JobOverrides,
+        video_codec: Codec,
+        frame_rate: u32,
+    ) -> Result<ScriptResult, String> {
+        let video_codec = overrides.video_codec.unwrap_or(video_codec);
+        let frame_rate = overrides.frame_rate.unwrap_or(frame_rate);
+        let image_count = utils::source_images(image_config).map_or(0, |images| images.len());
+        let mut job = Map::new();
+        let path = |path: &Path| Dynamic::from(path.display().to_string());
+        job.insert("location".into(), image_config.location.clone().into());
+        job.insert("camera".into(), image_config.camera.clone().into());
+        job.insert(
+            "start_date".into(),
+            image_config.start_date.to_string().into(),
+        );
+        job.insert("end_date".into(), image_config.end_date.to_string().into());
+        job.insert("input_path".into(), path(&image_config.input_path));
+        job.insert("image_count".into(), (image_count as i64).into());
+        job.insert("output_path".into(), path(&image_config.output_path));
+        job.insert("codec".into(), video_codec.name().into());
+        job.insert("frame_rate".into(), (frame_rate as i64).into());
+
+        let mut scope = Scope::new();
+        scope.push("job", job);
+        self.engine
+            .run_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| e.to_string())?;
+        let job = scope
+            .get_value::<Map>("job")
+            .ok_or("The script replaced `job`".to_owned())?;
+
+        let text = |name: &str| {
+            job.get(name)
+                .and_then(|value| value.clone().into_string().ok())
+                .ok_or(format!("`job.{}` must be a string", name))
+        };
+        let output_path = PathBuf::from(text("output_path")?);
+        let codec_name = text("codec")?;
+        let new_codec =
+            Codec::from_name(&codec_name).ok_or(format!("Unknown codec {}", codec_name))?;
+        let new_frame_rate = job
+            .get("frame_rate")
+            .and_then(|value| value.as_int().ok())
+            .and_then(|rate| u32::try_from(rate).ok())
+            .filter(|rate| *rate > 0)
+            .ok_or("`job.frame_rate` must be a positive integer".to_owned())?;
+        Ok(ScriptResult {
+            output_path,
+            overrides: JobOverrides {
+                video_codec: (new_codec != video_codec)
+                    .then_some(new_codec)
+                    .or(overrides.video_codec),
+                frame_rate: (new_frame_rate != frame_rate)
+                    .then_some(new_frame_rate)
+                    .or(overrides.frame_rate),
+                camera_clock: overrides.camera_clock,
+            },
+        })
+    }
+}
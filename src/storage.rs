@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+/// File in the default storage folder pointing to a custom app data folder.
+const DATA_DIR_POINTER: &str = "data_dir.txt";
+const SETTINGS_NAME: &str = "settings.json";
+
+/// Returns the folder eframe persists the app state in.
+pub fn default_dir() -> Option<PathBuf> {
+    eframe::storage_dir(crate::APP_NAME)
+}
+
+/// Returns the custom app data folder set in the settings, if any.
+pub fn custom_data_dir() -> Option<PathBuf> {
+    let pointer = default_dir()?.join(DATA_DIR_POINTER);
+    let path = std::fs::read_to_string(pointer).ok()?;
+    let path = path.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Sets the app data folder used from the next launch on, or the default one for `None`.
+pub fn set_custom_data_dir(path: Option<&Path>) -> std::io::Result<()> {
+    let dir = default_dir().ok_or(std::io::Error::other("No default storage folder"))?;
+    let pointer = dir.join(DATA_DIR_POINTER);
+    match path {
+        Some(path) => {
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(pointer, path.display().to_string())
+        }
+        None if pointer.exists() => std::fs::remove_file(pointer),
+        None => Ok(()),
+    }
+}
+
+/// Loads the settings persisted in a custom app data folder.
+pub fn load_settings<T: serde::de::DeserializeOwned>(data_dir: &Path) -> Option<T> {
+    let contents = std::fs::read_to_string(data_dir.join(SETTINGS_NAME)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Saves the settings into a custom app data folder, replacing the previous file atomically.
+pub fn save_settings<T: serde::Serialize>(data_dir: &Path, settings: &T) -> std::io::Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    let contents = serde_json::to_string_pretty(settings).map_err(std::io::Error::other)?;
+    let path = data_dir.join(SETTINGS_NAME);
+    let temporary = path.with_extension("json.tmp");
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(temporary, path)
+}
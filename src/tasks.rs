@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// Time given to running work to stop when the app shuts down.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runtime owned by the app. It runs the jobs, keeping a handle per job so that panics are
+/// surfaced, and the blocking background work of the UI.
+pub struct TaskRuntime {
+    /// Taken on shutdown.
+    runtime: Option<Runtime>,
+    jobs: HashMap<PathBuf, JoinHandle<()>>,
+}
+
+impl Default for TaskRuntime {
+    fn default() -> Self {
+        Self {
+            runtime: Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .expect("Cannot start the task runtime"),
+            ),
+            jobs: HashMap::new(),
+        }
+    }
+}
+
+impl TaskRuntime {
+    fn runtime(&self) -> &Runtime {
+        self.runtime.as_ref().expect("Task runtime is shut down")
+    }
+
+    /// Runs the job of the config file at `path`.
+    pub fn spawn_job(&mut self, path: PathBuf, job: impl Future<Output = ()> + Send + 'static) {
+        let handle = self.runtime().spawn(job);
+        self.jobs.insert(path, handle);
+    }
+
+    /// Runs blocking work such as verifying or cleaning up outputs.
+    pub fn spawn_blocking(&self, work: impl FnOnce() + Send + 'static) {
+        self.runtime().spawn_blocking(work);
+    }
+
+    /// Runs a future to completion on the runtime, for use outside of the UI.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime().block_on(future)
+    }
+
+    /// Forgets the finished jobs and returns the message of those that panicked.
+    pub fn reap(&mut self) -> Vec<(PathBuf, String)> {
+        let finished = self
+            .jobs
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        let mut panics = Vec::new();
+        for path in finished {
+            let Some(handle) = self.jobs.remove(&path) else {
+                continue;
+            };
+            if let Err(e) = self.runtime().block_on(handle) {
+                if e.is_panic() {
+                    panics.push((path, panic_message(e.into_panic())));
+                }
+            }
+        }
+        panics
+    }
+
+    /// Waits up to `SHUTDOWN_TIMEOUT` for running work, which should have been cancelled.
+    pub fn shutdown(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+        }
+    }
+}
+
+impl Drop for TaskRuntime {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|_| "Unknown panic".to_owned()),
+    }
+}
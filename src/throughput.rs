@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Span of the samples the rate is averaged over.
+const WINDOW: Duration = Duration::from_secs(60);
+/// Time between two samples.
+const INTERVAL: Duration = Duration::from_secs(1);
+/// Span of samples needed before the rate is trusted.
+const MIN_SPAN: Duration = Duration::from_secs(5);
+
+/// Rolling average of the progress of a job or a batch, from 0 to 1, over the last minute.
+#[derive(Default)]
+pub struct Throughput {
+    samples: VecDeque<(Instant, f32)>,
+}
+
+impl Throughput {
+    /// Records the current progress unless the last sample is more recent than `INTERVAL`.
+    pub fn record(&mut self, progress: f32) {
+        let now = Instant::now();
+        if self
+            .samples
+            .back()
+            .is_some_and(|(sampled_at, _)| now.duration_since(*sampled_at) < INTERVAL)
+        {
+            return;
+        }
+        self.samples.push_back((now, progress));
+        while self
+            .samples
+            .front()
+            .is_some_and(|(sampled_at, _)| now.duration_since(*sampled_at) > WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Returns the progress per second, once the samples span a few seconds.
+    pub fn rate(&self) -> Option<f32> {
+        let ((first_at, first), (last_at, last)) = (self.samples.front()?, self.samples.back()?);
+        let span = last_at.duration_since(*first_at);
+        (span >= MIN_SPAN && last > first).then(|| (last - first) / span.as_secs_f32())
+    }
+
+    /// Returns the time until the progress reaches 1 at the current rate.
+    pub fn remaining(&self) -> Option<Duration> {
+        let (_, progress) = self.samples.back()?;
+        Some(Duration::from_secs_f32(
+            (1.0 - progress).max(0.0) / self.rate()?,
+        ))
+    }
+}
+
+/// Estimates when each of `queued` waiting jobs starts, given the time left of the `running`
+/// jobs, the number of `slots` jobs run in and the expected duration of a job. Waiting jobs
+/// start in order, each in the slot that frees up first.
+pub fn start_times(
+    running: &[Duration],
+    slots: usize,
+    job_duration: Duration,
+    queued: usize,
+) -> Vec<Duration> {
+    let mut free_at = running.to_vec();
+    free_at.resize(slots.max(running.len()).max(1), Duration::ZERO);
+    (0..queued)
+        .map(|_| {
+            let (slot, start) = free_at
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by_key(|(_, free_at)| *free_at)
+                .unwrap_or_default();
+            free_at[slot] = start + job_duration;
+            start
+        })
+        .collect()
+}
+
+/// Describes an estimated duration, e.g. "1 h 5 min", "12 min" or "40 s".
+pub fn describe(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{} h {} min", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{} min", seconds / 60)
+    } else {
+        format!("{} s", seconds)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::cancel::CancelToken;
+use crate::joblog::JobLog;
+use crate::video::{self, Codec, CodecOptions};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const VIDEO_EXTENSIONS: [&str; 5] = ["mov", "mp4", "m4v", "mkv", "avi"];
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Container {
+    Mov,
+    Mp4,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TranscodeSettings {
+    pub codec: Codec,
+    pub container: Container,
+    pub height: Option<u32>,
+    /// Folder the transcoded videos are written to, next to their source if not set.
+    pub output_path: Option<PathBuf>,
+}
+
+impl Default for TranscodeSettings {
+    fn default() -> Self {
+        Self {
+            codec: Codec::H264,
+            container: Container::Mp4,
+            height: Some(1080),
+            output_path: None,
+        }
+    }
+}
+
+pub fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Returns the path of the transcoded video, `<name>-<codec>.<container>` in `output_dir`
+/// or next to the input.
+pub fn output_path(
+    input: &Path,
+    output_dir: Option<&Path>,
+    settings: &TranscodeSettings,
+) -> PathBuf {
+    let name = format!(
+        "{}-{}.{}",
+        input.file_stem().unwrap_or_default().to_string_lossy(),
+        match settings.codec {
+            Codec::H264 => "h264",
+            Codec::H265 => "hevc",
+            Codec::ProRes => "prores",
+            Codec::Vp9 => "vp9",
+            Codec::Gif => "gif",
+            Codec::None => "copy",
+        },
+        match settings.container {
+            Container::Mov => "mov",
+            Container::Mp4 => "mp4",
+        }
+    );
+    output_dir
+        .or(input.parent())
+        .unwrap_or(Path::new("."))
+        .join(name)
+}
+
+pub fn transcode(
+    ffmpeg_path: &Path,
+    input: &Path,
+    output: &Path,
+    settings: &TranscodeSettings,
+    options: &CodecOptions,
+) -> Result<(), String> {
+    if settings.codec == Codec::ProRes && settings.container == Container::Mp4 {
+        return Err("ProRes requires a MOV container".to_owned());
+    }
+    if settings.codec != Codec::None && !Codec::TRANSCODERS.contains(&settings.codec) {
+        return Err(format!("Cannot transcode to {}", settings.codec.label()));
+    }
+    options.validate(settings.codec)?;
+    let mut command = Command::new(ffmpeg_path);
+    command
+        .args(["-y", "-v", "error", "-i"])
+        .arg(input)
+        .arg("-an")
+        .args(options.args(settings.codec));
+    if let Some(height) = settings.height {
+        command.args(["-vf", format!("scale=-2:{}", height).as_str()]);
+    }
+    command.arg(output);
+    video::run_ffmpeg(command, &CancelToken::default(), &JobLog::default(), None)
+}
@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use sysinfo::{ComponentExt, CpuExt, PidExt, ProcessExt, ProcessStatus, System, SystemExt};
+
+/// Time between two samples, CPU usage is averaged over it.
+pub const INTERVAL: Duration = Duration::from_secs(1);
+/// Samples the sustained CPU load is averaged over, a minute at one sample per `INTERVAL`.
+const LOAD_SAMPLES: usize = 60;
+
+#[derive(Clone, Copy)]
+pub struct ProcessUsage {
+    /// Percentage of one core.
+    pub cpu: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+}
+
+impl ProcessUsage {
+    pub fn describe(&self) -> String {
+        format!(
+            "CPU {:.0}%, RSS {:.0} MB",
+            self.cpu,
+            self.memory as f64 / 1_000_000.0
+        )
+    }
+}
+
+/// Samples the resource usage of the app and of the processes it spawned.
+#[derive(Default)]
+pub struct UsageMonitor {
+    system: System,
+    sampled_at: Option<Instant>,
+    app: Option<ProcessUsage>,
+    /// Command line and usage of every child process.
+    children: Vec<(Vec<String>, ProcessUsage)>,
+    /// PIDs of the child processes that exited without being reaped.
+    zombies: Vec<u32>,
+}
+
+impl UsageMonitor {
+    /// Takes a new sample if the last one is older than `INTERVAL`.
+    pub fn sample(&mut self) {
+        if self
+            .sampled_at
+            .is_some_and(|sampled_at| sampled_at.elapsed() < INTERVAL)
+        {
+            return;
+        }
+        self.sampled_at = Some(Instant::now());
+        self.system.refresh_processes();
+        let Ok(pid) = sysinfo::get_current_pid() else {
+            return;
+        };
+        let usage = |process: &sysinfo::Process| ProcessUsage {
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+        };
+        self.app = self.system.process(pid).map(usage);
+        let children = self
+            .system
+            .processes()
+            .values()
+            .filter(|process| process.parent() == Some(pid))
+            .collect::<Vec<_>>();
+        self.zombies = children
+            .iter()
+            .filter(|process| process.status() == ProcessStatus::Zombie)
+            .map(|process| process.pid().as_u32())
+            .collect();
+        self.children = children
+            .iter()
+            .map(|process| (process.cmd().to_vec(), usage(process)))
+            .collect();
+    }
+
+    /// Returns the usage of the app process, which runs the migrations of all jobs.
+    pub fn app(&self) -> Option<ProcessUsage> {
+        self.app
+    }
+
+    /// Checks whether the child process `pid` exited without being reaped.
+    pub fn is_zombie(&self, pid: u32) -> bool {
+        self.zombies.contains(&pid)
+    }
+
+    /// Returns the summed usage of the child processes with an argument containing `name`.
+    pub fn children_with(&self, name: &str) -> Option<ProcessUsage> {
+        self.children
+            .iter()
+            .filter(|(cmd, _)| cmd.iter().any(|arg| arg.contains(name)))
+            .map(|(_, usage)| *usage)
+            .reduce(|total, usage| ProcessUsage {
+                cpu: total.cpu + usage.cpu,
+                memory: total.memory + usage.memory,
+            })
+    }
+}
+
+/// When to run fewer jobs at once, so that laptops do not overheat.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ThrottleSettings {
+    pub is_enabled: bool,
+    /// Hottest sensor reading in °C above which the batch is throttled.
+    pub max_temperature: f32,
+    /// CPU usage of all cores in percent, averaged over a minute, above which the batch is
+    /// throttled.
+    pub max_cpu: f32,
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            max_temperature: 85.0,
+            max_cpu: 95.0,
+        }
+    }
+}
+
+impl ThrottleSettings {
+    /// Returns the reason to throttle the batch, if the machine runs too hot or too busy.
+    pub fn reason(&self, load: &LoadMonitor) -> Option<String> {
+        if !self.is_enabled {
+            return None;
+        }
+        if let Some(temperature) = load.temperature().filter(|t| *t > self.max_temperature) {
+            return Some(format!("CPU at {:.0} °C", temperature));
+        }
+        load.sustained_cpu()
+            .filter(|cpu| *cpu > self.max_cpu)
+            .map(|cpu| format!("CPU load at {:.0}% for a minute", cpu))
+    }
+
+    /// Returns how many of `max_jobs` may run at once: half of them, at least one, while the
+    /// batch is throttled.
+    pub fn parallel_jobs(&self, max_jobs: usize, is_throttled: bool) -> usize {
+        if is_throttled {
+            (max_jobs / 2).max(1)
+        } else {
+            max_jobs.max(1)
+        }
+    }
+}
+
+/// Samples the CPU load and temperature of the machine.
+#[derive(Default)]
+pub struct LoadMonitor {
+    system: System,
+    sampled_at: Option<Instant>,
+    cpu_samples: VecDeque<f32>,
+    temperature: Option<f32>,
+}
+
+impl LoadMonitor {
+    /// Takes a new sample if the last one is older than `INTERVAL`.
+    pub fn sample(&mut self) {
+        if self
+            .sampled_at
+            .is_some_and(|sampled_at| sampled_at.elapsed() < INTERVAL)
+        {
+            return;
+        }
+        self.sampled_at = Some(Instant::now());
+        self.system.refresh_cpu();
+        if self.system.components().is_empty() {
+            self.system.refresh_components_list();
+        } else {
+            self.system.refresh_components();
+        }
+        self.cpu_samples
+            .push_back(self.system.global_cpu_info().cpu_usage());
+        if self.cpu_samples.len() > LOAD_SAMPLES {
+            self.cpu_samples.pop_front();
+        }
+        self.temperature = self
+            .system
+            .components()
+            .iter()
+            .map(|component| component.temperature())
+            .filter(|temperature| temperature.is_finite() && *temperature > 0.0)
+            .reduce(f32::max);
+    }
+
+    /// Returns the hottest sensor reading in °C, if the machine reports any.
+    pub fn temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    /// Returns the CPU usage of all cores in percent averaged over the last minute, once a
+    /// minute was sampled.
+    pub fn sustained_cpu(&self) -> Option<f32> {
+        (self.cpu_samples.len() >= LOAD_SAMPLES)
+            .then(|| self.cpu_samples.iter().sum::<f32>() / self.cpu_samples.len() as f32)
+    }
+}
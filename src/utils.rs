@@ -0,0 +1,140 @@
+use crate::dates::CameraClock;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "tif", "tiff"];
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Returns the image files directly inside `dir`, sorted by file name.
+pub fn image_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && is_image(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns the font of the UI, for drawing text onto frames.
+pub fn font() -> Option<rusttype::Font<'static>> {
+    egui::FontDefinitions::default()
+        .font_data
+        .remove("Ubuntu-Light")
+        .and_then(|font_data| rusttype::Font::try_from_vec(font_data.font.into_owned()))
+}
+
+/// Returns the hex encoded SHA-256 digest of the file at `path`.
+pub fn sha256(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut file = std::fs::File::open(path)?;
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Returns the hex encoded SHA-256 digest of the parsed config, so configs with the same
+/// settings hash alike wherever they are stored.
+pub fn config_hash(config: &tree_migration::Config) -> Option<String> {
+    let contents = serde_json::to_vec(config).ok()?;
+    Some(format!("{:x}", Sha256::digest(contents)))
+}
+
+/// Parses the capture date from a file name containing `YYYYMMDD`, `YYYY-MM-DD` or `YYYY_MM_DD`.
+pub fn frame_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_stem()?.to_string_lossy();
+    let parts = name
+        .split(|c: char| !c.is_ascii_digit() && c != '-' && c != '_')
+        .map(|part| part.replace(['-', '_'], ""));
+    for part in parts {
+        for start in 0..part.len().saturating_sub(7) {
+            if let Ok(date) = NaiveDate::parse_from_str(&part[start..start + 8], "%Y%m%d") {
+                return Some(date);
+            }
+        }
+    }
+    None
+}
+
+pub fn read_exif(path: &Path) -> Option<exif::Exif> {
+    let file = std::fs::File::open(path).ok()?;
+    exif::Reader::new()
+        .read_from_container(&mut std::io::BufReader::new(file))
+        .ok()
+}
+
+pub fn exif_ascii(exif: &exif::Exif, tag: exif::Tag) -> Option<Vec<u8>> {
+    match &exif.get_field(tag, exif::In::PRIMARY)?.value {
+        exif::Value::Ascii(values) => values.first().cloned(),
+        _ => None,
+    }
+}
+
+/// Returns the `DateTimeOriginal` capture time of an image.
+pub fn exif_time(exif: &exif::Exif) -> Option<NaiveDateTime> {
+    let time = exif::DateTime::from_ascii(&exif_ascii(exif, exif::Tag::DateTimeOriginal)?).ok()?;
+    NaiveDate::from_ymd_opt(time.year.into(), time.month.into(), time.day.into())?.and_hms_opt(
+        time.hour.into(),
+        time.minute.into(),
+        time.second.into(),
+    )
+}
+
+/// Returns the capture time of an image in UTC from its EXIF data. The offset the camera
+/// recorded with the time is used if there is one, and `clock` otherwise.
+pub fn capture_time(path: &Path, clock: CameraClock) -> Option<DateTime<Utc>> {
+    let exif = read_exif(path)?;
+    let time = exif_time(&exif)?;
+    let recorded_clock = exif_ascii(&exif, exif::Tag::OffsetTimeOriginal)
+        .and_then(|offset| CameraClock::parse(&String::from_utf8_lossy(&offset)));
+    recorded_clock.unwrap_or(clock).to_utc(time)
+}
+
+/// Sorts `frames` by their EXIF capture time. Frames without one keep their name order
+/// after the others.
+pub fn sort_by_capture_time(frames: &mut [PathBuf], clock: CameraClock) {
+    frames.sort_by_cached_key(|frame| {
+        let time = capture_time(frame, clock);
+        (time.is_none(), time, frame.clone())
+    });
+}
+
+/// Returns the capture date of an image from its file name, or from its EXIF data otherwise.
+pub fn image_date(path: &Path) -> Option<NaiveDate> {
+    frame_date(path).or_else(|| exif_time(&read_exif(path)?).map(|time| time.date()))
+}
+
+/// Returns the source images of a config that fall into its date range.
+/// Images without a date in their file name are always included.
+pub fn source_images(config: &tree_migration::Config) -> std::io::Result<Vec<PathBuf>> {
+    Ok(image_files(&config.input_path)?
+        .into_iter()
+        .filter(|image| {
+            frame_date(image)
+                .is_none_or(|date| date >= config.start_date && date <= config.end_date)
+        })
+        .collect())
+}
+
+/// Formats one CSV line, quoting fields that contain separators, quotes or line breaks.
+pub fn csv_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+        + "\n"
+}
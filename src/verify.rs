@@ -0,0 +1,95 @@
+use crate::dates::DateRange;
+use crate::{gaps, utils, video};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the `ffprobe` binary installed next to `ffmpeg_path`.
+pub fn ffprobe_path(ffmpeg_path: &Path) -> PathBuf {
+    ffmpeg_path.with_file_name(format!("ffprobe{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// Counts the frames of the first video stream of `video_path`.
+pub fn video_frame_count(ffprobe_path: &Path, video_path: &Path) -> Result<usize, String> {
+    let output = Command::new(ffprobe_path)
+        .args(["-v", "error", "-select_streams", "v:0", "-count_packets"])
+        .args(["-show_entries", "stream=nb_read_packets", "-of", "csv=p=0"])
+        .arg(video_path)
+        .output()
+        .map_err(|e| format!("Cannot run ffprobe: {}", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("Cannot read frame count of {}", video_path.display()))
+}
+
+/// Compares the outputs of a finished job with its sources.
+/// Returns a list of discrepancies, which is empty if the job verified cleanly.
+/// `excluded_frames` are left out of the expected video, as in `runner::video_frames`, and
+/// the missing days are filled within `gap_ranges` if the video fills them.
+pub fn verify(
+    image_config: &tree_migration::Config,
+    excluded_frames: &BTreeSet<PathBuf>,
+    gap_ranges: Option<&[DateRange]>,
+    video: Option<(PathBuf, PathBuf, Option<u32>)>,
+) -> Vec<String> {
+    let mut discrepancies = Vec::new();
+    let sources = match utils::source_images(image_config) {
+        Ok(sources) => sources,
+        Err(e) => return vec![format!("Cannot scan source folder: {}", e)],
+    };
+    let outputs = match utils::image_files(&image_config.output_path) {
+        Ok(outputs) => outputs,
+        Err(e) => return vec![format!("Cannot scan output folder: {}", e)],
+    };
+
+    if sources.len() != outputs.len() {
+        discrepancies.push(format!(
+            "Expected {} frames from source, found {} outputs",
+            sources.len(),
+            outputs.len()
+        ));
+    }
+
+    let output_dates = outputs
+        .iter()
+        .filter_map(|output| utils::frame_date(output))
+        .collect::<HashSet<_>>();
+    if !output_dates.is_empty() {
+        for source in &sources {
+            if let Some(date) = utils::frame_date(source) {
+                if !output_dates.contains(&date) {
+                    discrepancies.push(format!("No output for {}", source.display()));
+                }
+            }
+        }
+    }
+
+    if let Some((ffprobe_path, video_path, target_duration)) = video {
+        let mut frames = outputs.clone();
+        frames.retain(|frame| !excluded_frames.contains(frame));
+        if let Some(date_ranges) = gap_ranges {
+            frames = gaps::with_gaps(&frames, date_ranges)
+                .into_iter()
+                .map(|(frame, _)| frame.to_path_buf())
+                .collect();
+        }
+        let expected = match target_duration {
+            Some(seconds) => video::fit_to_duration(&frames, seconds).0.len(),
+            None => frames.len(),
+        };
+        if !video_path.exists() {
+            discrepancies.push(format!("Video {} is missing", video_path.display()));
+        } else {
+            match video_frame_count(&ffprobe_path, &video_path) {
+                Ok(count) if count != expected => {
+                    discrepancies.push(format!("Video has {} frames, expected {}", count, expected))
+                }
+                Ok(_) => {}
+                Err(e) => discrepancies.push(e),
+            }
+        }
+    }
+
+    discrepancies
+}
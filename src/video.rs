@@ -0,0 +1,805 @@
+use crate::cancel::CancelToken;
+use crate::children;
+use crate::joblog::JobLog;
+use crate::utils;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Codec {
+    H264,
+    H265,
+    ProRes,
+    Vp9,
+    Gif,
+    None,
+}
+
+impl Codec {
+    pub const ENCODERS: [Codec; 5] = [
+        Codec::H264,
+        Codec::H265,
+        Codec::ProRes,
+        Codec::Vp9,
+        Codec::Gif,
+    ];
+    /// Codecs the transcoder writes into MOV or MP4.
+    pub const TRANSCODERS: [Codec; 3] = [Codec::H264, Codec::H265, Codec::ProRes];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Codec::H264 => "h.264 (MP4)",
+            Codec::H265 => "h.265 (HEVC, MP4)",
+            Codec::ProRes => "Prores (MOV)",
+            Codec::Vp9 => "VP9 (WebM)",
+            Codec::Gif => "Animated GIF",
+            Codec::None => "None",
+        }
+    }
+
+    /// Returns the name of the codec on the command line and in batch files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::H264 => "h264",
+            Codec::H265 => "h265",
+            Codec::ProRes => "prores",
+            Codec::Vp9 => "vp9",
+            Codec::Gif => "gif",
+            Codec::None => "none",
+        }
+    }
+
+    /// Parses a codec name, accepting "hevc" and "webm" as well.
+    pub fn from_name(name: &str) -> Option<Codec> {
+        match name {
+            "hevc" => Some(Codec::H265),
+            "webm" => Some(Codec::Vp9),
+            _ => [Codec::None]
+                .into_iter()
+                .chain(Codec::ENCODERS)
+                .find(|codec| codec.name() == name),
+        }
+    }
+
+    /// Returns the extension of the container a video encoded with this codec is written in.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Codec::H264 | Codec::H265 => "mp4",
+            Codec::ProRes | Codec::None => "mov",
+            Codec::Vp9 => "webm",
+            Codec::Gif => "gif",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum H264Profile {
+    Baseline,
+    Main,
+    High,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Preset {
+    Ultrafast,
+    Veryfast,
+    Fast,
+    Medium,
+    Slow,
+    Veryslow,
+}
+
+/// How the size of h.264 and HEVC streams is chosen.
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum RateControl {
+    /// Constant quality, the size follows the content.
+    Quality,
+    /// Average bitrate, for a predictable size.
+    Bitrate,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum ProResProfile {
+    Proxy,
+    Lt,
+    Standard,
+    Hq,
+    P4444,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 6] = [
+        Preset::Ultrafast,
+        Preset::Veryfast,
+        Preset::Fast,
+        Preset::Medium,
+        Preset::Slow,
+        Preset::Veryslow,
+    ];
+
+    /// Returns the preset name understood by x264 and x265.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Ultrafast => "ultrafast",
+            Preset::Veryfast => "veryfast",
+            Preset::Fast => "fast",
+            Preset::Medium => "medium",
+            Preset::Slow => "slow",
+            Preset::Veryslow => "veryslow",
+        }
+    }
+}
+
+pub const H264_LEVELS: [&str; 10] = [
+    "", "3.0", "3.1", "4.0", "4.1", "4.2", "5.0", "5.1", "5.2", "6.0",
+];
+
+/// Widest video encoded with adaptive settings, larger frames are scaled down to it.
+const ADAPTIVE_MAX_WIDTH: u32 = 3840;
+/// h.264 bitrates in kbit/s by the largest number of pixels per frame they are used for,
+/// with the h.264 level those frames need. HEVC uses about half the bitrate.
+const BITRATE_LADDER: [(u64, u32, &str); 5] = [
+    (1280 * 720, 5_000, "3.1"),
+    (1920 * 1080, 8_000, "4.1"),
+    (2560 * 1440, 16_000, "5.0"),
+    (4096 * 2304, 35_000, "5.1"),
+    (u64::MAX, 50_000, "6.0"),
+];
+
+/// Encode settings picked for a job from the resolution of its source images.
+#[derive(Clone, PartialEq)]
+pub struct AdaptiveEncode {
+    pub source: (u32, u32),
+    /// Resolution of the video if the source images are scaled down.
+    pub scaled: Option<(u32, u32)>,
+    /// h.264 level, if the codec is h.264.
+    pub level: Option<&'static str>,
+    /// Average bitrate in kbit/s.
+    pub bitrate: u32,
+}
+
+impl AdaptiveEncode {
+    /// Picks the settings for `codec` from the resolution of the source images, or `None` if
+    /// the codec does not take them. The level of the Baseline profile is left as set, as it
+    /// is limited to 3.1.
+    pub fn new(codec: Codec, options: &CodecOptions, (width, height): (u32, u32)) -> Option<Self> {
+        if !matches!(codec, Codec::H264 | Codec::H265) || width == 0 || height == 0 {
+            return None;
+        }
+        let scaled = (width > ADAPTIVE_MAX_WIDTH).then(|| {
+            let scaled_height = (height as u64 * ADAPTIVE_MAX_WIDTH as u64 / width as u64) as u32;
+            (ADAPTIVE_MAX_WIDTH, scaled_height / 2 * 2)
+        });
+        let (output_width, output_height) = scaled.unwrap_or((width, height));
+        let pixels = output_width as u64 * output_height as u64;
+        let (_, bitrate, level) = BITRATE_LADDER
+            .into_iter()
+            .find(|(max_pixels, ..)| pixels <= *max_pixels)?;
+        Some(Self {
+            source: (width, height),
+            scaled,
+            level: (codec == Codec::H264 && options.h264_profile != H264Profile::Baseline)
+                .then_some(level),
+            bitrate: if codec == Codec::H265 {
+                bitrate / 2
+            } else {
+                bitrate
+            },
+        })
+    }
+
+    /// Returns `options` with the picked settings.
+    pub fn apply(&self, options: &CodecOptions) -> CodecOptions {
+        CodecOptions {
+            h264_level: self.level.map_or(options.h264_level.clone(), str::to_owned),
+            rate_control: RateControl::Bitrate,
+            bitrate: self.bitrate,
+            scale_width: self.scaled.map(|(width, _)| width),
+            ..options.clone()
+        }
+    }
+
+    /// Describes the settings, e.g. "6000×4000 scaled to 3840×2560, level 5.1, 35000 kbit/s".
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("{}×{}", self.source.0, self.source.1)];
+        if let Some((width, height)) = self.scaled {
+            parts[0] += format!(" scaled to {}×{}", width, height).as_str();
+        }
+        if let Some(level) = self.level {
+            parts.push(format!("level {}", level));
+        }
+        parts.push(format!("{} kbit/s", self.bitrate));
+        parts.join(", ")
+    }
+}
+
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CodecOptions {
+    pub h264_profile: H264Profile,
+    /// An empty level lets the encoder choose.
+    pub h264_level: String,
+    pub h264_preset: Preset,
+    pub hevc_preset: Preset,
+    pub rate_control: RateControl,
+    /// Constant quality of h.264, from 0 (lossless) to 51.
+    pub h264_crf: u32,
+    /// Constant quality of HEVC, from 0 (lossless) to 51.
+    pub hevc_crf: u32,
+    /// Average bitrate of h.264 and HEVC in kbit/s.
+    pub bitrate: u32,
+    /// Encodes h.264 at the average bitrate in two passes, which distributes it better over
+    /// the video.
+    pub h264_two_pass: bool,
+    /// Tags HEVC streams as `hvc1`, which QuickTime and iOS require, instead of `hev1`.
+    pub hevc_apple_tag: bool,
+    pub hevc_ten_bit: bool,
+    pub prores_profile: ProResProfile,
+    /// Four character vendor id written into ProRes streams, `apl0` is what Apple encoders use.
+    pub prores_vendor: String,
+    /// Constant quality of VP9, from 0 (lossless) to 63.
+    pub vp9_crf: u32,
+    /// Width h.264 and HEVC videos are scaled down to, set per job by `AdaptiveEncode`.
+    #[serde(skip)]
+    pub scale_width: Option<u32>,
+}
+
+impl Default for CodecOptions {
+    fn default() -> Self {
+        Self {
+            h264_profile: H264Profile::High,
+            h264_level: String::new(),
+            h264_preset: Preset::Medium,
+            hevc_preset: Preset::Medium,
+            rate_control: RateControl::Quality,
+            h264_crf: 23,
+            hevc_crf: 28,
+            bitrate: 8000,
+            h264_two_pass: false,
+            hevc_apple_tag: true,
+            hevc_ten_bit: false,
+            prores_profile: ProResProfile::Hq,
+            prores_vendor: "apl0".to_owned(),
+            vp9_crf: 31,
+            scale_width: None,
+        }
+    }
+}
+
+impl CodecOptions {
+    pub fn validate(&self, codec: Codec) -> Result<(), String> {
+        if matches!(codec, Codec::H264 | Codec::H265) {
+            match self.rate_control {
+                RateControl::Quality if self.crf(codec) > 51 => {
+                    return Err("Quality must be between 0 and 51".to_owned())
+                }
+                RateControl::Bitrate if self.bitrate == 0 => {
+                    return Err("Bitrate must be above 0".to_owned())
+                }
+                _ => {}
+            }
+        }
+        match codec {
+            Codec::H264 => {
+                if !H264_LEVELS.contains(&self.h264_level.as_str()) {
+                    return Err(format!("Unknown h.264 level {}", self.h264_level));
+                }
+                if self.h264_profile == H264Profile::Baseline
+                    && self
+                        .h264_level
+                        .parse::<f32>()
+                        .is_ok_and(|level| level > 3.1)
+                {
+                    return Err("Baseline profile is limited to level 3.1".to_owned());
+                }
+            }
+            Codec::H265 => {}
+            Codec::ProRes => {
+                if self.prores_vendor.len() != 4 || !self.prores_vendor.is_ascii() {
+                    return Err("ProRes vendor must be four ASCII characters".to_owned());
+                }
+            }
+            Codec::Vp9 => {
+                if self.vp9_crf > 63 {
+                    return Err("VP9 quality must be between 0 and 63".to_owned());
+                }
+            }
+            Codec::Gif | Codec::None => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the constant quality setting of h.264 or HEVC.
+    fn crf(&self, codec: Codec) -> u32 {
+        if codec == Codec::H265 {
+            self.hevc_crf
+        } else {
+            self.h264_crf
+        }
+    }
+
+    /// Returns warnings for option combinations that will not play on macOS and iOS.
+    pub fn compatibility_warnings(&self, codec: Codec) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if codec == Codec::H265 {
+            if !self.hevc_apple_tag {
+                warnings.push(
+                    "Without the hvc1 tag HEVC will not play in QuickTime or on iOS".to_owned(),
+                );
+            }
+            if self.hevc_ten_bit {
+                warnings.push(
+                    "10-bit HEVC requires macOS 10.13 or iOS 11 and hardware decoding support"
+                        .to_owned(),
+                );
+            }
+        }
+        warnings
+    }
+
+    /// Returns the ffmpeg output arguments selecting and configuring the encoder for `codec`.
+    pub fn args(&self, codec: Codec) -> Vec<String> {
+        let vp9_crf = self.vp9_crf.to_string();
+        let scale = self
+            .scale_width
+            .map(|width| format!("scale={}:-2:flags=lanczos", width));
+        let (rate_flag, rate) = match self.rate_control {
+            RateControl::Quality => ("-crf", self.crf(codec).to_string()),
+            RateControl::Bitrate => ("-b:v", format!("{}k", self.bitrate)),
+        };
+        let args: Vec<&str> = match codec {
+            Codec::H264 => {
+                let mut args = vec![
+                    "-c:v",
+                    "libx264",
+                    "-pix_fmt",
+                    "yuv420p",
+                    "-profile:v",
+                    match self.h264_profile {
+                        H264Profile::Baseline => "baseline",
+                        H264Profile::Main => "main",
+                        H264Profile::High => "high",
+                    },
+                    "-preset",
+                    self.h264_preset.name(),
+                ];
+                if !self.h264_level.is_empty() {
+                    args.extend(["-level:v", self.h264_level.as_str()]);
+                }
+                args.extend([rate_flag, rate.as_str()]);
+                if let Some(scale) = &scale {
+                    args.extend(["-vf", scale.as_str()]);
+                }
+                args
+            }
+            Codec::H265 => {
+                let mut args = vec![
+                    "-c:v",
+                    "libx265",
+                    "-pix_fmt",
+                    if self.hevc_ten_bit {
+                        "yuv420p10le"
+                    } else {
+                        "yuv420p"
+                    },
+                    "-preset",
+                    self.hevc_preset.name(),
+                    "-tag:v",
+                    if self.hevc_apple_tag { "hvc1" } else { "hev1" },
+                ];
+                args.extend([rate_flag, rate.as_str()]);
+                if let Some(scale) = &scale {
+                    args.extend(["-vf", scale.as_str()]);
+                }
+                args
+            }
+            Codec::ProRes => {
+                let profile = match self.prores_profile {
+                    ProResProfile::Proxy => "0",
+                    ProResProfile::Lt => "1",
+                    ProResProfile::Standard => "2",
+                    ProResProfile::Hq => "3",
+                    ProResProfile::P4444 => "4",
+                };
+                let pixel_format = if self.prores_profile == ProResProfile::P4444 {
+                    "yuva444p10le"
+                } else {
+                    "yuv422p10le"
+                };
+                vec![
+                    "-c:v",
+                    "prores_ks",
+                    "-profile:v",
+                    profile,
+                    "-vendor",
+                    self.prores_vendor.as_str(),
+                    "-pix_fmt",
+                    pixel_format,
+                ]
+            }
+            Codec::Vp9 => vec![
+                "-c:v",
+                "libvpx-vp9",
+                "-pix_fmt",
+                "yuv420p",
+                "-crf",
+                vp9_crf.as_str(),
+                "-b:v",
+                "0",
+                "-row-mt",
+                "1",
+            ],
+            Codec::Gif => vec![
+                "-filter_complex",
+                "split[a][b];[a]palettegen[p];[b][p]paletteuse",
+                "-loop",
+                "0",
+            ],
+            Codec::None => vec![],
+        };
+        args.into_iter().map(str::to_owned).collect()
+    }
+}
+
+/// Receives the completed fraction of an encode, from 0 to 1.
+pub type ProgressCallback = Arc<dyn Fn(f32) + Send + Sync>;
+
+/// Where an encode keeps its temporary files, how it is cancelled and where it reports progress
+/// and the output of ffmpeg.
+#[derive(Clone, Default)]
+pub struct EncodeContext {
+    pub temp_dir: Option<PathBuf>,
+    pub cancel: CancelToken,
+    pub on_progress: Option<ProgressCallback>,
+    pub environment: ProcessEnvironment,
+    pub log: JobLog,
+}
+
+/// Environment variables and working directory ffmpeg and the hooks run with, e.g. for proxy
+/// settings or credentials files.
+/// They are saved in plain text with the other settings, so secrets are better kept in a
+/// credentials file that a variable points to.
+#[derive(Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ProcessEnvironment {
+    pub variables: Vec<(String, String)>,
+    /// `None` keeps the working directory of the app.
+    pub working_dir: Option<PathBuf>,
+}
+
+impl ProcessEnvironment {
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.working_dir.is_none()
+    }
+
+    pub fn apply(&self, command: &mut Command) {
+        command.envs(
+            self.variables
+                .iter()
+                .filter(|(name, _)| !name.trim().is_empty())
+                .map(|(name, value)| (name.trim(), value)),
+        );
+        if let Some(working_dir) = &self.working_dir {
+            command.current_dir(working_dir);
+        }
+    }
+
+    /// Describes the environment for the log. Values are left out as they may be secrets.
+    pub fn describe(&self) -> String {
+        let names = self
+            .variables
+            .iter()
+            .map(|(name, _)| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        let mut description = if names.is_empty() {
+            "no extra variables".to_owned()
+        } else {
+            format!("variables {}", names.join(", "))
+        };
+        if let Some(working_dir) = &self.working_dir {
+            description += format!(" in {}", working_dir.display()).as_str();
+        }
+        description
+    }
+}
+
+/// Runs ffmpeg and returns the last line it logged if it fails. Everything it logged is added
+/// to `log`.
+/// ffmpeg is killed if `cancel` is triggered while it runs, and is registered as a child
+/// process of the app until it exits.
+/// With `progress`, the command must write `-progress pipe:1` output, which is reported
+/// relative to the given number of frames.
+pub fn run_ffmpeg(
+    mut command: Command,
+    cancel: &CancelToken,
+    log: &JobLog,
+    progress: Option<(usize, ProgressCallback)>,
+) -> Result<(), String> {
+    let on_stdout_line = progress.map(|(frames, on_progress)| {
+        Box::new(move |line: &str| {
+            if let Some(Ok(frame)) = line.strip_prefix("frame=").map(str::parse::<usize>) {
+                on_progress((frame as f32 / frames.max(1) as f32).min(1.0));
+            }
+        }) as children::LineCallback
+    });
+    let finished = children::run(&mut command, cancel, on_stdout_line).map_err(|e| match e {
+        children::RunError::Spawn(e) => format!("Cannot run ffmpeg: {}", e),
+        children::RunError::Wait(e) => format!("Cannot wait for ffmpeg: {}", e),
+        children::RunError::Cancelled => "Cancelled".to_owned(),
+    })?;
+    log.output("ffmpeg", &finished.stderr);
+    if finished.status.success() {
+        Ok(())
+    } else {
+        Err(finished
+            .stderr
+            .lines()
+            .last()
+            .unwrap_or("ffmpeg failed")
+            .to_owned())
+    }
+}
+
+/// Writes an ffconcat list of `frames` into `temp_dir`, or next to `output` without one.
+fn write_frame_list(
+    frames: &[PathBuf],
+    output: &Path,
+    temp_dir: Option<&Path>,
+) -> std::io::Result<PathBuf> {
+    let mut list = String::from("ffconcat version 1.0\n");
+    for frame in frames {
+        list += format!(
+            "file '{}'\n",
+            std::path::absolute(frame)?
+                .display()
+                .to_string()
+                .replace('\'', "'\\''")
+        )
+        .as_str();
+    }
+    let list_path = match (temp_dir, output.file_stem()) {
+        (Some(temp_dir), Some(stem)) => {
+            std::fs::create_dir_all(temp_dir)?;
+            temp_dir.join(stem).with_extension("ffconcat")
+        }
+        _ => output.with_extension("ffconcat"),
+    };
+    std::fs::write(&list_path, list)?;
+    Ok(list_path)
+}
+
+/// Highest frame rate used when fitting a video to a target duration.
+pub const MAX_FRAME_RATE: f64 = 25.0;
+
+/// Chooses the frame rate, and if necessary evenly spaced frames, so that
+/// `frames` play for `seconds`.
+pub fn fit_to_duration(frames: &[PathBuf], seconds: u32) -> (Vec<PathBuf>, f64) {
+    let seconds = seconds.max(1) as f64;
+    let frame_rate = frames.len() as f64 / seconds;
+    if frame_rate <= MAX_FRAME_RATE {
+        return (frames.to_vec(), frame_rate);
+    }
+    let count = (seconds * MAX_FRAME_RATE) as usize;
+    let step = frames.len() as f64 / count as f64;
+    let decimated = (0..count)
+        .map(|i| frames[(i as f64 * step) as usize].clone())
+        .collect();
+    (decimated, MAX_FRAME_RATE)
+}
+
+/// Describes how much real time one second of a video covers when its `frames` at
+/// `frame_rate` span `days` days, e.g. "1 s of video = 3.5 days".
+pub fn describe_speed(days: i64, frames: usize, frame_rate: f64) -> Option<String> {
+    if days <= 0 || frames == 0 || frame_rate <= 0.0 {
+        return None;
+    }
+    let days_per_second = days as f64 * frame_rate / frames as f64;
+    let (amount, unit) = if days_per_second < 1.0 {
+        (days_per_second * 24.0, "hours")
+    } else {
+        (days_per_second, "days")
+    };
+    let precision = if amount < 10.0 { 1 } else { 0 };
+    Some(format!("1 s of video = {:.*} {}", precision, amount, unit))
+}
+
+/// Encodes `frames` in the given order into a video at `output`.
+pub fn encode_frames(
+    ffmpeg_path: &Path,
+    frames: &[PathBuf],
+    frame_rate: f64,
+    codec: Codec,
+    options: &CodecOptions,
+    output: &Path,
+    context: &EncodeContext,
+) -> Result<(), String> {
+    options.validate(codec)?;
+    if frames.is_empty() {
+        return Err("No frames to encode".to_owned());
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let list_path =
+        write_frame_list(frames, output, context.temp_dir.as_deref()).map_err(|e| e.to_string())?;
+    let is_two_pass = codec == Codec::H264
+        && options.rate_control == RateControl::Bitrate
+        && options.h264_two_pass;
+    let passes = if is_two_pass {
+        vec![Some("1"), Some("2")]
+    } else {
+        vec![None]
+    };
+    let pass_log = list_path.with_extension("passlog");
+    let mut result = Ok(());
+    for (i, pass) in passes.iter().enumerate() {
+        let mut command = Command::new(ffmpeg_path);
+        context.environment.apply(&mut command);
+        if context.on_progress.is_some() {
+            command.args(["-progress", "pipe:1", "-nostats"]);
+        }
+        command
+            .args(["-y", "-v", "error", "-r", frame_rate.to_string().as_str()])
+            .args(["-f", "concat", "-safe", "0", "-i"])
+            .arg(&list_path)
+            .args(options.args(codec))
+            .args(["-r", frame_rate.to_string().as_str()]);
+        if let Some(pass) = pass {
+            command.args(["-pass", pass, "-passlogfile"]).arg(&pass_log);
+        }
+        if *pass == Some("1") {
+            // The first pass only writes the statistics the second one encodes with.
+            command.args(["-an", "-f", "null", "-"]);
+        } else {
+            command.arg(output);
+        }
+        let progress = context.on_progress.clone().map(|on_progress| {
+            let (offset, share) = (i as f32, passes.len() as f32);
+            let on_progress: ProgressCallback =
+                Arc::new(move |fraction| on_progress((offset + fraction) / share));
+            (frames.len(), on_progress)
+        });
+        result = run_ffmpeg(command, &context.cancel, &context.log, progress);
+        if result.is_err() {
+            break;
+        }
+    }
+    let _ = std::fs::remove_file(list_path);
+    if is_two_pass {
+        for suffix in ["-0.log", "-0.log.mbtree"] {
+            let mut path = pass_log.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    result
+}
+
+/// Encodes `frames` quickly into a small h.264 video, 640 px wide, at `output`.
+pub fn encode_proxy(
+    ffmpeg_path: &Path,
+    frames: &[PathBuf],
+    frame_rate: f64,
+    output: &Path,
+    context: &EncodeContext,
+) -> Result<(), String> {
+    if frames.is_empty() {
+        return Err("No frames to encode".to_owned());
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let list_path =
+        write_frame_list(frames, output, context.temp_dir.as_deref()).map_err(|e| e.to_string())?;
+    let mut command = Command::new(ffmpeg_path);
+    context.environment.apply(&mut command);
+    if context.on_progress.is_some() {
+        command.args(["-progress", "pipe:1", "-nostats"]);
+    }
+    command
+        .args(["-y", "-v", "error", "-r", frame_rate.to_string().as_str()])
+        .args(["-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-vf", "scale=640:-2:flags=fast_bilinear"])
+        .args(["-c:v", "libx264", "-preset", "ultrafast", "-crf", "32"])
+        .args(["-pix_fmt", "yuv420p"])
+        .args(["-r", frame_rate.to_string().as_str()])
+        .arg(output);
+    let progress = context
+        .on_progress
+        .clone()
+        .map(|on_progress| (frames.len(), on_progress));
+    let result = run_ffmpeg(command, &context.cancel, &context.log, progress);
+    let _ = std::fs::remove_file(list_path);
+    result
+}
+
+/// Number of evenly spaced frames in a preview animation.
+pub const PREVIEW_FRAMES: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum PreviewFormat {
+    Gif,
+    Webp,
+}
+
+impl PreviewFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PreviewFormat::Gif => "gif",
+            PreviewFormat::Webp => "webp",
+        }
+    }
+}
+
+/// Encodes a small looping preview of `frames`, 480 px wide, at `output`.
+pub fn encode_preview(
+    ffmpeg_path: &Path,
+    frames: &[PathBuf],
+    format: PreviewFormat,
+    output: &Path,
+    context: &EncodeContext,
+) -> Result<(), String> {
+    if frames.is_empty() {
+        return Err("No frames to encode".to_owned());
+    }
+    let step = (frames.len() as f64 / PREVIEW_FRAMES as f64).max(1.0);
+    let frames = (0..PREVIEW_FRAMES.min(frames.len()))
+        .map(|i| frames[(i as f64 * step) as usize].clone())
+        .collect::<Vec<_>>();
+    let list_path = write_frame_list(&frames, output, context.temp_dir.as_deref())
+        .map_err(|e| e.to_string())?;
+    let mut command = Command::new(ffmpeg_path);
+    context.environment.apply(&mut command);
+    command
+        .args(["-y", "-v", "error", "-r", "5"])
+        .args(["-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path);
+    match format {
+        PreviewFormat::Gif => command.args([
+            "-filter_complex",
+            "scale=480:-2:flags=lanczos,split[a][b];[a]palettegen[p];[b][p]paletteuse",
+        ]),
+        PreviewFormat::Webp => command
+            .args(["-vf", "scale=480:-2:flags=lanczos"])
+            .args(["-c:v", "libwebp", "-q:v", "70"]),
+    };
+    command.args(["-loop", "0", "-r", "5"]).arg(output);
+    let result = run_ffmpeg(command, &context.cancel, &context.log, None);
+    let _ = std::fs::remove_file(list_path);
+    result
+}
+
+/// Decodes every `step`th frame of `video`, scaled to `width`, into JPEGs in `dir` for
+/// previewing it and returns them in order. Decoding stops after `max_frames` frames.
+pub fn extract_frames(
+    ffmpeg_path: &Path,
+    video: &Path,
+    width: u32,
+    (step, max_frames): (usize, usize),
+    dir: &Path,
+    context: &EncodeContext,
+) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut command = Command::new(ffmpeg_path);
+    context.environment.apply(&mut command);
+    command
+        .args(["-y", "-v", "error", "-i"])
+        .arg(video)
+        .args([
+            "-vf",
+            format!("select='not(mod(n,{}))',scale={}:-2", step.max(1), width).as_str(),
+        ])
+        .args([
+            "-vsync",
+            "vfr",
+            "-frames:v",
+            max_frames.to_string().as_str(),
+        ])
+        .args(["-q:v", "4"])
+        .arg(dir.join("%06d.jpg"));
+    run_ffmpeg(command, &context.cancel, &context.log, None)?;
+    utils::image_files(dir).map_err(|e| e.to_string())
+}
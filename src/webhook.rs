@@ -0,0 +1,92 @@
+use crate::queue::HistoryEntry;
+use std::time::Duration;
+
+/// Attempts at delivering a payload before it is dropped.
+const ATTEMPTS: u32 = 4;
+/// Wait before the first retry, doubled for every further one.
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn job(entry: &HistoryEntry) -> serde_json::Value {
+    serde_json::json!({
+        "config_path": entry.config_path,
+        "location": entry.location,
+        "camera": entry.camera,
+        "start_date": entry.start_date.to_string(),
+        "end_date": entry.end_date.to_string(),
+        "status": entry.status,
+        "duration": entry.duration,
+        "outputs": entry.outputs,
+    })
+}
+
+/// Returns the payload announcing that the job of `entry` finished.
+pub fn job_finished(entry: &HistoryEntry) -> serde_json::Value {
+    serde_json::json!({
+        "event": "job_finished",
+        "finished_at": entry.finished_at.to_rfc3339(),
+        "job": job(entry),
+    })
+}
+
+/// Returns the payload announcing that the batch of `entries` finished with `done` jobs
+/// delivered, `failed` jobs failed and `cancelled` jobs cancelled. It is sent whether the
+/// batch succeeded or not.
+pub fn batch_finished(
+    entries: &[HistoryEntry],
+    done: usize,
+    failed: usize,
+    cancelled: usize,
+) -> serde_json::Value {
+    serde_json::json!({
+        "event": "batch_finished",
+        "finished_at": chrono::Local::now().fixed_offset().to_rfc3339(),
+        "status": if failed == 0 { "success" } else { "failure" },
+        "done": done,
+        "failed": failed,
+        "cancelled": cancelled,
+        "jobs": entries.iter().map(job).collect::<Vec<_>>(),
+    })
+}
+
+/// Returns the payload announcing that the batch stopped after `failures` jobs failed in a
+/// row with `last_error` the error of the last of them, cancelling `cancelled` queued jobs.
+pub fn batch_stopped(failures: u32, cancelled: usize, last_error: &str) -> serde_json::Value {
+    serde_json::json!({
+        "event": "batch_stopped",
+        "stopped_at": chrono::Local::now().fixed_offset().to_rfc3339(),
+        "consecutive_failures": failures,
+        "cancelled": cancelled,
+        "last_error": last_error,
+    })
+}
+
+/// POSTs `payload` as JSON to `url`. Network errors, rate limiting and server errors are
+/// retried with exponential backoff, other responses are not.
+pub fn post(url: &str, payload: &serde_json::Value) -> Result<(), String> {
+    let body = payload.to_string();
+    let mut backoff = FIRST_BACKOFF;
+    for attempt in 1..=ATTEMPTS {
+        let result = ureq::post(url.trim())
+            .timeout(TIMEOUT)
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+        let error = match result {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(status, _)) if status != 429 && status < 500 => {
+                return Err(format!("The webhook answered {}", status));
+            }
+            Err(e) => e,
+        };
+        if attempt == ATTEMPTS {
+            return Err(format!(
+                "Cannot reach the webhook after {} attempts: {}",
+                ATTEMPTS, error
+            ));
+        }
+        log::debug!("Retrying the webhook in {:?}: {}", backoff, error);
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+    Ok(())
+}
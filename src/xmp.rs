@@ -0,0 +1,49 @@
+use crate::utils;
+use std::path::Path;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn sidecar(keywords: &[String]) -> String {
+    let items = keywords
+        .iter()
+        .map(|keyword| format!("     <rdf:li>{}</rdf:li>\n", escape(keyword)))
+        .collect::<String>();
+    format!(
+        r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+   <dc:subject>
+    <rdf:Bag>
+{}    </rdf:Bag>
+   </dc:subject>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>
+"#,
+        items
+    )
+}
+
+/// Writes a `<frame>.<ext>.xmp` sidecar tagged with `keywords` for every frame in `dir`.
+/// Existing sidecars are left untouched. Returns the number of written sidecars.
+pub fn write_sidecars(dir: &Path, keywords: &[String]) -> std::io::Result<usize> {
+    let contents = sidecar(keywords);
+    let mut written = 0;
+    for frame in utils::image_files(dir)? {
+        let mut path = frame.into_os_string();
+        path.push(".xmp");
+        if Path::new(&path).exists() {
+            continue;
+        }
+        std::fs::write(&path, &contents)?;
+        written += 1;
+    }
+    Ok(written)
+}